@@ -0,0 +1,73 @@
+use std::ops::RangeInclusive;
+
+use common_game::utils::ID;
+
+/// How the planet treats an explorer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Tier {
+    /// Served first, and warned through the covert channel, see [`CovertConfig`](crate::CovertConfig).
+    Ally,
+    /// Served with the energy left over by the allies.
+    #[default]
+    Neutral,
+    /// Offered no combinations, and Carbon only with the energy left over by the allies.
+    Rival,
+}
+
+/// Alliance registry, set when the planet is created.
+///
+/// Explorers are listed by ID or by range of IDs; the first entry containing
+/// an explorer gives its tier, and unlisted explorers are [`Tier::Neutral`].
+/// The default registry is empty with no reserve, so everybody is served alike.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{AllianceConfig, Tier};
+///
+/// let alliance = AllianceConfig::default()
+///     .member(3, Tier::Ally)
+///     .members(10..=19, Tier::Ally)
+///     .members(100..=199, Tier::Rival)
+///     .reserve(1);
+/// assert_eq!(alliance.tier(12), Tier::Ally);
+/// assert_eq!(alliance.tier(150), Tier::Rival);
+/// assert_eq!(alliance.tier(50), Tier::Neutral);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllianceConfig {
+    pub entries: Vec<(RangeInclusive<ID>, Tier)>,
+    /// Charged cells kept for the allies: other explorers are only served
+    /// while more cells than this are charged.
+    pub reserve: usize,
+}
+
+impl AllianceConfig {
+    pub fn member(self, explorer_id: ID, tier: Tier) -> AllianceConfig {
+        self.members(explorer_id..=explorer_id, tier)
+    }
+
+    pub fn members(mut self, explorers: RangeInclusive<ID>, tier: Tier) -> AllianceConfig {
+        self.entries.push((explorers, tier));
+        self
+    }
+
+    pub fn reserve(mut self, cells: usize) -> AllianceConfig {
+        self.reserve = cells;
+        self
+    }
+
+    pub fn tier(&self, explorer_id: ID) -> Tier {
+        self.entries
+            .iter()
+            .find(|(explorers, _)| explorers.contains(&explorer_id))
+            .map_or(Tier::Neutral, |(_, tier)| *tier)
+    }
+
+    /// Whether an explorer of the tier may spend energy with `charged` cells charged.
+    ///
+    /// Without any charged cell the request fails anyway, for lack of energy.
+    pub(crate) fn may_spend(&self, tier: Tier, charged: usize) -> bool {
+        tier == Tier::Ally || charged == 0 || charged > self.reserve
+    }
+}
@@ -0,0 +1,339 @@
+//! Adaptive choice of the sunray and asteroid policies.
+//!
+//! Every pair of policies is an arm of a multi-armed bandit. An episode lasts
+//! from an asteroid to the next one: it's rewarded for the explorer requests
+//! served meanwhile and for surviving the asteroid that closes it, then the
+//! next arm is picked epsilon-greedy with a seeded RNG, so a game replayed
+//! with the same seed makes the same choices.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::rng::Rng;
+
+/// How the planet spends sunrays when no rocket is ready.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SunrayPolicy {
+    /// Charge the cells, building a rocket only when they are all charged.
+    #[default]
+    Hoard,
+    /// Build a rocket from a charged cell before charging another one.
+    RocketFirst,
+}
+
+/// How the planet answers an asteroid when no rocket is ready.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AsteroidPolicy {
+    /// Build a rocket from a charged cell on the spot.
+    #[default]
+    BuildOnDemand,
+    /// Only launch a rocket built in advance, keeping the cells for explorers.
+    ReadyOnly,
+}
+
+impl SunrayPolicy {
+    const ALL: [SunrayPolicy; 2] = [SunrayPolicy::Hoard, SunrayPolicy::RocketFirst];
+
+    fn name(&self) -> &'static str {
+        match self {
+            SunrayPolicy::Hoard => "hoard",
+            SunrayPolicy::RocketFirst => "rocket_first",
+        }
+    }
+}
+
+impl AsteroidPolicy {
+    const ALL: [AsteroidPolicy; 2] = [AsteroidPolicy::BuildOnDemand, AsteroidPolicy::ReadyOnly];
+
+    fn name(&self) -> &'static str {
+        match self {
+            AsteroidPolicy::BuildOnDemand => "build_on_demand",
+            AsteroidPolicy::ReadyOnly => "ready_only",
+        }
+    }
+}
+
+/// Tuning of the bandit.
+///
+/// The bandit is disabled by default: the planet then always plays the
+/// default policies, still recording their statistics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BanditConfig {
+    pub enabled: bool,
+    /// Probability of exploring a random arm instead of the best one.
+    pub epsilon: f64,
+    pub seed: u64,
+    /// Reward for an asteroid survived.
+    pub survival_reward: f64,
+    /// Reward for every explorer request served.
+    pub served_reward: f64,
+    /// Statistics learned in previous games, see [`BanditStats::load`].
+    /// Ignored unless they list every arm exactly once.
+    pub prior: Option<BanditStats>,
+}
+
+impl Default for BanditConfig {
+    fn default() -> Self {
+        BanditConfig {
+            enabled: false,
+            epsilon: 0.1,
+            seed: 0,
+            survival_reward: 10.0,
+            served_reward: 1.0,
+            prior: None,
+        }
+    }
+}
+
+impl BanditConfig {
+    /// A bandit that always plays the given policies.
+    ///
+    /// Every arm counts as played once, and only the chosen one was rewarded.
+    /// Rewards are never negative by default, so it keeps the best mean.
+    pub fn fixed(sunray: SunrayPolicy, asteroid: AsteroidPolicy) -> BanditConfig {
+        let mut prior = BanditStats::default();
+        for arm in prior.arms.iter_mut() {
+            arm.pulls = 1;
+            if arm.sunray == sunray && arm.asteroid == asteroid {
+                arm.total_reward = 1.0;
+            }
+        }
+        BanditConfig {
+            enabled: true,
+            epsilon: 0.0,
+            prior: Some(prior),
+            ..BanditConfig::default()
+        }
+    }
+}
+
+/// What an arm earned so far.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArmStats {
+    pub sunray: SunrayPolicy,
+    pub asteroid: AsteroidPolicy,
+    /// Episodes played with this arm.
+    pub pulls: u32,
+    pub total_reward: f64,
+}
+
+impl ArmStats {
+    /// Average reward per episode, 0 for an arm never played.
+    pub fn mean(&self) -> f64 {
+        if self.pulls == 0 {
+            return 0.0;
+        }
+        self.total_reward / self.pulls as f64
+    }
+}
+
+/// Learned statistics of every arm, as exposed in the [`PlanetReport`](crate::PlanetReport).
+///
+/// They are persisted as plain text, one arm per line:
+///
+/// ```text
+/// # air_fryer bandit v1
+/// hoard build_on_demand 12 130.5
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BanditStats {
+    pub arms: Vec<ArmStats>,
+    /// Index in [`arms`](Self::arms) of the arm being played.
+    pub current: usize,
+}
+
+const HEADER: &str = "# air_fryer bandit v1";
+
+impl Default for BanditStats {
+    fn default() -> Self {
+        let arms = SunrayPolicy::ALL
+            .into_iter()
+            .flat_map(|sunray| {
+                AsteroidPolicy::ALL
+                    .into_iter()
+                    .map(move |asteroid| ArmStats {
+                        sunray,
+                        asteroid,
+                        pulls: 0,
+                        total_reward: 0.0,
+                    })
+            })
+            .collect();
+        BanditStats { arms, current: 0 }
+    }
+}
+
+impl BanditStats {
+    /// The arm being played.
+    pub fn current_arm(&self) -> &ArmStats {
+        &self.arms[self.current]
+    }
+
+    /// Whether every arm is there exactly once, in any order.
+    fn is_complete(&self) -> bool {
+        let default = BanditStats::default();
+        self.arms.len() == default.arms.len()
+            && default.arms.iter().all(|expected| {
+                self.arms
+                    .iter()
+                    .filter(|arm| {
+                        arm.sunray == expected.sunray && arm.asteroid == expected.asteroid
+                    })
+                    .count()
+                    == 1
+            })
+    }
+
+    /// Parses statistics written by [`to_string`](ToString::to_string).
+    ///
+    /// Arms missing from the text start from scratch; the current arm is not persisted.
+    pub fn parse(text: &str) -> Result<BanditStats, String> {
+        let mut stats = BanditStats::default();
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            _ => return Err(format!("line 1: expected \"{}\"", HEADER)),
+        }
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [sunray, asteroid, pulls, total_reward] = fields[..] else {
+                return Err(format!(
+                    "line {}: expected 4 fields, found {}",
+                    index + 1,
+                    fields.len()
+                ));
+            };
+            let arm = stats
+                .arms
+                .iter_mut()
+                .find(|arm| arm.sunray.name() == sunray && arm.asteroid.name() == asteroid)
+                .ok_or_else(|| {
+                    format!("line {}: unknown arm {} {}", index + 1, sunray, asteroid)
+                })?;
+            arm.pulls = pulls
+                .parse()
+                .map_err(|_| format!("line {}: invalid pulls {:?}", index + 1, pulls))?;
+            arm.total_reward = total_reward
+                .parse()
+                .map_err(|_| format!("line {}: invalid reward {:?}", index + 1, total_reward))?;
+        }
+        Ok(stats)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<BanditStats, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("can't read {}: {}", path.display(), err))?;
+        BanditStats::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
+
+impl fmt::Display for BanditStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for arm in &self.arms {
+            writeln!(
+                f,
+                "{} {} {} {}",
+                arm.sunray.name(),
+                arm.asteroid.name(),
+                arm.pulls,
+                arm.total_reward
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Epsilon-greedy bandit driving the [`PlanetAI`](crate::PlanetAI) policies.
+pub(crate) struct Bandit {
+    config: BanditConfig,
+    stats: BanditStats,
+    rng: Rng,
+    /// Reward collected in the current episode, survival excluded.
+    episode_reward: f64,
+}
+
+impl Bandit {
+    /// A prior without every arm exactly once is dropped, with an error in the log.
+    pub(crate) fn new(config: BanditConfig) -> Bandit {
+        let stats = match config.prior.clone() {
+            Some(prior) if prior.is_complete() => prior,
+            Some(prior) => {
+                log::error!(
+                    "ignoring a bandit prior with {} arms instead of every arm once",
+                    prior.arms.len()
+                );
+                BanditStats::default()
+            }
+            None => BanditStats::default(),
+        };
+        let mut bandit = Bandit {
+            rng: Rng::new(config.seed),
+            config,
+            stats,
+            episode_reward: 0.0,
+        };
+        bandit.stats.current = bandit.pick();
+        bandit
+    }
+
+    pub(crate) fn sunray_policy(&self) -> SunrayPolicy {
+        self.stats.current_arm().sunray
+    }
+
+    pub(crate) fn asteroid_policy(&self) -> AsteroidPolicy {
+        self.stats.current_arm().asteroid
+    }
+
+    pub(crate) fn served(&mut self) {
+        self.episode_reward += self.config.served_reward;
+    }
+
+    /// Closes the episode at an asteroid and picks the arm of the next one.
+    pub(crate) fn asteroid(&mut self, survived: bool) {
+        let mut reward = self.episode_reward;
+        if survived {
+            reward += self.config.survival_reward;
+        }
+        let arm = &mut self.stats.arms[self.stats.current];
+        arm.pulls += 1;
+        arm.total_reward += reward;
+        self.episode_reward = 0.0;
+        self.stats.current = self.pick();
+    }
+
+    fn pick(&mut self) -> usize {
+        if !self.config.enabled {
+            return 0;
+        }
+        // Every arm is played once before trusting the means
+        if let Some(unplayed) = self.stats.arms.iter().position(|arm| arm.pulls == 0) {
+            return unplayed;
+        }
+        if self.rng.chance(self.config.epsilon) {
+            return self.rng.below(self.stats.arms.len());
+        }
+        let mut best = 0;
+        for (index, arm) in self.stats.arms.iter().enumerate() {
+            if arm.mean() > self.stats.arms[best].mean() {
+                best = index;
+            }
+        }
+        best
+    }
+
+    pub(crate) fn snapshot(&self) -> BanditStats {
+        self.stats.clone()
+    }
+}
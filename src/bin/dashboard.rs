@@ -0,0 +1,325 @@
+//! Terminal dashboard for a single AirFryer planet.
+//!
+//! The binary acts as a tiny orchestrator: it creates a planet with
+//! [`create_planet`], feeds it a scripted stream of sunrays, asteroids and
+//! explorer requests, polls it with `InternalStateRequest` and redraws the
+//! screen on every tick.
+//!
+//! ```text
+//! cargo run --bin dashboard -- --ticks 200 --tick-ms 250
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use air_fryer::{PlanetAI, PlanetReport, ReportHandle, create_planet};
+use common_game::{
+    components::{
+        forge::Forge,
+        planet::{DummyPlanetState, PlanetType},
+        resource::BasicResourceType,
+    },
+    protocols::{
+        orchestrator_planet::{OrchestratorToPlanet, PlanetToOrchestrator},
+        planet_explorer::{ExplorerToPlanet, PlanetToExplorer},
+    },
+    utils::ID,
+};
+use crossbeam_channel::{Receiver, Sender, unbounded};
+
+const PLANET_ID: ID = 1;
+const EXPLORER_ID: ID = 7;
+const LOG_LINES: usize = 12;
+
+// Scripted scenario, expressed in ticks
+const SUNRAY_EVERY: u64 = 4;
+const ASTEROID_EVERY: u64 = 23;
+const CARBON_EVERY: u64 = 9;
+const COMBINATIONS_EVERY: u64 = 5;
+const EXPLORER_ARRIVAL: u64 = 3;
+const EXPLORER_STAY: u64 = 60;
+
+struct Options {
+    ticks: u64,
+    tick: Duration,
+}
+
+impl Options {
+    fn from_args() -> Result<Options, String> {
+        let mut options = Options {
+            ticks: 200,
+            tick: Duration::from_millis(250),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for '{}'", arg))?;
+            let value: u64 = value
+                .parse()
+                .map_err(|_| format!("invalid value '{}' for '{}'", value, arg))?;
+            match arg.as_str() {
+                "--ticks" => options.ticks = value,
+                "--tick-ms" => options.tick = Duration::from_millis(value),
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Everything the dashboard knows about the planet.
+struct Dashboard {
+    tick: u64,
+    state: Option<DummyPlanetState>,
+    report: PlanetReport,
+    destroyed: bool,
+    log: VecDeque<String>,
+}
+
+impl Dashboard {
+    fn new() -> Dashboard {
+        Dashboard {
+            tick: 0,
+            state: None,
+            report: PlanetReport::default(),
+            destroyed: false,
+            log: VecDeque::with_capacity(LOG_LINES),
+        }
+    }
+
+    fn push_event(&mut self, event: String) {
+        if self.log.len() == LOG_LINES {
+            self.log.pop_front();
+        }
+        self.log.push_back(format!("[{:>5}] {}", self.tick, event));
+    }
+
+    fn on_orchestrator_msg(&mut self, msg: PlanetToOrchestrator) {
+        let event = match msg {
+            PlanetToOrchestrator::SunrayAck { .. } => "sunray absorbed".to_string(),
+            PlanetToOrchestrator::AsteroidAck { rocket, .. } => {
+                if rocket.is_some() {
+                    "asteroid destroyed by a rocket".to_string()
+                } else {
+                    self.destroyed = true;
+                    "asteroid hit: no rocket available".to_string()
+                }
+            }
+            PlanetToOrchestrator::StartPlanetAIResult { .. } => "planet AI started".to_string(),
+            PlanetToOrchestrator::StopPlanetAIResult { .. } => "planet AI stopped".to_string(),
+            PlanetToOrchestrator::KillPlanetResult { .. } => "planet killed".to_string(),
+            PlanetToOrchestrator::InternalStateResponse { planet_state, .. } => {
+                // Polled every tick, too noisy for the log
+                self.state = Some(planet_state);
+                return;
+            }
+            PlanetToOrchestrator::IncomingExplorerResponse { res, .. } => match res {
+                Ok(_) => format!("explorer {} landed", EXPLORER_ID),
+                Err(err) => format!("explorer {} refused: {}", EXPLORER_ID, err),
+            },
+            PlanetToOrchestrator::OutgoingExplorerResponse { res, .. } => match res {
+                Ok(_) => format!("explorer {} left", EXPLORER_ID),
+                Err(err) => format!("explorer {} could not leave: {}", EXPLORER_ID, err),
+            },
+            PlanetToOrchestrator::Stopped { .. } => "planet is stopped".to_string(),
+        };
+        self.push_event(event);
+    }
+
+    fn on_explorer_msg(&mut self, msg: PlanetToExplorer) {
+        let event = match msg {
+            PlanetToExplorer::GenerateResourceResponse { resource } => match resource {
+                Some(_) => "explorer received Carbon".to_string(),
+                None => "explorer refused Carbon".to_string(),
+            },
+            // Warnings are read from the report, see `on_report`
+            PlanetToExplorer::SupportedCombinationResponse { .. } => return,
+            _ => "explorer received a response".to_string(),
+        };
+        self.push_event(event);
+    }
+
+    fn on_report(&mut self, report: PlanetReport) {
+        let warned = report.pending_warning && !self.report.pending_warning;
+        self.report = report;
+        if warned {
+            self.push_event("explorer received an asteroid warning".to_string());
+        }
+    }
+
+    fn render(&self, out: &mut impl Write) -> io::Result<()> {
+        // Clear the screen and move the cursor to the top-left corner
+        write!(out, "\x1b[2J\x1b[H")?;
+        writeln!(out, "AirFryer planet {} - tick {}", PLANET_ID, self.tick)?;
+        writeln!(out, "{}", "=".repeat(48))?;
+
+        match &self.state {
+            Some(state) => {
+                let cells: String = state
+                    .energy_cells
+                    .iter()
+                    .map(|charged| if *charged { "[#]" } else { "[ ]" })
+                    .collect();
+                writeln!(out, "Energy cells    : {}", cells)?;
+                writeln!(
+                    out,
+                    "Rocket          : {}",
+                    if state.has_rocket { "ready" } else { "none" }
+                )?;
+            }
+            None => {
+                writeln!(out, "Energy cells    : waiting for the first report")?;
+                writeln!(out, "Rocket          : waiting for the first report")?;
+            }
+        }
+        writeln!(
+            out,
+            "AI              : {}",
+            if self.report.started {
+                "running"
+            } else {
+                "stopped"
+            }
+        )?;
+        writeln!(
+            out,
+            "Pending warning : {}",
+            if self.report.pending_warning {
+                "YES"
+            } else {
+                "no"
+            }
+        )?;
+        let explorers = if self.report.explorers.is_empty() {
+            "none".to_string()
+        } else {
+            self.report
+                .explorers
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(out, "Explorers       : {}", explorers)?;
+
+        writeln!(out, "{}", "-".repeat(48))?;
+        for line in &self.log {
+            writeln!(out, "{}", line)?;
+        }
+        out.flush()
+    }
+}
+
+/// Channels the dashboard uses to play the orchestrator and the explorer.
+struct Links {
+    to_planet: Sender<OrchestratorToPlanet>,
+    from_planet: Receiver<PlanetToOrchestrator>,
+    explorer_to_planet: Sender<ExplorerToPlanet>,
+    planet_to_explorer: (Sender<PlanetToExplorer>, Receiver<PlanetToExplorer>),
+}
+
+fn spawn_planet() -> Result<(Links, ReportHandle), String> {
+    let (tx_to_planet, rx_from_orc) = unbounded();
+    let (tx_to_orc, rx_from_planet) = unbounded();
+    let (tx_from_explorer, rx_at_planet) = unbounded();
+
+    let ai = PlanetAI::new();
+    let report = ai.report_handle();
+    let mut planet = create_planet(
+        PLANET_ID,
+        PlanetType::C,
+        ai,
+        (rx_from_orc, tx_to_orc),
+        rx_at_planet,
+    )?;
+    thread::spawn(move || {
+        if let Err(err) = planet.run() {
+            log::error!("planet {} stopped with an error: {}", PLANET_ID, err);
+        }
+    });
+
+    let links = Links {
+        to_planet: tx_to_planet,
+        from_planet: rx_from_planet,
+        explorer_to_planet: tx_from_explorer,
+        planet_to_explorer: unbounded(),
+    };
+    Ok((links, report))
+}
+
+/// Sends the scripted events scheduled for `tick`.
+fn play_scenario(tick: u64, forge: &Forge, links: &Links) {
+    let to_planet = &links.to_planet;
+    let explorer = &links.explorer_to_planet;
+
+    // The explorer alternates between staying on the planet and travelling
+    let phase = tick
+        .checked_sub(EXPLORER_ARRIVAL)
+        .map(|t| t % (2 * EXPLORER_STAY));
+    let explorer_present = phase.is_some_and(|phase| phase < EXPLORER_STAY);
+    if phase == Some(0) {
+        let _ = to_planet.send(OrchestratorToPlanet::IncomingExplorerRequest {
+            explorer_id: EXPLORER_ID,
+            new_sender: links.planet_to_explorer.0.clone(),
+        });
+    } else if phase == Some(EXPLORER_STAY) {
+        let _ = to_planet.send(OrchestratorToPlanet::OutgoingExplorerRequest {
+            explorer_id: EXPLORER_ID,
+        });
+    }
+    if tick.is_multiple_of(SUNRAY_EVERY) {
+        let _ = to_planet.send(OrchestratorToPlanet::Sunray(forge.generate_sunray()));
+    }
+    if tick > 0 && tick.is_multiple_of(ASTEROID_EVERY) {
+        let _ = to_planet.send(OrchestratorToPlanet::Asteroid(forge.generate_asteroid()));
+    }
+    if explorer_present && tick.is_multiple_of(CARBON_EVERY) {
+        let _ = explorer.send(ExplorerToPlanet::GenerateResourceRequest {
+            explorer_id: EXPLORER_ID,
+            resource: BasicResourceType::Carbon,
+        });
+    }
+    if explorer_present && tick.is_multiple_of(COMBINATIONS_EVERY) {
+        let _ = explorer.send(ExplorerToPlanet::SupportedCombinationRequest {
+            explorer_id: EXPLORER_ID,
+        });
+    }
+    let _ = to_planet.send(OrchestratorToPlanet::InternalStateRequest);
+}
+
+fn main() -> Result<(), String> {
+    let options = Options::from_args()?;
+    let forge = Forge::new()?;
+    let (links, report) = spawn_planet()?;
+    let mut dashboard = Dashboard::new();
+    let mut stdout = io::stdout();
+
+    let _ = links.to_planet.send(OrchestratorToPlanet::StartPlanetAI);
+
+    while dashboard.tick < options.ticks && !dashboard.destroyed {
+        play_scenario(dashboard.tick, &forge, &links);
+        thread::sleep(options.tick);
+
+        while let Ok(msg) = links.from_planet.try_recv() {
+            dashboard.on_orchestrator_msg(msg);
+        }
+        while let Ok(msg) = links.planet_to_explorer.1.try_recv() {
+            dashboard.on_explorer_msg(msg);
+        }
+        dashboard.on_report(report.snapshot());
+        dashboard
+            .render(&mut stdout)
+            .map_err(|err| err.to_string())?;
+        dashboard.tick += 1;
+    }
+
+    // Either the scenario is over or the planet was hit without a rocket
+    let _ = links.to_planet.send(OrchestratorToPlanet::KillPlanet);
+    while let Ok(msg) = links.from_planet.recv_timeout(options.tick) {
+        dashboard.on_orchestrator_msg(msg);
+    }
+    dashboard.render(&mut stdout).map_err(|err| err.to_string())
+}
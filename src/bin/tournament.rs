@@ -0,0 +1,110 @@
+//! Ranks a lineup of AirFryer configurations over the same event streams.
+//!
+//! The streams are either generated from consecutive seeds or loaded from
+//! recorded files, see [`EventStream::parse`].
+//!
+//! ```text
+//! cargo run --bin tournament -- --streams 20 --seed 7 --ticks 300
+//! cargo run --bin tournament -- --events game1.txt --events game2.txt
+//! ```
+
+use air_fryer::{
+    BanditConfig, Entrant, EventStream, OverflowPolicy, PlanetConfig, StreamConfig, Tournament,
+};
+use common_game::components::forge::Forge;
+
+struct Options {
+    streams: u64,
+    seed: u64,
+    ticks: u64,
+    recorded: Vec<String>,
+}
+
+impl Options {
+    fn from_args() -> Result<Options, String> {
+        let mut options = Options {
+            streams: 10,
+            seed: 0,
+            ticks: StreamConfig::default().ticks,
+            recorded: Vec::new(),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for '{}'", arg))?;
+            if arg == "--events" {
+                options.recorded.push(value);
+                continue;
+            }
+            let number: u64 = value
+                .parse()
+                .map_err(|_| format!("invalid value '{}' for '{}'", value, arg))?;
+            match arg.as_str() {
+                "--streams" => options.streams = number,
+                "--seed" => options.seed = number,
+                "--ticks" => options.ticks = number,
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+        Ok(options)
+    }
+
+    fn streams(&self) -> Result<Vec<EventStream>, String> {
+        if !self.recorded.is_empty() {
+            return self.recorded.iter().map(EventStream::load).collect();
+        }
+        let config = StreamConfig {
+            ticks: self.ticks,
+            ..StreamConfig::default()
+        };
+        Ok((0..self.streams)
+            .map(|i| EventStream::generate(self.seed + i, &config))
+            .collect())
+    }
+}
+
+fn lineup(seed: u64) -> Vec<Entrant> {
+    let bandit = BanditConfig {
+        enabled: true,
+        seed,
+        ..BanditConfig::default()
+    };
+    vec![
+        Entrant::new("default", PlanetConfig::default()),
+        Entrant::new(
+            "stock_carbon",
+            PlanetConfig {
+                overflow: OverflowPolicy::StockCarbon,
+                ..PlanetConfig::default()
+            },
+        ),
+        Entrant::new(
+            "bandit",
+            PlanetConfig {
+                bandit: bandit.clone(),
+                ..PlanetConfig::default()
+            },
+        ),
+        Entrant::new(
+            "bandit_greedy",
+            PlanetConfig {
+                bandit: BanditConfig {
+                    epsilon: 0.0,
+                    ..bandit
+                },
+                ..PlanetConfig::default()
+            },
+        ),
+    ]
+}
+
+fn main() -> Result<(), String> {
+    let options = Options::from_args()?;
+    let streams = options.streams()?;
+    let forge = Forge::new()?;
+
+    let report = Tournament::new(lineup(options.seed), streams).run(&forge)?;
+    print!("{}", report);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Tunes the AirFryer knobs over seeded event streams and saves the best config.
+//!
+//! Searches the whole grid, or `--trials` random candidates of it, and writes
+//! the result to `--out`, ready for [`PlanetConfig::load`].
+//!
+//! ```text
+//! cargo run --bin tune -- --streams 20 --seed 7 --asteroid 0.05 --out tuned.cfg
+//! cargo run --bin tune -- --trials 12 --explorers 16 --sunray 0.3
+//! ```
+
+use air_fryer::{Knob, PlanetConfig, Search, StreamConfig, Tuner};
+use common_game::components::forge::Forge;
+
+struct Options {
+    streams: u64,
+    seed: u64,
+    trials: usize,
+    load: StreamConfig,
+    out: String,
+}
+
+impl Options {
+    fn from_args() -> Result<Options, String> {
+        let mut options = Options {
+            streams: 10,
+            seed: 0,
+            trials: 0,
+            load: StreamConfig::default(),
+            out: String::from("tuned.cfg"),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for '{}'", arg))?;
+            let invalid = || format!("invalid value '{}' for '{}'", value, arg);
+            match arg.as_str() {
+                "--out" => options.out = value.clone(),
+                "--streams" => options.streams = value.parse().map_err(|_| invalid())?,
+                "--seed" => options.seed = value.parse().map_err(|_| invalid())?,
+                "--trials" => options.trials = value.parse().map_err(|_| invalid())?,
+                "--ticks" => options.load.ticks = value.parse().map_err(|_| invalid())?,
+                "--explorers" => options.load.explorers = value.parse().map_err(|_| invalid())?,
+                "--sunray" => {
+                    options.load.sunray_probability = value.parse().map_err(|_| invalid())?
+                }
+                "--asteroid" => {
+                    options.load.asteroid_probability = value.parse().map_err(|_| invalid())?
+                }
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn main() -> Result<(), String> {
+    let options = Options::from_args()?;
+    let forge = Forge::new()?;
+
+    let search = if options.trials == 0 {
+        Search::Grid
+    } else {
+        Search::Random {
+            trials: options.trials,
+            seed: options.seed,
+        }
+    };
+    let report = Tuner::new(
+        options.load.clone(),
+        options.seed..options.seed + options.streams,
+    )
+    .knob(Knob::StockOverflow, [0, 1])
+    .knob(Knob::StockCap, [4, 16, 64])
+    .knob(Knob::Reserve, [0, 1])
+    .knob(Knob::MaxRequests, [10, 20, 40])
+    .knob(Knob::RocketPolicy, [0, 2, 3, 4])
+    .run(search, &forge)?;
+
+    report
+        .save(&options.out)
+        .map_err(|err| format!("can't write {}: {}", options.out, err))?;
+    // Checks the file before anyone relies on it
+    PlanetConfig::load(&options.out)?;
+    print!("{}", report);
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::Path;
+
+use crate::alliance::AllianceConfig;
+use crate::bandit::BanditConfig;
+use crate::covert::CovertConfig;
+use crate::credits::CreditConfig;
+use crate::energy::EnergyConfig;
+use crate::explain::ExplanationConfig;
+use crate::extension::ExtensionConfig;
+use crate::forecast::ForecastConfig;
+use crate::overflow::OverflowPolicy;
+use crate::reputation::ReputationConfig;
+use crate::rules::RuleSet;
+use crate::stock::StockConfig;
+use crate::threat::ThreatBus;
+use crate::tuning::Knob;
+use crate::warning::WarningConfig;
+
+/// Tunable knobs of the [`PlanetAI`](crate::PlanetAI).
+///
+/// `PlanetConfig::default()` reproduces the behaviour of [`PlanetAI::new`](crate::PlanetAI::new).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanetConfig {
+    pub reputation: ReputationConfig,
+    pub credits: CreditConfig,
+    pub energy: EnergyConfig,
+    pub overflow: OverflowPolicy,
+    pub stock: StockConfig,
+    pub bandit: BanditConfig,
+    /// Sunray and asteroid decisions, see [`RuleSet`].
+    pub rules: RuleSet,
+    /// Keyed asteroid warning for the allies, see [`CovertConfig`].
+    pub covert: CovertConfig,
+    /// Service tiers of the explorers, see [`AllianceConfig`].
+    pub alliance: AllianceConfig,
+    pub warning: WarningConfig,
+    /// Bus shared with sibling planets, see [`ThreatBus`].
+    pub threat_bus: Option<ThreatBus>,
+    /// Services of the extension channel, see [`create_planet_with_extension`](crate::create_planet_with_extension).
+    pub extension: ExtensionConfig,
+    pub explanations: ExplanationConfig,
+    pub forecast: ForecastConfig,
+}
+
+impl PlanetConfig {
+    /// Reads a config file: one `<knob> <value>` per line, see [`Knob`].
+    ///
+    /// Unlisted knobs, and everything that isn't a knob, keep their default.
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<PlanetConfig, String> {
+        let mut config = PlanetConfig::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, value] = fields[..] else {
+                return Err(format!(
+                    "line {}: expected a knob and a value, found \"{}\"",
+                    index + 1,
+                    line
+                ));
+            };
+            let knob = Knob::from_name(name)
+                .ok_or_else(|| format!("line {}: unknown knob \"{}\"", index + 1, name))?;
+            let value = value
+                .parse()
+                .ok()
+                .filter(|value| knob.accepts(*value))
+                .ok_or_else(|| format!("line {}: invalid value \"{}\"", index + 1, value))?;
+            knob.set(&mut config, value);
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<PlanetConfig, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("can't read {}: {}", path.display(), err))?;
+        PlanetConfig::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
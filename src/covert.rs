@@ -0,0 +1,260 @@
+//! Keyed covert channel for the asteroid warning.
+//!
+//! Without a secret the planet signals a pending warning by dropping
+//! `AIPartner` from every `SupportedCombinationResponse`, a pattern any
+//! explorer can learn. With a secret shared with the allies, every response
+//! to an ally is a subset of the planet recipes picked by a keyed hash of the
+//! secret, the explorer ID and a counter that moves on with every request:
+//! one subset means "clear", another one "warning". Without the secret both
+//! look like random subsets, and a response replayed from an earlier request
+//! doesn't match the current counter. Other explorers always get the plain
+//! recipe list, warning or not.
+//!
+//! Allies decode the responses with a [`CovertDecoder`].
+
+use std::collections::{HashMap, HashSet};
+
+use common_game::components::resource::ComplexResourceType;
+use common_game::utils::ID;
+
+/// Every recipe, in the order used to build the keyed subsets.
+const RECIPES: [ComplexResourceType; 6] = [
+    ComplexResourceType::Diamond,
+    ComplexResourceType::Water,
+    ComplexResourceType::Life,
+    ComplexResourceType::Robot,
+    ComplexResourceType::Dolphin,
+    ComplexResourceType::AIPartner,
+];
+
+/// How many past counters a [`CovertDecoder`] checks to spot a replay.
+const REPLAY_WINDOW: u64 = 64;
+
+/// How many responses a [`CovertDecoder`] may miss before losing track.
+///
+/// Kept short: every counter checked ahead is one more subset a forged list
+/// could match.
+const RESYNC_WINDOW: u64 = 4;
+
+/// Setup of the covert warning channel.
+///
+/// The allies are the [`Tier::Ally`](crate::Tier::Ally) explorers of the
+/// [`AllianceConfig`](crate::AllianceConfig). Without a secret the planet
+/// keeps the legacy `AIPartner` signal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CovertConfig {
+    /// Secret shared with the allies.
+    pub secret: Option<u128>,
+}
+
+/// What a [`CovertDecoder`] read in a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// No asteroid hit the planet since the last warning.
+    Clear,
+    /// An asteroid hit the planet without a rocket.
+    Warning,
+    /// The response answered an earlier request.
+    Replayed,
+    /// The response wasn't encoded with this secret and explorer, or the
+    /// decoder missed too many responses.
+    Unrecognized,
+}
+
+/// Explorer side of the covert warning channel.
+///
+/// Feed it every `SupportedCombinationResponse` of the planet, in order. A
+/// few missed responses, lost or crossed with another request, are skipped
+/// over: the decoder catches up with the first response it recognizes.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{CovertDecoder, Signal};
+/// use common_game::components::resource::ComplexResourceType;
+///
+/// let recipes = [ComplexResourceType::Diamond, ComplexResourceType::Water];
+/// let mut decoder = CovertDecoder::new(0x5EC2E7, 7, recipes);
+/// // A list the planet didn't encode for explorer 7 is rejected
+/// let forged = recipes.into_iter().collect();
+/// assert_ne!(decoder.decode(&forged), Signal::Warning);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CovertDecoder {
+    secret: u128,
+    explorer_id: ID,
+    recipes: Vec<ComplexResourceType>,
+    counter: u64,
+}
+
+impl CovertDecoder {
+    /// `recipes` are the recipes of the planet, which depend on its type.
+    pub fn new(
+        secret: u128,
+        explorer_id: ID,
+        recipes: impl IntoIterator<Item = ComplexResourceType>,
+    ) -> CovertDecoder {
+        let recipes: HashSet<ComplexResourceType> = recipes.into_iter().collect();
+        CovertDecoder {
+            secret,
+            explorer_id,
+            recipes: canonical(&recipes),
+            counter: 0,
+        }
+    }
+
+    /// Responses decoded so far.
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    pub fn decode(&mut self, combination_list: &HashSet<ComplexResourceType>) -> Signal {
+        let encode = |counter, warning| {
+            encode(
+                self.secret,
+                self.explorer_id,
+                counter,
+                &self.recipes,
+                warning,
+            )
+        };
+        let read = |counter| {
+            let clear = encode(counter, false);
+            let warning = encode(counter, true);
+            if clear == warning {
+                None
+            } else if *combination_list == clear {
+                Some(Signal::Clear)
+            } else if *combination_list == warning {
+                Some(Signal::Warning)
+            } else {
+                None
+            }
+        };
+        if let Some(signal) = read(self.counter) {
+            self.counter += 1;
+            return signal;
+        }
+        let replayed = (self.counter.saturating_sub(REPLAY_WINDOW)..self.counter).any(|counter| {
+            *combination_list == encode(counter, false)
+                || *combination_list == encode(counter, true)
+        });
+        if replayed {
+            return Signal::Replayed;
+        }
+        // Some responses went missing: catch up with the planet counter
+        for counter in self.counter + 1..=self.counter + RESYNC_WINDOW {
+            if let Some(signal) = read(counter) {
+                self.counter = counter + 1;
+                return signal;
+            }
+        }
+        Signal::Unrecognized
+    }
+}
+
+/// Planet side of the covert warning channel.
+pub(crate) struct CovertChannel {
+    secret: u128,
+    counters: HashMap<ID, u64>,
+}
+
+impl CovertChannel {
+    /// `None` when no secret is configured.
+    pub(crate) fn new(config: CovertConfig) -> Option<CovertChannel> {
+        Some(CovertChannel {
+            secret: config.secret?,
+            counters: HashMap::new(),
+        })
+    }
+
+    /// Encodes the warning state in the response to an ally.
+    pub(crate) fn encode(
+        &mut self,
+        explorer_id: ID,
+        available: &HashSet<ComplexResourceType>,
+        warning: bool,
+    ) -> HashSet<ComplexResourceType> {
+        let counter = self.counters.entry(explorer_id).or_default();
+        let list = encode(
+            self.secret,
+            explorer_id,
+            *counter,
+            &canonical(available),
+            warning,
+        );
+        *counter += 1;
+        list
+    }
+}
+
+fn canonical(recipes: &HashSet<ComplexResourceType>) -> Vec<ComplexResourceType> {
+    RECIPES
+        .into_iter()
+        .filter(|recipe| recipes.contains(recipe))
+        .collect()
+}
+
+/// Subset of `recipes` signalling `warning` for the given explorer and counter.
+///
+/// The warning subset is the clear one with at least one recipe flipped, so
+/// the two never match when the planet has any recipe.
+fn encode(
+    secret: u128,
+    explorer_id: ID,
+    counter: u64,
+    recipes: &[ComplexResourceType],
+    warning: bool,
+) -> HashSet<ComplexResourceType> {
+    let all = (1u64 << recipes.len()) - 1;
+    let mut mask = siphash(secret, &[explorer_id as u64, counter, 0]) & all;
+    if warning {
+        let flip = siphash(secret, &[explorer_id as u64, counter, 1]) & all;
+        mask ^= if flip == 0 { all } else { flip };
+    }
+    recipes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| mask & (1 << i) != 0)
+        .map(|(_, recipe)| *recipe)
+        .collect()
+}
+
+/// SipHash-2-4 of the little-endian bytes of `words`.
+fn siphash(key: u128, words: &[u64]) -> u64 {
+    let (k0, k1) = (key as u64, (key >> 64) as u64);
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+    fn round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+    fn compress(v: &mut [u64; 4], m: u64) {
+        v[3] ^= m;
+        round(v);
+        round(v);
+        v[0] ^= m;
+    }
+    for word in words {
+        compress(&mut v, *word);
+    }
+    // Final block: the message length, the message being a whole number of words
+    compress(&mut v, ((words.len() as u64 * 8) & 0xff) << 56);
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use common_game::utils::ID;
+
+/// Exchange rates of the Carbon credit ledger.
+///
+/// With the default rates Carbon is free, so the ledger never refuses anyone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CreditConfig {
+    /// Credits opened to an explorer the first time the planet sees it.
+    pub initial_credits: u32,
+    /// Credits earned for every successful combination.
+    pub per_combination: u32,
+    /// Credits earned when arriving while every energy cell is charged, once
+    /// per explorer until a cell is used and charged again.
+    pub arrival_bonus: u32,
+    /// Credits spent for every unit of Carbon.
+    pub carbon_price: u32,
+}
+
+/// Credit balance of an explorer, as exposed in the [`PlanetReport`](crate::PlanetReport).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CreditAccount {
+    pub explorer_id: ID,
+    pub balance: u32,
+    pub earned: u32,
+    pub spent: u32,
+    /// Carbon requests refused because the balance was too low.
+    pub refused: u32,
+}
+
+/// Ledger of the credits explorers earn with their work and spend on Carbon.
+pub(crate) struct CreditLedger {
+    config: CreditConfig,
+    accounts: HashMap<ID, CreditAccount>,
+    /// Charged period in which each explorer last got the arrival bonus.
+    bonus_periods: HashMap<ID, u32>,
+}
+
+impl CreditLedger {
+    pub(crate) fn new(config: CreditConfig) -> CreditLedger {
+        CreditLedger {
+            config,
+            accounts: HashMap::new(),
+            bonus_periods: HashMap::new(),
+        }
+    }
+
+    fn account(&mut self, explorer_id: ID) -> &mut CreditAccount {
+        let initial_credits = self.config.initial_credits;
+        self.accounts
+            .entry(explorer_id)
+            .or_insert_with(|| CreditAccount {
+                explorer_id,
+                balance: initial_credits,
+                ..CreditAccount::default()
+            })
+    }
+
+    fn earn(&mut self, explorer_id: ID, credits: u32) {
+        let account = self.account(explorer_id);
+        account.balance = account.balance.saturating_add(credits);
+        account.earned = account.earned.saturating_add(credits);
+    }
+
+    /// Opens the account of an arriving explorer and pays the arrival bonus.
+    ///
+    /// `charged_period` tells which period of fully charged cells the planet
+    /// is in, `None` when a cell is empty. Leaving and coming back within the
+    /// same period earns nothing more.
+    pub(crate) fn arrival(&mut self, explorer_id: ID, charged_period: Option<u32>) {
+        self.account(explorer_id);
+        let Some(period) = charged_period else {
+            return;
+        };
+        if self.bonus_periods.insert(explorer_id, period) != Some(period) {
+            self.earn(explorer_id, self.config.arrival_bonus);
+        }
+    }
+
+    pub(crate) fn combination(&mut self, explorer_id: ID) {
+        self.earn(explorer_id, self.config.per_combination);
+    }
+
+    /// Checks the balance before generating Carbon, counting a refusal if it's too low.
+    pub(crate) fn can_buy_carbon(&mut self, explorer_id: ID) -> bool {
+        let price = self.config.carbon_price;
+        let account = self.account(explorer_id);
+        if account.balance < price {
+            account.refused += 1;
+            return false;
+        }
+        true
+    }
+
+    /// Charges the Carbon once it has actually been generated.
+    ///
+    /// The balance is checked by [`can_buy_carbon`](Self::can_buy_carbon);
+    /// both totals still stop at their bounds.
+    pub(crate) fn carbon_sold(&mut self, explorer_id: ID) {
+        let price = self.config.carbon_price;
+        let account = self.account(explorer_id);
+        account.balance = account.balance.saturating_sub(price);
+        account.spent = account.spent.saturating_add(price);
+    }
+
+    /// Every account opened so far, sorted by explorer ID.
+    pub(crate) fn snapshot(&self) -> Vec<CreditAccount> {
+        let mut accounts: Vec<CreditAccount> = self.accounts.values().cloned().collect();
+        accounts.sort_by_key(|account| account.explorer_id);
+        accounts
+    }
+}
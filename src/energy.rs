@@ -0,0 +1,191 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use common_game::components::resource::{BasicResourceType, ComplexResourceType};
+use common_game::utils::ID;
+
+/// Tuning of the energy attribution ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnergyConfig {
+    /// How many charges and discharges are kept in the history.
+    pub history: usize,
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        EnergyConfig { history: 256 }
+    }
+}
+
+/// Where the energy of a sunray ended up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnergyConsumer {
+    /// A rocket built against an asteroid, or to make room for a sunray.
+    Rocket,
+    /// A basic resource generated for an explorer.
+    Generation {
+        explorer_id: ID,
+        resource: BasicResourceType,
+    },
+    /// A complex resource combined for an explorer.
+    Combination {
+        explorer_id: ID,
+        recipe: ComplexResourceType,
+    },
+    /// Carbon banked with the energy of an overflowing sunray.
+    CarbonStock,
+    /// A sunray that arrived with every cell charged.
+    WastedSunray,
+}
+
+impl EnergyConsumer {
+    /// The explorer served with the energy, if any.
+    pub fn explorer_id(&self) -> Option<ID> {
+        match self {
+            EnergyConsumer::Generation { explorer_id, .. }
+            | EnergyConsumer::Combination { explorer_id, .. } => Some(*explorer_id),
+            EnergyConsumer::Rocket | EnergyConsumer::CarbonStock | EnergyConsumer::WastedSunray => {
+                None
+            }
+        }
+    }
+}
+
+/// A single movement of energy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyFlow {
+    /// A sunray charged the cell.
+    Charged { cell: usize },
+    /// The energy went to a consumer. The cell is `None` for a wasted sunray,
+    /// which never reached one.
+    Consumed {
+        cell: Option<usize>,
+        consumer: EnergyConsumer,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnergyEntry {
+    pub at: Instant,
+    pub flow: EnergyFlow,
+}
+
+/// Energy consumed by a consumer since the planet was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsumerTotal {
+    pub consumer: EnergyConsumer,
+    pub cells: u32,
+}
+
+/// Energy bookkeeping exposed in the [`PlanetReport`](crate::PlanetReport).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnergyReport {
+    /// Sunrays that charged a cell.
+    pub charged: u32,
+    /// Per-consumer totals, the biggest consumers first.
+    pub consumed: Vec<ConsumerTotal>,
+    /// The last charges and discharges, oldest first.
+    pub history: Vec<EnergyEntry>,
+}
+
+impl EnergyReport {
+    /// Cells consumed by `consumer`.
+    pub fn total(&self, consumer: EnergyConsumer) -> u32 {
+        self.consumed
+            .iter()
+            .find(|total| total.consumer == consumer)
+            .map_or(0, |total| total.cells)
+    }
+
+    /// Cells consumed by an explorer, all resources and recipes included.
+    pub fn explorer_total(&self, explorer_id: ID) -> u32 {
+        self.consumed
+            .iter()
+            .filter(|total| total.consumer.explorer_id() == Some(explorer_id))
+            .map(|total| total.cells)
+            .sum()
+    }
+
+    /// Entries of the history recorded in the `window` before `now`, as read
+    /// on the planet clock, see [`PlanetAI::with_clock`](crate::PlanetAI::with_clock).
+    pub fn recent(&self, window: Duration, now: Instant) -> impl Iterator<Item = &EnergyEntry> {
+        self.history
+            .iter()
+            .filter(move |entry| now.duration_since(entry.at) <= window)
+    }
+}
+
+/// Attribution of every charge and discharge of the energy cells.
+pub(crate) struct EnergyLedger {
+    config: EnergyConfig,
+    charged: u32,
+    consumed: HashMap<EnergyConsumer, u32>,
+    history: VecDeque<EnergyEntry>,
+}
+
+impl EnergyLedger {
+    pub(crate) fn new(config: EnergyConfig) -> EnergyLedger {
+        EnergyLedger {
+            config,
+            charged: 0,
+            consumed: HashMap::new(),
+            history: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn charged(&mut self, cell: usize, now: Instant) {
+        self.charged += 1;
+        self.push(EnergyFlow::Charged { cell }, now);
+    }
+
+    /// Cells charged so far: it changes whenever a used cell is charged again.
+    pub(crate) fn charges(&self) -> u32 {
+        self.charged
+    }
+
+    pub(crate) fn consumed(&mut self, cell: usize, consumer: EnergyConsumer, now: Instant) {
+        self.consume(Some(cell), consumer, now);
+    }
+
+    pub(crate) fn wasted(&mut self, now: Instant) {
+        self.consume(None, EnergyConsumer::WastedSunray, now);
+    }
+
+    fn consume(&mut self, cell: Option<usize>, consumer: EnergyConsumer, now: Instant) {
+        *self.consumed.entry(consumer).or_default() += 1;
+        self.push(EnergyFlow::Consumed { cell, consumer }, now);
+    }
+
+    fn push(&mut self, flow: EnergyFlow, now: Instant) {
+        if self.config.history == 0 {
+            return;
+        }
+        if self.history.len() == self.config.history {
+            self.history.pop_front();
+        }
+        self.history.push_back(EnergyEntry { at: now, flow });
+    }
+
+    pub(crate) fn snapshot(&self) -> EnergyReport {
+        let mut consumed: Vec<ConsumerTotal> = self
+            .consumed
+            .iter()
+            .map(|(consumer, cells)| ConsumerTotal {
+                consumer: *consumer,
+                cells: *cells,
+            })
+            .collect();
+        // Consumers have no natural order, the debug name keeps ties stable
+        consumed.sort_by_cached_key(|total| {
+            (
+                std::cmp::Reverse(total.cells),
+                format!("{:?}", total.consumer),
+            )
+        });
+        EnergyReport {
+            charged: self.charged,
+            consumed,
+            history: self.history.iter().copied().collect(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+/// Prefix of the rendered [`PlanetError`]s, so explorers can tell them apart
+/// from the messages of other planets.
+const PREFIX: &str = "air_fryer:";
+
+/// Why the planet refused or failed an explorer request.
+///
+/// Combination errors carry the error rendered with [`Display`](fmt::Display),
+/// a stable `air_fryer:<code>` string that explorers turn back into the enum
+/// with [`PlanetError::parse`]. Generation failures can only be reported as
+/// `resource: None` by the protocol.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::PlanetError;
+///
+/// let message = PlanetError::NoEnergy.to_string();
+/// assert_eq!(message, "air_fryer:no_energy");
+/// assert_eq!(PlanetError::parse(&message), Some(PlanetError::NoEnergy));
+/// assert_eq!(PlanetError::parse("Energy cell not charged"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlanetError {
+    /// The planet doesn't generate that resource or doesn't know that recipe.
+    UnsupportedResource,
+    /// No energy cell is charged.
+    NoEnergy,
+    /// The last charged cell is kept to build a rocket against an announced asteroid.
+    ReservedForDefense,
+    /// The charged energy is kept for the allies, see [`AllianceConfig`](crate::AllianceConfig).
+    ReservedForAllies,
+    /// The charged energy is held by other explorers, see
+    /// [`ExtensionRequest::Reserve`](crate::ExtensionRequest::Reserve).
+    ReservedByOthers,
+    /// The explorer is over its request rate or out of credits.
+    QuotaExceeded,
+    /// The explorer is not on the planet.
+    ExplorerNotRegistered,
+    /// The planet AI is not running.
+    PlanetStopped,
+    /// The combinator refused the resources handed in.
+    WrongInputs,
+}
+
+impl PlanetError {
+    /// Every error, in the order of their codes.
+    pub const ALL: [PlanetError; 9] = [
+        PlanetError::UnsupportedResource,
+        PlanetError::NoEnergy,
+        PlanetError::ReservedForDefense,
+        PlanetError::ReservedForAllies,
+        PlanetError::ReservedByOthers,
+        PlanetError::QuotaExceeded,
+        PlanetError::ExplorerNotRegistered,
+        PlanetError::PlanetStopped,
+        PlanetError::WrongInputs,
+    ];
+
+    /// The stable code of the error, without the `air_fryer:` prefix.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PlanetError::UnsupportedResource => "unsupported_resource",
+            PlanetError::NoEnergy => "no_energy",
+            PlanetError::ReservedForDefense => "reserved_for_defense",
+            PlanetError::ReservedForAllies => "reserved_for_allies",
+            PlanetError::ReservedByOthers => "reserved_by_others",
+            PlanetError::QuotaExceeded => "quota_exceeded",
+            PlanetError::ExplorerNotRegistered => "explorer_not_registered",
+            PlanetError::PlanetStopped => "planet_stopped",
+            PlanetError::WrongInputs => "wrong_inputs",
+        }
+    }
+
+    /// Parses an error message sent by an AirFryer planet.
+    ///
+    /// Returns `None` for messages that don't come from AirFryer.
+    pub fn parse(message: &str) -> Option<PlanetError> {
+        let code = message.strip_prefix(PREFIX)?;
+        PlanetError::ALL
+            .into_iter()
+            .find(|error| error.code() == code)
+    }
+}
+
+impl fmt::Display for PlanetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", PREFIX, self.code())
+    }
+}
+
+impl std::error::Error for PlanetError {}
@@ -0,0 +1,201 @@
+//! Explanations of the decisions of the [`PlanetAI`](crate::PlanetAI).
+//!
+//! Every callback of the AI leaves an [`Explanation`]: what it was handling,
+//! what it saw, which rules fired and what came out of it. The last ones are
+//! kept in a ring buffer, read live through an [`ExplanationHandle`] or with
+//! the [`PlanetReport`](crate::PlanetReport).
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use common_game::utils::ID;
+
+use crate::error::PlanetError;
+use crate::lifecycle::Rejection;
+
+/// Tuning of the explanation ring buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplanationConfig {
+    /// How many explanations are kept; the oldest ones are dropped first.
+    pub capacity: usize,
+}
+
+impl Default for ExplanationConfig {
+    fn default() -> Self {
+        ExplanationConfig { capacity: 128 }
+    }
+}
+
+/// The callback of the AI that took the decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Callback {
+    Sunray,
+    Asteroid,
+    InternalStateRequest,
+    ExplorerRequest,
+    ExplorerArrival,
+    ExplorerDeparture,
+    Start,
+    Stop,
+}
+
+/// What the AI saw when the callback started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Observation {
+    pub charged_cells: usize,
+    pub cells: usize,
+    pub rocket: bool,
+    pub pending_warning: bool,
+    /// Explorers on the planet.
+    pub explorers: usize,
+}
+
+/// A rule that fired, see [`RuleSet`](crate::RuleSet).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiredRule {
+    /// Position of the rule in the set, starting from 1.
+    pub number: usize,
+    pub rule: String,
+}
+
+/// What came out of the callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The sunray charged a cell.
+    Charged,
+    /// Every cell was charged, the sunray was lost.
+    Wasted,
+    /// A rocket was launched at the asteroid.
+    Deflected,
+    /// No rocket was ready when the asteroid came.
+    Hit,
+    /// The explorer got the resource it asked for.
+    Served,
+    /// The explorer was answered without spending energy.
+    Answered,
+    Refused(PlanetError),
+    /// The lifecycle event was applied.
+    Applied,
+    /// The lifecycle event was illegal, see [`Rejection`].
+    Rejected(Rejection),
+}
+
+/// Why the planet did what it did, for one callback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub at: Instant,
+    pub callback: Callback,
+    /// The explorer behind the callback, if any.
+    pub explorer: Option<ID>,
+    pub observed: Observation,
+    /// The rules that fired, in order.
+    pub fired: Vec<FiredRule>,
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for Explanation {
+    /// One line, such as
+    /// `Asteroid: Hit (cells 0/1 charged, no rocket, warning pending, 2 explorers; no rule fired)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let observed = &self.observed;
+        write!(f, "{:?}", self.callback)?;
+        if let Some(explorer) = self.explorer {
+            write!(f, " of explorer {}", explorer)?;
+        }
+        write!(
+            f,
+            ": {:?} (cells {}/{} charged, {}, {}, {} explorers; ",
+            self.outcome,
+            observed.charged_cells,
+            observed.cells,
+            if observed.rocket {
+                "rocket"
+            } else {
+                "no rocket"
+            },
+            if observed.pending_warning {
+                "warning pending"
+            } else {
+                "no warning"
+            },
+            observed.explorers,
+        )?;
+        if self.fired.is_empty() {
+            write!(f, "no rule fired)")
+        } else {
+            let fired: Vec<String> = self
+                .fired
+                .iter()
+                .map(|fired| format!("rule {} `{}`", fired.number, fired.rule))
+                .collect();
+            write!(f, "fired {})", fired.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Ring {
+    capacity: usize,
+    explanations: VecDeque<Explanation>,
+}
+
+/// Cloneable handle on the explanations of a [`PlanetAI`](crate::PlanetAI),
+/// updated as the planet runs.
+///
+/// The handle must be taken with
+/// [`PlanetAI::explanation_handle`](crate::PlanetAI::explanation_handle)
+/// before the AI is moved into the planet.
+#[derive(Debug, Clone)]
+pub struct ExplanationHandle(Arc<Mutex<Ring>>);
+
+impl ExplanationHandle {
+    pub(crate) fn new(config: ExplanationConfig) -> ExplanationHandle {
+        ExplanationHandle(Arc::new(Mutex::new(Ring {
+            capacity: config.capacity.max(1),
+            explanations: VecDeque::new(),
+        })))
+    }
+
+    /// The kept explanations, oldest first.
+    pub fn snapshot(&self) -> Vec<Explanation> {
+        self.lock().explanations.iter().cloned().collect()
+    }
+
+    /// The latest explanation of a callback, such as the last asteroid.
+    pub fn last(&self, callback: Callback) -> Option<Explanation> {
+        self.lock()
+            .explanations
+            .iter()
+            .rev()
+            .find(|explanation| explanation.callback == callback)
+            .cloned()
+    }
+
+    /// The kept explanations involving an explorer, oldest first.
+    pub fn of_explorer(&self, explorer_id: ID) -> Vec<Explanation> {
+        self.lock()
+            .explanations
+            .iter()
+            .filter(|explanation| explanation.explorer == Some(explorer_id))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn record(&self, explanation: Explanation) {
+        log::trace!("{}", explanation);
+        let mut ring = self.lock();
+        if ring.explanations.len() == ring.capacity {
+            ring.explanations.pop_front();
+        }
+        ring.explanations.push_back(explanation);
+    }
+
+    // Explanations are plain data, so a poisoned mutex is still safe to read
+    fn lock(&self) -> MutexGuard<'_, Ring> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,229 @@
+//! Extension protocol for allied explorers and tools.
+//!
+//! `ExplorerToPlanet` can't be extended outside common-game, so the AirFryer
+//! services it can't express travel on a separate channel pair, created by
+//! [`create_planet_with_extension`](crate::create_planet_with_extension)
+//! next to the standard channels. The [`PlanetAI`](crate::PlanetAI) serves the
+//! pending requests every time the planet hands it a message (sunray,
+//! asteroid, internal state request or explorer request), with the same state
+//! and the same checks as the standard requests.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use common_game::components::resource::Carbon;
+use common_game::utils::ID;
+use crossbeam_channel::{Receiver, Sender, unbounded};
+
+use crate::error::PlanetError;
+use crate::forecast::EnergyForecast;
+use crate::lifecycle::LifecyclePhase;
+
+/// Tuning of the extension services.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionConfig {
+    /// Most Carbon handed out by one [`ExtensionRequest::BatchCarbon`].
+    pub max_batch: u32,
+    /// How long a reservation holds if the explorer doesn't use it.
+    pub reservation_ttl: Duration,
+}
+
+impl Default for ExtensionConfig {
+    fn default() -> Self {
+        ExtensionConfig {
+            max_batch: 8,
+            reservation_ttl: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Request sent on the extension channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionRequest {
+    /// Several units of Carbon at once, charged like as many single requests.
+    BatchCarbon {
+        explorer_id: ID,
+        amount: u32,
+    },
+    /// How long until a charged cell is free, see [`EnergyForecast`].
+    EnergyEta,
+    /// Keeps charged cells for the explorer: the others can't spend them
+    /// until it does, leaves, or the reservation expires.
+    Reserve {
+        explorer_id: ID,
+        cells: u32,
+    },
+    /// Drops the reservation of the explorer.
+    Release {
+        explorer_id: ID,
+    },
+    Status,
+}
+
+/// Response to an [`ExtensionRequest`], in the order of the requests.
+#[derive(Debug)]
+pub enum ExtensionResponse {
+    /// The Carbon handed out, and why the batch stopped short, if it did.
+    Carbon {
+        carbon: Vec<Carbon>,
+        error: Option<PlanetError>,
+    },
+    EnergyEta(EnergyForecast),
+    /// Cells now held for the explorer, possibly fewer than requested.
+    Reserved {
+        cells: u32,
+    },
+    Released,
+    Status(PlanetStatus),
+    /// The request was refused before being served.
+    Refused(PlanetError),
+}
+
+/// Summary of the planet for [`ExtensionRequest::Status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanetStatus {
+    pub lifecycle: LifecyclePhase,
+    pub cells: u32,
+    pub charged_cells: u32,
+    /// Charged cells held by reservations.
+    pub reserved_cells: u32,
+    pub rocket: bool,
+    pub explorers: u32,
+    /// Units of Carbon in stock.
+    pub carbon_stock: u32,
+    pub pending_warning: bool,
+}
+
+/// Tool side of the extension channel pair.
+///
+/// Responses come back in the order of the requests, once the planet handles
+/// its next message.
+#[derive(Debug)]
+pub struct ExtensionClient {
+    requests: Sender<ExtensionRequest>,
+    responses: Receiver<ExtensionResponse>,
+}
+
+impl ExtensionClient {
+    /// Fails when the planet is gone.
+    pub fn send(&self, request: ExtensionRequest) -> Result<(), String> {
+        self.requests
+            .send(request)
+            .map_err(|_| String::from("the planet dropped the extension channel"))
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<ExtensionResponse> {
+        self.responses.recv_timeout(timeout).ok()
+    }
+
+    pub fn try_recv(&self) -> Option<ExtensionResponse> {
+        self.responses.try_recv().ok()
+    }
+}
+
+/// Planet side of the extension channel pair.
+pub(crate) struct ExtensionServer {
+    requests: Receiver<ExtensionRequest>,
+    responses: Sender<ExtensionResponse>,
+}
+
+impl ExtensionServer {
+    pub(crate) fn pair() -> (ExtensionServer, ExtensionClient) {
+        let (tx_request, rx_request) = unbounded();
+        let (tx_response, rx_response) = unbounded();
+        let server = ExtensionServer {
+            requests: rx_request,
+            responses: tx_response,
+        };
+        let client = ExtensionClient {
+            requests: tx_request,
+            responses: rx_response,
+        };
+        (server, client)
+    }
+
+    pub(crate) fn next(&self) -> Option<ExtensionRequest> {
+        self.requests.try_recv().ok()
+    }
+
+    pub(crate) fn respond(&self, response: ExtensionResponse) {
+        // The tool may have gone away, which is its business
+        let _ = self.responses.send(response);
+    }
+}
+
+struct Reservation {
+    cells: u32,
+    expires: Instant,
+}
+
+/// Charged cells held for explorers, see [`ExtensionRequest::Reserve`].
+pub(crate) struct Reservations {
+    ttl: Duration,
+    held: HashMap<ID, Reservation>,
+}
+
+impl Reservations {
+    pub(crate) fn new(config: &ExtensionConfig) -> Reservations {
+        Reservations {
+            ttl: config.reservation_ttl,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Holds up to `cells` of the charged cells nobody else holds.
+    ///
+    /// Returns the cells held for the explorer.
+    pub(crate) fn reserve(
+        &mut self,
+        explorer_id: ID,
+        cells: u32,
+        charged: u32,
+        now: Instant,
+    ) -> u32 {
+        let free = charged.saturating_sub(self.held_by_others(explorer_id, now));
+        let cells = cells.min(free);
+        self.held.remove(&explorer_id);
+        if cells > 0 {
+            self.held.insert(
+                explorer_id,
+                Reservation {
+                    cells,
+                    expires: now + self.ttl,
+                },
+            );
+        }
+        cells
+    }
+
+    pub(crate) fn release(&mut self, explorer_id: ID) {
+        self.held.remove(&explorer_id);
+    }
+
+    /// The explorer spent one of the cells it holds.
+    pub(crate) fn spent(&mut self, explorer_id: ID) {
+        if let Some(reservation) = self.held.get_mut(&explorer_id) {
+            reservation.cells -= 1;
+            if reservation.cells == 0 {
+                self.held.remove(&explorer_id);
+            }
+        }
+    }
+
+    pub(crate) fn held_by_others(&mut self, explorer_id: ID, now: Instant) -> u32 {
+        self.held.retain(|_, reservation| reservation.expires > now);
+        self.held
+            .iter()
+            .filter(|(holder, _)| **holder != explorer_id)
+            .map(|(_, reservation)| reservation.cells)
+            .sum()
+    }
+
+    pub(crate) fn total(&mut self, now: Instant) -> u32 {
+        self.held.retain(|_, reservation| reservation.expires > now);
+        self.held
+            .values()
+            .map(|reservation| reservation.cells)
+            .sum()
+    }
+}
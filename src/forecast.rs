@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Tuning of the sunray rate estimate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForecastConfig {
+    /// How many of the last sunray arrivals the rate is estimated from.
+    pub samples: usize,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        ForecastConfig { samples: 16 }
+    }
+}
+
+/// When the planet will have energy to spare, as seen by the
+/// [`PlanetAI`](crate::PlanetAI).
+///
+/// A charged cell is free unless it's the one kept to build the rocket: a
+/// planet that can have a rocket and has none keeps one charged cell for it
+/// while an asteroid is announced, and refuses to spend it with
+/// [`PlanetError::ReservedForDefense`](crate::PlanetError::ReservedForDefense).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnergyForecast {
+    /// Charged cells free from rocket duty.
+    pub free_cells: u32,
+    /// Sunrays needed before a cell is free, 0 when one already is.
+    pub sunrays_needed: u32,
+    /// Mean time between the last sunrays, `None` before the second one.
+    pub sunray_interval: Option<Duration>,
+    /// Expected time until a cell is free: zero when one already is, `None`
+    /// while the interval is unknown.
+    pub eta: Option<Duration>,
+}
+
+impl EnergyForecast {
+    /// Sunrays per minute, `None` while the interval is unknown.
+    pub fn sunrays_per_minute(&self) -> Option<f64> {
+        let interval = self.sunray_interval?.as_secs_f64();
+        (interval > 0.0).then(|| 60.0 / interval)
+    }
+}
+
+/// Arrival times of the last sunrays.
+pub(crate) struct SunrayRate {
+    samples: usize,
+    arrivals: VecDeque<Instant>,
+}
+
+impl SunrayRate {
+    pub(crate) fn new(config: ForecastConfig) -> SunrayRate {
+        SunrayRate {
+            samples: config.samples.max(2),
+            arrivals: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, now: Instant) {
+        if self.arrivals.len() == self.samples {
+            self.arrivals.pop_front();
+        }
+        self.arrivals.push_back(now);
+    }
+
+    /// Mean time between the recorded arrivals.
+    pub(crate) fn interval(&self) -> Option<Duration> {
+        let (first, last) = (self.arrivals.front()?, self.arrivals.back()?);
+        let gaps = self.arrivals.len() as u32 - 1;
+        (gaps > 0).then(|| last.duration_since(*first) / gaps)
+    }
+
+    /// Forecast for a planet with `charged` of its cells charged.
+    pub(crate) fn forecast(
+        &self,
+        charged: u32,
+        rocket_needed: bool,
+        now: Instant,
+    ) -> EnergyForecast {
+        let kept = u32::from(rocket_needed);
+        let free_cells = charged.saturating_sub(kept);
+        // Each sunray either charges a cell or turns a charged one into the rocket
+        let sunrays_needed = (kept + 1).saturating_sub(charged);
+        let sunray_interval = self.interval();
+        let eta = if sunrays_needed == 0 {
+            Some(Duration::ZERO)
+        } else {
+            sunray_interval.map(|interval| {
+                // The next sunray is due one interval after the last one
+                let since = self
+                    .arrivals
+                    .back()
+                    .map_or(Duration::ZERO, |last| now.duration_since(*last));
+                interval.saturating_sub(since) + interval * (sunrays_needed - 1)
+            })
+        };
+        EnergyForecast {
+            free_cells,
+            sunrays_needed,
+            sunray_interval,
+            eta,
+        }
+    }
+}
@@ -0,0 +1,675 @@
+//! Multi-planet galaxy simulation.
+//!
+//! A [`Galaxy`] spawns several planets in their own threads and plays the
+//! orchestrator: it sends sunrays and asteroids on a seeded schedule and moves
+//! explorer bots between planets with `IncomingExplorerRequest` /
+//! `OutgoingExplorerRequest`. Every bot works towards a complex resource,
+//! gathering basic resources on the provider planets and combining them on the
+//! AirFryer planets, so the final [`GalaxyReport`] shows how well AirFryer
+//! serves as the Carbon and complex-resource hub of the economy.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use common_game::{
+    components::{
+        forge::Forge,
+        planet as common_planet,
+        resource::{
+            BasicResourceType, ComplexResourceRequest, ComplexResourceType, GenericResource,
+        },
+    },
+    protocols::{
+        orchestrator_planet::{OrchestratorToPlanet, PlanetToOrchestrator},
+        planet_explorer::{ExplorerToPlanet, PlanetToExplorer},
+    },
+    utils::ID,
+};
+use crossbeam_channel::{Receiver, Sender, unbounded};
+
+use crate::mock_planet::{MockAI, MockConfig};
+use crate::recipes::{self, Ingredient};
+use crate::rng::Rng;
+use crate::{PlanetAI, create_planet};
+
+/// Kind of planet instantiated by the [`Galaxy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanetKind {
+    /// An AirFryer planet, created with [`create_planet`].
+    AirFryer,
+    /// A Type B planet that only generates the given basic resources and
+    /// can't defend itself.
+    Provider(Vec<BasicResourceType>),
+}
+
+impl PlanetKind {
+    fn generates(&self, resource: BasicResourceType) -> bool {
+        match self {
+            PlanetKind::AirFryer => resource == BasicResourceType::Carbon,
+            PlanetKind::Provider(resources) => resources.contains(&resource),
+        }
+    }
+
+    fn combines(&self) -> bool {
+        matches!(self, PlanetKind::AirFryer)
+    }
+}
+
+impl fmt::Display for PlanetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanetKind::AirFryer => write!(f, "AirFryer"),
+            PlanetKind::Provider(resources) => {
+                let names: Vec<String> = resources.iter().map(|r| format!("{:?}", r)).collect();
+                write!(f, "Provider({})", names.join(","))
+            }
+        }
+    }
+}
+
+/// Parameters of a galaxy simulation.
+#[derive(Debug, Clone)]
+pub struct GalaxyConfig {
+    /// Seed of the sunray/asteroid schedule and of the explorers' choices.
+    pub seed: u64,
+    /// Planets to spawn; their IDs are their indexes in this list.
+    pub planets: Vec<PlanetKind>,
+    /// Number of explorer bots, initially spread round-robin over the planets.
+    pub explorers: usize,
+    /// Length of the simulation.
+    pub ticks: u64,
+    /// Probability that a planet receives a sunray in a tick.
+    pub sunray_probability: f64,
+    /// Probability that a planet is hit by an asteroid in a tick.
+    pub asteroid_probability: f64,
+    /// Ticks at the beginning of the game without asteroids.
+    pub asteroid_grace_ticks: u64,
+    /// Complex resources the explorers try to build.
+    pub targets: Vec<ComplexResourceType>,
+    /// How long to wait for any planet response before giving up.
+    pub timeout: Duration,
+}
+
+impl Default for GalaxyConfig {
+    fn default() -> Self {
+        GalaxyConfig {
+            seed: 0,
+            planets: vec![
+                PlanetKind::AirFryer,
+                PlanetKind::Provider(vec![BasicResourceType::Hydrogen, BasicResourceType::Oxygen]),
+                PlanetKind::Provider(vec![BasicResourceType::Silicon]),
+            ],
+            explorers: 3,
+            ticks: 200,
+            sunray_probability: 0.5,
+            asteroid_probability: 0.02,
+            asteroid_grace_ticks: 20,
+            targets: vec![
+                ComplexResourceType::Water,
+                ComplexResourceType::Diamond,
+                ComplexResourceType::Life,
+                ComplexResourceType::Robot,
+                ComplexResourceType::Dolphin,
+                ComplexResourceType::AIPartner,
+            ],
+            timeout: Duration::from_millis(500),
+        }
+    }
+}
+
+/// What happened to a single planet during the simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanetStats {
+    pub id: ID,
+    pub kind: PlanetKind,
+    pub sunrays: u32,
+    pub asteroids: u32,
+    /// Asteroids deflected with a rocket.
+    pub rockets: u32,
+    /// Tick of the asteroid that destroyed the planet, if any.
+    pub destroyed_at: Option<u64>,
+    pub resources_served: u32,
+    pub resources_refused: u32,
+    pub combinations_served: u32,
+    pub combinations_failed: u32,
+    /// Explorer arrivals, including the initial placement.
+    pub visits: u32,
+}
+
+impl PlanetStats {
+    fn new(id: ID, kind: PlanetKind) -> PlanetStats {
+        PlanetStats {
+            id,
+            kind,
+            sunrays: 0,
+            asteroids: 0,
+            rockets: 0,
+            destroyed_at: None,
+            resources_served: 0,
+            resources_refused: 0,
+            combinations_served: 0,
+            combinations_failed: 0,
+            visits: 0,
+        }
+    }
+}
+
+/// Outcome of a [`Galaxy::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GalaxyReport {
+    pub ticks: u64,
+    pub planets: Vec<PlanetStats>,
+    /// Complex resources the explorers managed to build.
+    pub targets_completed: u32,
+    /// Explorers that were on a planet when it was destroyed.
+    pub explorers_lost: u32,
+}
+
+impl GalaxyReport {
+    /// Statistics of the AirFryer planets only.
+    pub fn air_fryers(&self) -> impl Iterator<Item = &PlanetStats> {
+        self.planets
+            .iter()
+            .filter(|stats| stats.kind == PlanetKind::AirFryer)
+    }
+}
+
+impl fmt::Display for GalaxyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>3} {:<26} {:>9} {:>7} {:>9} {:>7} {:>6} {:>7} {:>8} {:>6} {:>6}",
+            "id",
+            "kind",
+            "destroyed",
+            "sunrays",
+            "asteroids",
+            "rockets",
+            "served",
+            "refused",
+            "combined",
+            "failed",
+            "visits"
+        )?;
+        for p in &self.planets {
+            let destroyed = p
+                .destroyed_at
+                .map_or_else(|| "-".to_string(), |tick| tick.to_string());
+            writeln!(
+                f,
+                "{:>3} {:<26} {:>9} {:>7} {:>9} {:>7} {:>6} {:>7} {:>8} {:>6} {:>6}",
+                p.id,
+                p.kind.to_string(),
+                destroyed,
+                p.sunrays,
+                p.asteroids,
+                p.rockets,
+                p.resources_served,
+                p.resources_refused,
+                p.combinations_served,
+                p.combinations_failed,
+                p.visits
+            )?;
+        }
+        write!(
+            f,
+            "{} ticks, {} targets completed, {} explorers lost",
+            self.ticks, self.targets_completed, self.explorers_lost
+        )
+    }
+}
+
+/// A set of planets driven by a stand-in orchestrator.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{Galaxy, GalaxyConfig};
+/// use common_game::components::forge::Forge;
+///
+/// let forge = Forge::new().unwrap();
+/// let config = GalaxyConfig {
+///     ticks: 50,
+///     ..GalaxyConfig::default()
+/// };
+/// let report = Galaxy::new(config).run(&forge).unwrap();
+/// println!("{}", report);
+/// ```
+pub struct Galaxy {
+    config: GalaxyConfig,
+}
+
+impl Galaxy {
+    pub fn new(config: GalaxyConfig) -> Galaxy {
+        Galaxy { config }
+    }
+
+    /// Runs the whole simulation and kills every planet before returning.
+    ///
+    /// The [`Forge`] is borrowed because only one can exist in a process.
+    pub fn run(self, forge: &Forge) -> Result<GalaxyReport, String> {
+        let mut sim = Simulation::start(self.config)?;
+        let result = sim.play(forge);
+        sim.shutdown();
+        result?;
+        Ok(sim.report())
+    }
+}
+
+// =========================================================================
+// ORCHESTRATOR SIDE
+// =========================================================================
+
+struct SimPlanet {
+    to_planet: Sender<OrchestratorToPlanet>,
+    from_planet: Receiver<PlanetToOrchestrator>,
+    explorers: Sender<ExplorerToPlanet>,
+    thread: Option<JoinHandle<()>>,
+    stats: PlanetStats,
+}
+
+impl SimPlanet {
+    fn spawn(id: ID, kind: PlanetKind, timeout: Duration) -> Result<SimPlanet, String> {
+        let (to_planet, rx_from_orc) = unbounded();
+        let (tx_to_orc, from_planet) = unbounded();
+        let (explorers, rx_from_explorers) = unbounded();
+
+        let mut planet = match &kind {
+            PlanetKind::AirFryer => create_planet(
+                id,
+                common_planet::PlanetType::C,
+                PlanetAI::new(),
+                (rx_from_orc, tx_to_orc),
+                rx_from_explorers,
+            )?,
+            PlanetKind::Provider(resources) => common_planet::Planet::new(
+                id,
+                common_planet::PlanetType::B,
+                Box::new(MockAI::with_config(MockConfig {
+                    resources: resources.clone(),
+                    recipes: vec![],
+                    defends: false,
+                })),
+                resources.clone(),
+                vec![],
+                (rx_from_orc, tx_to_orc),
+                rx_from_explorers,
+            )?,
+        };
+        let thread = thread::spawn(move || {
+            if let Err(err) = planet.run() {
+                log::error!("planet {} stopped with an error: {}", id, err);
+            }
+        });
+
+        let sim_planet = SimPlanet {
+            to_planet,
+            from_planet,
+            explorers,
+            thread: Some(thread),
+            stats: PlanetStats::new(id, kind),
+        };
+        sim_planet.request(OrchestratorToPlanet::StartPlanetAI, timeout)?;
+        Ok(sim_planet)
+    }
+
+    fn alive(&self) -> bool {
+        self.stats.destroyed_at.is_none()
+    }
+
+    /// Sends a message and waits for the planet acknowledgement.
+    fn request(
+        &self,
+        msg: OrchestratorToPlanet,
+        timeout: Duration,
+    ) -> Result<PlanetToOrchestrator, String> {
+        let id = self.stats.id;
+        self.to_planet
+            .send(msg)
+            .map_err(|_| format!("planet {} is unreachable", id))?;
+        self.from_planet
+            .recv_timeout(timeout)
+            .map_err(|_| format!("planet {} didn't answer in time", id))
+    }
+}
+
+struct Bot {
+    id: ID,
+    /// Index of the planet the bot is on, `None` once it's lost.
+    planet: Option<usize>,
+    sender: Sender<PlanetToExplorer>,
+    inbox: Receiver<PlanetToExplorer>,
+    inventory: Inventory,
+    target: ComplexResourceType,
+}
+
+impl Bot {
+    /// Drops the responses that came in after an earlier request timed out,
+    /// so they aren't read as the answer to the next one.
+    fn drain_inbox(&self) {
+        while self.inbox.try_recv().is_ok() {}
+    }
+}
+
+struct Simulation {
+    config: GalaxyConfig,
+    rng: Rng,
+    planets: Vec<SimPlanet>,
+    bots: Vec<Bot>,
+    targets_completed: u32,
+    explorers_lost: u32,
+}
+
+impl Simulation {
+    fn start(config: GalaxyConfig) -> Result<Simulation, String> {
+        if config.planets.is_empty() {
+            return Err("a galaxy needs at least one planet".to_string());
+        }
+        if config.targets.is_empty() {
+            return Err("the explorers need at least one target".to_string());
+        }
+        let mut rng = Rng::new(config.seed);
+
+        let mut planets = Vec::with_capacity(config.planets.len());
+        for (id, kind) in config.planets.iter().enumerate() {
+            planets.push(SimPlanet::spawn(id as ID, kind.clone(), config.timeout)?);
+        }
+
+        // Explorer IDs follow the planet IDs, so they never collide
+        let mut bots = Vec::with_capacity(config.explorers);
+        for i in 0..config.explorers {
+            let (sender, inbox) = unbounded();
+            bots.push(Bot {
+                id: (planets.len() + i) as ID,
+                planet: None,
+                sender,
+                inbox,
+                inventory: Inventory::default(),
+                target: config.targets[rng.below(config.targets.len())],
+            });
+        }
+
+        let mut sim = Simulation {
+            config,
+            rng,
+            planets,
+            bots,
+            targets_completed: 0,
+            explorers_lost: 0,
+        };
+        for i in 0..sim.bots.len() {
+            let planet = i % sim.planets.len();
+            sim.land(i, planet)?;
+        }
+        Ok(sim)
+    }
+
+    fn play(&mut self, forge: &Forge) -> Result<(), String> {
+        for tick in 0..self.config.ticks {
+            for p in 0..self.planets.len() {
+                if self.planets[p].alive() && self.rng.chance(self.config.sunray_probability) {
+                    let sunray = OrchestratorToPlanet::Sunray(forge.generate_sunray());
+                    self.planets[p].request(sunray, self.config.timeout)?;
+                    self.planets[p].stats.sunrays += 1;
+                }
+            }
+            if tick >= self.config.asteroid_grace_ticks {
+                for p in 0..self.planets.len() {
+                    if self.planets[p].alive() && self.rng.chance(self.config.asteroid_probability)
+                    {
+                        self.asteroid(p, tick, forge)?;
+                    }
+                }
+            }
+            for b in 0..self.bots.len() {
+                self.act(b)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn asteroid(&mut self, p: usize, tick: u64, forge: &Forge) -> Result<(), String> {
+        let asteroid = OrchestratorToPlanet::Asteroid(forge.generate_asteroid());
+        let deflected = match self.planets[p].request(asteroid, self.config.timeout)? {
+            PlanetToOrchestrator::AsteroidAck { rocket, .. } => rocket.is_some(),
+            _ => return Err(format!("planet {} didn't acknowledge the asteroid", p)),
+        };
+        let planet = &mut self.planets[p];
+        planet.stats.asteroids += 1;
+        if deflected {
+            planet.stats.rockets += 1;
+            return Ok(());
+        }
+
+        // Without a rocket the planet is destroyed, together with its explorers
+        planet.request(OrchestratorToPlanet::KillPlanet, self.config.timeout)?;
+        planet.stats.destroyed_at = Some(tick);
+        for bot in self.bots.iter_mut().filter(|bot| bot.planet == Some(p)) {
+            bot.planet = None;
+            self.explorers_lost += 1;
+        }
+        Ok(())
+    }
+
+    /// Lets a bot take one action: deliver, gather, combine or travel.
+    fn act(&mut self, b: usize) -> Result<(), String> {
+        let Some(here) = self.bots[b].planet else {
+            return Ok(());
+        };
+
+        let bot = &mut self.bots[b];
+        if bot
+            .inventory
+            .take(Ingredient::Complex(bot.target))
+            .is_some()
+        {
+            self.targets_completed += 1;
+            bot.target = self.config.targets[self.rng.below(self.config.targets.len())];
+            return Ok(());
+        }
+
+        match bot.inventory.next_step(bot.target) {
+            Step::Gather(resource) if self.planets[here].stats.kind.generates(resource) => {
+                self.gather(b, here, resource)
+            }
+            Step::Gather(resource) => self.travel(b, |kind| kind.generates(resource)),
+            Step::Combine(target) if self.planets[here].stats.kind.combines() => {
+                self.combine(b, here, target)
+            }
+            Step::Combine(_) => self.travel(b, PlanetKind::combines),
+        }
+    }
+
+    fn gather(&mut self, b: usize, p: usize, resource: BasicResourceType) -> Result<(), String> {
+        let bot = &mut self.bots[b];
+        let planet = &mut self.planets[p];
+        bot.drain_inbox();
+        let _ = planet
+            .explorers
+            .send(ExplorerToPlanet::GenerateResourceRequest {
+                explorer_id: bot.id,
+                resource,
+            });
+        match bot.inbox.recv_timeout(self.config.timeout) {
+            Ok(PlanetToExplorer::GenerateResourceResponse {
+                resource: Some(resource),
+            }) => {
+                bot.inventory.add(GenericResource::BasicResources(resource));
+                planet.stats.resources_served += 1;
+            }
+            _ => planet.stats.resources_refused += 1,
+        }
+        Ok(())
+    }
+
+    fn combine(&mut self, b: usize, p: usize, target: ComplexResourceType) -> Result<(), String> {
+        let bot = &mut self.bots[b];
+        let planet = &mut self.planets[p];
+        let Some(msg) = bot.inventory.take_request(target) else {
+            return Err(format!(
+                "explorer {} lost the inputs of {:?}",
+                bot.id, target
+            ));
+        };
+        bot.drain_inbox();
+        let _ = planet
+            .explorers
+            .send(ExplorerToPlanet::CombineResourceRequest {
+                explorer_id: bot.id,
+                msg,
+            });
+        match bot.inbox.recv_timeout(self.config.timeout) {
+            Ok(PlanetToExplorer::CombineResourceResponse { complex_response }) => {
+                match complex_response {
+                    Ok(complex) => {
+                        bot.inventory
+                            .add(GenericResource::ComplexResources(complex));
+                        planet.stats.combinations_served += 1;
+                    }
+                    Err((_, left, right)) => {
+                        bot.inventory.add(left);
+                        bot.inventory.add(right);
+                        planet.stats.combinations_failed += 1;
+                    }
+                }
+                Ok(())
+            }
+            _ => Err(format!("planet {} swallowed a combination request", p)),
+        }
+    }
+
+    /// Moves a bot to a random living planet matching `wanted`.
+    ///
+    /// If no such planet is left, the bot gives up and picks another target.
+    fn travel(&mut self, b: usize, wanted: impl Fn(&PlanetKind) -> bool) -> Result<(), String> {
+        let here = self.bots[b].planet;
+        let candidates: Vec<usize> = (0..self.planets.len())
+            .filter(|p| Some(*p) != here && self.planets[*p].alive())
+            .filter(|p| wanted(&self.planets[*p].stats.kind))
+            .collect();
+        if candidates.is_empty() {
+            self.bots[b].target = self.config.targets[self.rng.below(self.config.targets.len())];
+            return Ok(());
+        }
+        let to = candidates[self.rng.below(candidates.len())];
+
+        if let Some(from) = here {
+            let explorer_id = self.bots[b].id;
+            self.planets[from].request(
+                OrchestratorToPlanet::OutgoingExplorerRequest { explorer_id },
+                self.config.timeout,
+            )?;
+        }
+        self.land(b, to)
+    }
+
+    fn land(&mut self, b: usize, p: usize) -> Result<(), String> {
+        let bot = &mut self.bots[b];
+        self.planets[p].request(
+            OrchestratorToPlanet::IncomingExplorerRequest {
+                explorer_id: bot.id,
+                new_sender: bot.sender.clone(),
+            },
+            self.config.timeout,
+        )?;
+        bot.planet = Some(p);
+        self.planets[p].stats.visits += 1;
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        for planet in self.planets.iter_mut() {
+            if planet.alive() {
+                let _ = planet.request(OrchestratorToPlanet::KillPlanet, self.config.timeout);
+            }
+            if let Some(thread) = planet.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    fn report(&self) -> GalaxyReport {
+        GalaxyReport {
+            ticks: self.config.ticks,
+            planets: self.planets.iter().map(|p| p.stats.clone()).collect(),
+            targets_completed: self.targets_completed,
+            explorers_lost: self.explorers_lost,
+        }
+    }
+}
+
+// =========================================================================
+// EXPLORER SIDE
+// =========================================================================
+
+/// Next thing an explorer has to do to build its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Gather(BasicResourceType),
+    Combine(ComplexResourceType),
+}
+
+#[derive(Default)]
+struct Inventory {
+    items: Vec<GenericResource>,
+}
+
+impl Inventory {
+    fn add(&mut self, resource: GenericResource) {
+        self.items.push(resource);
+    }
+
+    fn take(&mut self, ingredient: Ingredient) -> Option<GenericResource> {
+        let index = self
+            .items
+            .iter()
+            .position(|item| recipes::ingredient_of(item) == ingredient)?;
+        Some(self.items.swap_remove(index))
+    }
+
+    /// Walks the recipe tree of `target` depth-first and returns the first
+    /// missing piece, counting each owned resource only once.
+    fn next_step(&self, target: ComplexResourceType) -> Step {
+        let mut owned: HashMap<Ingredient, usize> = HashMap::new();
+        for item in &self.items {
+            *owned.entry(recipes::ingredient_of(item)).or_default() += 1;
+        }
+        // The target itself is never owned here, it's delivered as soon as it's built
+        missing(Ingredient::Complex(target), &mut owned).unwrap_or(Step::Combine(target))
+    }
+
+    fn take_request(&mut self, target: ComplexResourceType) -> Option<ComplexResourceRequest> {
+        let (left, right) = recipes::ingredients(target);
+        let left = self.take(left)?;
+        let Some(right) = self.take(right) else {
+            self.add(left);
+            return None;
+        };
+        match recipes::build_request(target, left, right) {
+            Ok(request) => Some(request),
+            Err((left, right)) => {
+                self.add(left);
+                self.add(right);
+                None
+            }
+        }
+    }
+}
+
+fn missing(ingredient: Ingredient, owned: &mut HashMap<Ingredient, usize>) -> Option<Step> {
+    if let Some(count) = owned.get_mut(&ingredient).filter(|count| **count > 0) {
+        *count -= 1;
+        return None;
+    }
+    match ingredient {
+        Ingredient::Basic(resource) => Some(Step::Gather(resource)),
+        Ingredient::Complex(target) => {
+            let (left, right) = recipes::ingredients(target);
+            missing(left, owned)
+                .or_else(|| missing(right, owned))
+                .or(Some(Step::Combine(target)))
+        }
+    }
+}
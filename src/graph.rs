@@ -0,0 +1,233 @@
+//! Dependency graph of the combination recipes.
+//!
+//! Every recipe takes two inputs, basic or complex, so the recipes of a
+//! planet form a graph from the basic resources up to AIPartner. A
+//! [`RecipeGraph`] tells which targets the planet completes with the basic
+//! resources it generates, which inputs must come from other planets, and
+//! draws the graph in Graphviz DOT.
+
+use std::fmt::Write;
+
+use common_game::components::planet::PlanetType;
+use common_game::components::resource::{BasicResourceType, ComplexResourceType};
+
+use crate::recipes::{self, Ingredient};
+
+/// Every basic resource, in the order of the listings.
+const BASICS: [BasicResourceType; 4] = [
+    BasicResourceType::Carbon,
+    BasicResourceType::Hydrogen,
+    BasicResourceType::Oxygen,
+    BasicResourceType::Silicon,
+];
+
+/// Every complex resource, ingredients before the recipes using them.
+const COMPLEX: [ComplexResourceType; 6] = [
+    ComplexResourceType::Diamond,
+    ComplexResourceType::Water,
+    ComplexResourceType::Life,
+    ComplexResourceType::Robot,
+    ComplexResourceType::Dolphin,
+    ComplexResourceType::AIPartner,
+];
+
+/// Inputs of a target that the planet can't provide.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExternalInputs {
+    /// Basic resources the planet doesn't generate.
+    pub basics: Vec<BasicResourceType>,
+    /// Complex resources the planet doesn't combine.
+    pub complex: Vec<ComplexResourceType>,
+}
+
+impl ExternalInputs {
+    pub fn is_empty(&self) -> bool {
+        self.basics.is_empty() && self.complex.is_empty()
+    }
+}
+
+/// Recipe graph of a planet, built from the same lists as the planet.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::RecipeGraph;
+/// use common_game::components::planet::PlanetType;
+/// use common_game::components::resource::{BasicResourceType, ComplexResourceType};
+///
+/// let graph = RecipeGraph::for_planet(PlanetType::C);
+/// // Carbon alone only makes Diamonds
+/// assert_eq!(graph.completable(), vec![ComplexResourceType::Diamond]);
+/// assert!(graph.external_basics().contains(&BasicResourceType::Silicon));
+/// assert!(graph.to_dot().contains("\"Robot\" -> \"AIPartner\""));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecipeGraph {
+    basics: Vec<BasicResourceType>,
+    recipes: Vec<ComplexResourceType>,
+}
+
+impl RecipeGraph {
+    /// A graph of the recipes of a planet generating `basics`.
+    pub fn new(
+        basics: impl IntoIterator<Item = BasicResourceType>,
+        recipes: impl IntoIterator<Item = ComplexResourceType>,
+    ) -> RecipeGraph {
+        let basics: Vec<BasicResourceType> = basics.into_iter().collect();
+        let recipes: Vec<ComplexResourceType> = recipes.into_iter().collect();
+        RecipeGraph {
+            basics: BASICS
+                .into_iter()
+                .filter(|basic| basics.contains(basic))
+                .collect(),
+            recipes: COMPLEX
+                .into_iter()
+                .filter(|recipe| recipes.contains(recipe))
+                .collect(),
+        }
+    }
+
+    /// The graph of an AirFryer planet of the given type, see [`create_planet`](crate::create_planet).
+    pub fn for_planet(planet_type: PlanetType) -> RecipeGraph {
+        RecipeGraph::new(
+            crate::supported_resources(),
+            crate::combination_rules(planet_type),
+        )
+    }
+
+    /// Basic resources generated by the planet.
+    pub fn basics(&self) -> &[BasicResourceType] {
+        &self.basics
+    }
+
+    /// Recipes of the planet, ingredients first.
+    pub fn recipes(&self) -> &[ComplexResourceType] {
+        &self.recipes
+    }
+
+    /// Whether the planet makes `target` from the basic resources it
+    /// generates, combining every complex ingredient itself.
+    pub fn can_complete(&self, target: ComplexResourceType) -> bool {
+        self.recipes.contains(&target) && self.external_inputs(target).is_empty()
+    }
+
+    /// The recipes the planet completes alone, ingredients first.
+    pub fn completable(&self) -> Vec<ComplexResourceType> {
+        self.recipes
+            .iter()
+            .copied()
+            .filter(|target| self.can_complete(*target))
+            .collect()
+    }
+
+    /// What other planets must provide to make `target` here.
+    ///
+    /// Complex ingredients are followed down to the basic resources as long
+    /// as the planet has their recipe; the other ones are listed as they are.
+    pub fn external_inputs(&self, target: ComplexResourceType) -> ExternalInputs {
+        let mut external = ExternalInputs::default();
+        if !self.recipes.contains(&target) {
+            external.complex.push(target);
+            return external;
+        }
+        let mut pending = vec![target];
+        let mut seen = vec![target];
+        while let Some(recipe) = pending.pop() {
+            let (left, right) = recipes::ingredients(recipe);
+            for ingredient in [left, right] {
+                match ingredient {
+                    Ingredient::Basic(basic) => {
+                        if !self.basics.contains(&basic) && !external.basics.contains(&basic) {
+                            external.basics.push(basic);
+                        }
+                    }
+                    Ingredient::Complex(complex) if seen.contains(&complex) => {}
+                    Ingredient::Complex(complex) => {
+                        seen.push(complex);
+                        if self.recipes.contains(&complex) {
+                            pending.push(complex);
+                        } else {
+                            external.complex.push(complex);
+                        }
+                    }
+                }
+            }
+        }
+        external.basics = ordered(&BASICS, &external.basics);
+        external.complex = ordered(&COMPLEX, &external.complex);
+        external
+    }
+
+    /// Basic resources the planet needs from elsewhere for any of its recipes.
+    pub fn external_basics(&self) -> Vec<BasicResourceType> {
+        let needed: Vec<BasicResourceType> = self
+            .recipes
+            .iter()
+            .flat_map(|target| self.external_inputs(*target).basics)
+            .collect();
+        ordered(&BASICS, &needed)
+    }
+
+    /// The graph in Graphviz DOT, an edge from each ingredient to its recipe.
+    ///
+    /// Resources the planet generates or combines are drawn solid, the ones
+    /// other planets must provide dashed.
+    pub fn to_dot(&self) -> String {
+        let mut basics = Vec::new();
+        let mut complex = self.recipes.clone();
+        let mut edges = Vec::new();
+        for target in &self.recipes {
+            let (left, right) = recipes::ingredients(*target);
+            // Diamond takes two Carbon, one edge is enough
+            let inputs = if left == right {
+                vec![left]
+            } else {
+                vec![left, right]
+            };
+            for ingredient in inputs {
+                let name = match ingredient {
+                    Ingredient::Basic(basic) => {
+                        basics.push(basic);
+                        format!("{:?}", basic)
+                    }
+                    Ingredient::Complex(input) => {
+                        complex.push(input);
+                        format!("{:?}", input)
+                    }
+                };
+                edges.push((name, format!("{:?}", target)));
+            }
+        }
+
+        let mut dot = String::from("digraph recipes {\n    rankdir=LR;\n");
+        for basic in ordered(&BASICS, &basics) {
+            let style = if self.basics.contains(&basic) {
+                "solid"
+            } else {
+                "dashed"
+            };
+            let _ = writeln!(dot, "    \"{:?}\" [shape=ellipse, style={}];", basic, style);
+        }
+        for resource in ordered(&COMPLEX, &complex) {
+            let style = if self.recipes.contains(&resource) {
+                "solid"
+            } else {
+                "dashed"
+            };
+            let _ = writeln!(dot, "    \"{:?}\" [shape=box, style={}];", resource, style);
+        }
+        for (from, to) in edges {
+            let _ = writeln!(dot, "    \"{}\" -> \"{}\";", from, to);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// The items of `all` found in `items`, in the order of `all` and once each.
+fn ordered<T: Copy + PartialEq>(all: &[T], items: &[T]) -> Vec<T> {
+    all.iter()
+        .copied()
+        .filter(|item| items.contains(item))
+        .collect()
+}
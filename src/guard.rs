@@ -0,0 +1,267 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use common_game::components::{
+    planet::{self, DummyPlanetState, PlanetState},
+    resource::{Combinator, Generator},
+    rocket::Rocket,
+    sunray::Sunray,
+};
+use common_game::protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer};
+use common_game::utils::ID;
+
+/// How many incidents are kept in the [`IncidentHandle`] history.
+const MAX_INCIDENTS: usize = 32;
+
+/// A panic caught by the [`GuardedAI`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incident {
+    /// The `PlanetAI` callback that panicked.
+    pub callback: &'static str,
+    /// The message being handled, when the callback receives one.
+    pub input: Option<String>,
+    /// The panic payload.
+    pub panic: String,
+}
+
+#[derive(Debug, Default)]
+struct IncidentLog {
+    total: u32,
+    recent: VecDeque<Incident>,
+}
+
+/// Cloneable handle to the incidents caught by a [`GuardedAI`].
+///
+/// A planet with incidents keeps running, but its AI may be in an
+/// inconsistent state, so the orchestrator should treat it as degraded.
+#[derive(Debug, Clone, Default)]
+pub struct IncidentHandle(Arc<Mutex<IncidentLog>>);
+
+impl IncidentHandle {
+    /// Number of panics caught since the planet was created.
+    pub fn count(&self) -> u32 {
+        self.lock().total
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.count() > 0
+    }
+
+    /// The last incidents, oldest first.
+    pub fn recent(&self) -> Vec<Incident> {
+        self.lock().recent.iter().cloned().collect()
+    }
+
+    fn push(&self, incident: Incident) {
+        log::error!(
+            "planet AI panicked in {} ({}): {}",
+            incident.callback,
+            incident.input.as_deref().unwrap_or("no input"),
+            incident.panic
+        );
+        let mut log = self.lock();
+        log.total += 1;
+        if log.recent.len() == MAX_INCIDENTS {
+            log.recent.pop_front();
+        }
+        log.recent.push_back(incident);
+    }
+
+    // The log is only touched outside of the guarded calls, poisoning can't leave it half-written
+    fn lock(&self) -> MutexGuard<'_, IncidentLog> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// `PlanetAI` wrapper that keeps the planet thread alive when the inner AI panics.
+///
+/// Every panic is logged, recorded in the [`IncidentHandle`] and replaced by a
+/// safe fallback: no response to the explorer, no rocket, and the raw planet
+/// state for the internal state report.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{GuardedAI, PlanetAI, create_planet};
+/// use common_game::components::planet::PlanetType;
+/// use crossbeam_channel::unbounded;
+///
+/// let (_tx_to_planet, rx_from_orc) = unbounded();
+/// let (tx_to_orc, _rx_from_planet) = unbounded();
+/// let (_tx_from_explorer, rx_at_planet) = unbounded();
+///
+/// let ai = GuardedAI::new(PlanetAI::new());
+/// let incidents = ai.incident_handle();
+/// let planet = create_planet(1, PlanetType::C, ai, (rx_from_orc, tx_to_orc), rx_at_planet);
+/// assert!(planet.is_ok());
+/// assert!(!incidents.is_degraded());
+/// ```
+pub struct GuardedAI<A: planet::PlanetAI> {
+    inner: A,
+    incidents: IncidentHandle,
+}
+
+impl<A: planet::PlanetAI> GuardedAI<A> {
+    pub fn new(inner: A) -> GuardedAI<A> {
+        GuardedAI {
+            inner,
+            incidents: IncidentHandle::default(),
+        }
+    }
+
+    /// Returns a handle to the caught panics, to be taken before the AI is
+    /// moved into the planet.
+    pub fn incident_handle(&self) -> IncidentHandle {
+        self.incidents.clone()
+    }
+
+    /// Runs `f` on the inner AI, recording a panic as an incident.
+    fn guard<T>(
+        &mut self,
+        callback: &'static str,
+        input: Option<String>,
+        f: impl FnOnce(&mut A) -> T,
+    ) -> Option<T> {
+        let inner = &mut self.inner;
+        match panic::catch_unwind(AssertUnwindSafe(|| f(inner))) {
+            Ok(value) => Some(value),
+            Err(payload) => {
+                self.incidents.push(Incident {
+                    callback,
+                    input,
+                    panic: panic_message(payload.as_ref()),
+                });
+                None
+            }
+        }
+    }
+}
+
+impl<A: planet::PlanetAI> planet::PlanetAI for GuardedAI<A> {
+    fn handle_sunray(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        combinator: &Combinator,
+        sunray: Sunray,
+    ) {
+        self.guard("handle_sunray", None, |ai| {
+            ai.handle_sunray(state, generator, combinator, sunray)
+        });
+    }
+
+    fn handle_asteroid(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        combinator: &Combinator,
+    ) -> Option<Rocket> {
+        self.guard("handle_asteroid", None, |ai| {
+            ai.handle_asteroid(state, generator, combinator)
+        })
+        .flatten()
+    }
+
+    fn handle_internal_state_req(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        combinator: &Combinator,
+    ) -> DummyPlanetState {
+        let report = self.guard("handle_internal_state_req", None, |ai| {
+            ai.handle_internal_state_req(state, generator, combinator)
+        });
+        report.unwrap_or_else(|| state.to_dummy())
+    }
+
+    fn handle_explorer_msg(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        combinator: &Combinator,
+        msg: ExplorerToPlanet,
+    ) -> Option<PlanetToExplorer> {
+        let input = describe(&msg);
+        self.guard("handle_explorer_msg", Some(input), |ai| {
+            ai.handle_explorer_msg(state, generator, combinator, msg)
+        })
+        .flatten()
+    }
+
+    fn on_explorer_arrival(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        combinator: &Combinator,
+        explorer_id: ID,
+    ) {
+        let input = format!("explorer {}", explorer_id);
+        self.guard("on_explorer_arrival", Some(input), |ai| {
+            ai.on_explorer_arrival(state, generator, combinator, explorer_id)
+        });
+    }
+
+    fn on_explorer_departure(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        combinator: &Combinator,
+        explorer_id: ID,
+    ) {
+        let input = format!("explorer {}", explorer_id);
+        self.guard("on_explorer_departure", Some(input), |ai| {
+            ai.on_explorer_departure(state, generator, combinator, explorer_id)
+        });
+    }
+
+    fn on_start(&mut self, state: &PlanetState, generator: &Generator, combinator: &Combinator) {
+        self.guard("on_start", None, |ai| {
+            ai.on_start(state, generator, combinator)
+        });
+    }
+
+    fn on_stop(&mut self, state: &PlanetState, generator: &Generator, combinator: &Combinator) {
+        self.guard("on_stop", None, |ai| {
+            ai.on_stop(state, generator, combinator)
+        });
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Short description of an explorer message for the incident log.
+fn describe(msg: &ExplorerToPlanet) -> String {
+    match msg {
+        ExplorerToPlanet::SupportedResourceRequest { explorer_id } => {
+            format!("SupportedResourceRequest from explorer {}", explorer_id)
+        }
+        ExplorerToPlanet::SupportedCombinationRequest { explorer_id } => {
+            format!("SupportedCombinationRequest from explorer {}", explorer_id)
+        }
+        ExplorerToPlanet::GenerateResourceRequest {
+            explorer_id,
+            resource,
+        } => format!(
+            "GenerateResourceRequest({:?}) from explorer {}",
+            resource, explorer_id
+        ),
+        ExplorerToPlanet::CombineResourceRequest { explorer_id, .. } => {
+            format!("CombineResourceRequest from explorer {}", explorer_id)
+        }
+        ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id } => {
+            format!("AvailableEnergyCellRequest from explorer {}", explorer_id)
+        }
+    }
+}
@@ -17,19 +17,113 @@ use crossbeam_channel::{Receiver, Sender};
 pub(crate) mod planet;
 pub use crate::planet::PlanetAI;
 
+pub(crate) mod config;
+pub use crate::config::PlanetConfig;
+
+pub(crate) mod clock;
+pub use crate::clock::{Clock, FakeClock, SystemClock};
+
+pub(crate) mod credits;
+pub use crate::credits::{CreditAccount, CreditConfig};
+
+pub(crate) mod error;
+pub use crate::error::PlanetError;
+
+pub(crate) mod energy;
+pub use crate::energy::{
+    ConsumerTotal, EnergyConfig, EnergyConsumer, EnergyEntry, EnergyFlow, EnergyReport,
+};
+
+pub(crate) mod forecast;
+pub use crate::forecast::{EnergyForecast, ForecastConfig};
+
+pub(crate) mod overflow;
+pub use crate::overflow::{OverflowPolicy, SunrayStats};
+
+pub(crate) mod stock;
+pub use crate::stock::{StockConfig, StockReport};
+
+pub(crate) mod bandit;
+pub use crate::bandit::{ArmStats, AsteroidPolicy, BanditConfig, BanditStats, SunrayPolicy};
+
+pub(crate) mod alliance;
+pub use crate::alliance::{AllianceConfig, Tier};
+
+pub(crate) mod explain;
+pub use crate::explain::{
+    Callback, Explanation, ExplanationConfig, ExplanationHandle, FiredRule, Observation, Outcome,
+};
+
+pub(crate) mod extension;
+pub use crate::extension::{
+    ExtensionClient, ExtensionConfig, ExtensionRequest, ExtensionResponse, PlanetStatus,
+};
+
+pub(crate) mod threat;
+pub use crate::threat::{ThreatBus, ThreatEvent};
+
+pub(crate) mod warning;
+pub use crate::warning::{Warning, WarningConfig};
+
+pub(crate) mod covert;
+pub use crate::covert::{CovertConfig, CovertDecoder, Signal};
+
+pub(crate) mod lifecycle;
+pub use crate::lifecycle::{LifecycleEvent, LifecyclePhase, Rejection, Transition};
+
+pub(crate) mod rules;
+pub use crate::rules::{Comparison, DEFAULT_RULES, Fact, Rule, RuleAction, RuleSet, RuleTest};
+
+pub(crate) mod reputation;
+pub use crate::reputation::{ExplorerReputation, ReputationConfig};
+
+pub(crate) mod report;
+pub use crate::report::{PlanetReport, ReportHandle};
+
+pub(crate) mod guard;
+pub use crate::guard::{GuardedAI, Incident, IncidentHandle};
+
+pub(crate) mod galaxy;
+pub use crate::galaxy::{Galaxy, GalaxyConfig, GalaxyReport, PlanetKind, PlanetStats};
+
+pub(crate) mod tournament;
+pub use crate::tournament::{
+    Entrant, EntrantScore, Event, EventStream, ScoreWeights, StreamConfig, Tournament,
+    TournamentReport,
+};
+
+pub(crate) mod tuning;
+pub use crate::tuning::{Knob, Search, Sensitivity, Trial, Tuner, TuningReport};
+
+pub(crate) mod graph;
+pub use crate::graph::{ExternalInputs, RecipeGraph};
+
+pub(crate) mod mock_planet;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+pub(crate) mod recipes;
+pub(crate) mod rng;
+
 #[cfg(test)]
 mod tests;
 
 /// Creates a new planet instance with predefined resource capabilities.
 ///
-/// This function creates a Type-C planet that can generate Carbon as a basic resource
-/// and combine various complex resources including Water, Life, Dolphin, Robot,
-/// Diamond, and AIPartner.
+/// The planet generates Carbon as a basic resource and combines as many complex
+/// resources as its type allows:
+///
+/// * Type C (the AirFryer layout): one energy cell, a rocket, and all the six
+///   recipes (Water, Life, Dolphin, Robot, Diamond, and AIPartner).
+/// * Type B: one energy cell, no rocket, and Diamond, the only recipe made of Carbon alone.
+/// * Types A and D: five energy cells and no recipes; only type A can build rockets.
 ///
 /// # Arguments
 ///
 /// * `id` - Unique identifier for the planet.
-/// * `planet_ai` - Implementation of the Planet AI logic.
+/// * `planet_type` - Layout of the planet, see above.
+/// * `planet_ai` - Implementation of the Planet AI logic, usually a [`PlanetAI`],
+///   possibly wrapped in a [`GuardedAI`].
 /// * `orchestrator_channels` - A tuple of (Receiver for Orchestrator, Sender to Orchestrator).
 /// * `explorers_receiver` - Channel to receive incoming requests from explorers.
 ///
@@ -42,6 +136,7 @@ mod tests;
 ///
 /// ```rust
 /// use air_fryer::{create_planet, PlanetAI};
+/// use common_game::components::planet::PlanetType;
 /// use crossbeam_channel::unbounded;
 ///
 /// // Orchestrator <=> Planet channels
@@ -57,6 +152,7 @@ mod tests;
 ///
 /// match create_planet(
 ///     planet_id,
+///     PlanetType::C,
 ///     ai,
 ///     (rx_from_orc, tx_to_orc),
 ///     rx_at_planet,
@@ -67,16 +163,124 @@ mod tests;
 /// ```
 pub fn create_planet(
     id: ID,
-    planet_ai: planet::PlanetAI,
+    planet_type: common_planet::PlanetType,
+    planet_ai: impl common_planet::PlanetAI + 'static,
     orchestrator_channels: (Receiver<OrchestratorToPlanet>, Sender<PlanetToOrchestrator>),
     explorers_receiver: Receiver<ExplorerToPlanet>,
 ) -> Result<common_planet::Planet, String> {
     common_planet::Planet::new(
         id,
-        common_planet::PlanetType::C,
+        planet_type,
         Box::new(planet_ai),
-        vec![BasicResourceType::Carbon],
-        vec![
+        supported_resources(),
+        combination_rules(planet_type),
+        orchestrator_channels,
+        explorers_receiver,
+    )
+}
+
+/// Creates a planet like [`create_planet`], along with the extension channel
+/// pair for the services `ExplorerToPlanet` can't express.
+///
+/// The planet speaks the common protocol exactly like one created by
+/// [`create_planet`]; the returned [`ExtensionClient`] is for allied explorers
+/// and tools. Its requests are served whenever the planet handles a message.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{create_planet_with_extension, ExtensionRequest, PlanetAI};
+/// use common_game::components::planet::PlanetType;
+/// use crossbeam_channel::unbounded;
+///
+/// let (_tx_to_planet, rx_from_orc) = unbounded();
+/// let (tx_to_orc, _rx_from_planet) = unbounded();
+/// let (_tx_from_explorer, rx_at_planet) = unbounded();
+///
+/// let (_planet, extension) = create_planet_with_extension(
+///     42,
+///     PlanetType::C,
+///     PlanetAI::new(),
+///     (rx_from_orc, tx_to_orc),
+///     rx_at_planet,
+/// )
+/// .expect("valid planet");
+/// extension.send(ExtensionRequest::Status).unwrap();
+/// ```
+pub fn create_planet_with_extension(
+    id: ID,
+    planet_type: common_planet::PlanetType,
+    mut planet_ai: PlanetAI,
+    orchestrator_channels: (Receiver<OrchestratorToPlanet>, Sender<PlanetToOrchestrator>),
+    explorers_receiver: Receiver<ExplorerToPlanet>,
+) -> Result<(common_planet::Planet, ExtensionClient), String> {
+    let extension = planet_ai.attach_extension();
+    let planet = create_planet(
+        id,
+        planet_type,
+        planet_ai,
+        orchestrator_channels,
+        explorers_receiver,
+    )?;
+    Ok((planet, extension))
+}
+
+/// Creates a planet like [`create_planet`], sharing asteroid sightings and
+/// rocket readiness with the other planets on the [`ThreatBus`].
+///
+/// It replaces any bus set in the [`PlanetConfig::threat_bus`] of the AI.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{PlanetAI, ThreatBus, create_planet_with_threat_bus};
+/// use common_game::components::planet::PlanetType;
+/// use crossbeam_channel::unbounded;
+///
+/// let bus = ThreatBus::default();
+/// for id in [1, 2] {
+///     let (_tx_to_planet, rx_from_orc) = unbounded();
+///     let (tx_to_orc, _rx_from_planet) = unbounded();
+///     let (_tx_from_explorer, rx_at_planet) = unbounded();
+///     create_planet_with_threat_bus(
+///         id,
+///         PlanetType::C,
+///         PlanetAI::new(),
+///         (rx_from_orc, tx_to_orc),
+///         rx_at_planet,
+///         &bus,
+///     )
+///     .expect("valid planet");
+/// }
+/// assert_eq!(bus.members(), 2);
+/// ```
+pub fn create_planet_with_threat_bus(
+    id: ID,
+    planet_type: common_planet::PlanetType,
+    mut planet_ai: PlanetAI,
+    orchestrator_channels: (Receiver<OrchestratorToPlanet>, Sender<PlanetToOrchestrator>),
+    explorers_receiver: Receiver<ExplorerToPlanet>,
+    bus: &ThreatBus,
+) -> Result<common_planet::Planet, String> {
+    planet_ai.join_threat_bus(bus);
+    create_planet(
+        id,
+        planet_type,
+        planet_ai,
+        orchestrator_channels,
+        explorers_receiver,
+    )
+}
+
+/// Basic resources generated by every AirFryer planet, see [`create_planet`].
+fn supported_resources() -> Vec<BasicResourceType> {
+    vec![BasicResourceType::Carbon]
+}
+
+/// Recipes of an AirFryer planet of the given type, see [`create_planet`].
+fn combination_rules(planet_type: common_planet::PlanetType) -> Vec<ComplexResourceType> {
+    match planet_type {
+        common_planet::PlanetType::C => vec![
             ComplexResourceType::Water,
             ComplexResourceType::Life,
             ComplexResourceType::Dolphin,
@@ -84,7 +288,7 @@ pub fn create_planet(
             ComplexResourceType::Diamond,
             ComplexResourceType::AIPartner,
         ],
-        orchestrator_channels,
-        explorers_receiver,
-    )
+        common_planet::PlanetType::B => vec![ComplexResourceType::Diamond],
+        common_planet::PlanetType::A | common_planet::PlanetType::D => vec![],
+    }
 }
@@ -0,0 +1,190 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::time::Instant;
+
+use common_game::utils::ID;
+
+use crate::error::PlanetError;
+
+/// How many transitions are kept in the history.
+const HISTORY: usize = 64;
+
+/// Phase of the [`PlanetAI`](crate::PlanetAI) lifecycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LifecyclePhase {
+    /// Created, never started.
+    #[default]
+    Created,
+    Running,
+    Stopped,
+}
+
+/// Something the planet is told by the orchestrator or an explorer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    Start,
+    Stop,
+    Arrival(ID),
+    Departure(ID),
+    /// An explorer request that needs the planet running and the explorer on it.
+    Request(ID),
+}
+
+/// Why an event was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    AlreadyRunning,
+    NotRunning,
+    /// The explorer arrived twice without leaving.
+    AlreadyOnPlanet,
+    /// The explorer left, or sent a request, without arriving.
+    NotOnPlanet,
+}
+
+impl Rejection {
+    fn planet_error(&self) -> PlanetError {
+        match self {
+            Rejection::AlreadyRunning | Rejection::NotRunning => PlanetError::PlanetStopped,
+            Rejection::AlreadyOnPlanet | Rejection::NotOnPlanet => {
+                PlanetError::ExplorerNotRegistered
+            }
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Rejection::AlreadyRunning => "the planet is already running",
+            Rejection::NotRunning => "the planet is not running",
+            Rejection::AlreadyOnPlanet => "the explorer is already on the planet",
+            Rejection::NotOnPlanet => "the explorer is not on the planet",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// An event applied to the lifecycle, as kept in the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub at: Instant,
+    pub event: LifecycleEvent,
+    pub from: LifecyclePhase,
+    pub to: LifecyclePhase,
+    /// `Some` when the event was illegal and left the lifecycle untouched.
+    pub rejected: Option<Rejection>,
+}
+
+/// Lifecycle state machine of the [`PlanetAI`](crate::PlanetAI).
+///
+/// ```text
+/// Created --Start--> Running --Stop--> Stopped --Start--> Running
+/// Running --Arrival(id)/Departure(id)--> Running
+/// ```
+///
+/// Any other event is rejected, logged and recorded in the history, along
+/// with every legal transition. Accepted requests change nothing and are not
+/// recorded. Starting and stopping forget the explorers on the planet.
+pub(crate) struct Lifecycle {
+    phase: LifecyclePhase,
+    explorers: HashSet<ID>,
+    history: VecDeque<Transition>,
+}
+
+impl Lifecycle {
+    pub(crate) fn new() -> Lifecycle {
+        Lifecycle {
+            phase: LifecyclePhase::Created,
+            explorers: HashSet::new(),
+            history: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn phase(&self) -> LifecyclePhase {
+        self.phase
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.phase == LifecyclePhase::Running
+    }
+
+    pub(crate) fn explorer_count(&self) -> usize {
+        self.explorers.len()
+    }
+
+    /// Explorers on the planet, sorted by ID.
+    pub(crate) fn explorers(&self) -> Vec<ID> {
+        let mut explorers: Vec<ID> = self.explorers.iter().copied().collect();
+        explorers.sort_unstable();
+        explorers
+    }
+
+    /// Checks that an explorer request can be served.
+    pub(crate) fn request(&mut self, explorer_id: ID, now: Instant) -> Result<(), PlanetError> {
+        self.apply(LifecycleEvent::Request(explorer_id), now)
+            .map_err(|rejection| rejection.planet_error())
+    }
+
+    pub(crate) fn apply(&mut self, event: LifecycleEvent, now: Instant) -> Result<(), Rejection> {
+        let from = self.phase;
+        let running = from == LifecyclePhase::Running;
+        let result = match event {
+            LifecycleEvent::Start if running => Err(Rejection::AlreadyRunning),
+            LifecycleEvent::Start => {
+                self.phase = LifecyclePhase::Running;
+                self.explorers.clear();
+                Ok(())
+            }
+            LifecycleEvent::Stop if !running => Err(Rejection::NotRunning),
+            LifecycleEvent::Stop => {
+                self.phase = LifecyclePhase::Stopped;
+                self.explorers.clear();
+                Ok(())
+            }
+            LifecycleEvent::Arrival(_) if !running => Err(Rejection::NotRunning),
+            LifecycleEvent::Arrival(explorer_id) => {
+                if self.explorers.insert(explorer_id) {
+                    Ok(())
+                } else {
+                    Err(Rejection::AlreadyOnPlanet)
+                }
+            }
+            LifecycleEvent::Departure(explorer_id) => {
+                if self.explorers.remove(&explorer_id) {
+                    Ok(())
+                } else {
+                    Err(Rejection::NotOnPlanet)
+                }
+            }
+            LifecycleEvent::Request(_) if !running => Err(Rejection::NotRunning),
+            LifecycleEvent::Request(explorer_id) if !self.explorers.contains(&explorer_id) => {
+                Err(Rejection::NotOnPlanet)
+            }
+            LifecycleEvent::Request(_) => return Ok(()),
+        };
+
+        if let Err(rejection) = result {
+            log::warn!("illegal {:?} while {:?}: {}", event, from, rejection);
+        }
+        self.record(Transition {
+            at: now,
+            event,
+            from,
+            to: self.phase,
+            rejected: result.err(),
+        });
+        result
+    }
+
+    fn record(&mut self, transition: Transition) {
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(transition);
+    }
+
+    /// The last transitions, oldest first.
+    pub(crate) fn history(&self) -> Vec<Transition> {
+        self.history.iter().copied().collect()
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+
+use common_game::{
+    components::{
+        planet::{self, DummyPlanetState, PlanetState},
+        resource::{BasicResource, BasicResourceType, Combinator, ComplexResourceType, Generator},
+        rocket::Rocket,
+        sunray::Sunray,
+    },
+    protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer},
+};
+
+use crate::error::PlanetError;
+use crate::planet::combine;
+use crate::recipes;
+
+/// What a [`MockAI`] planet offers to explorers.
+///
+/// The configuration must be compatible with the planet type passed to
+/// `Planet::new`: the generated resources and recipes must also be the planet
+/// rules, and a planet that defends itself must be able to build rockets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockConfig {
+    /// Basic resources generated on request.
+    pub resources: Vec<BasicResourceType>,
+    /// Complex resources combined on request.
+    pub recipes: Vec<ComplexResourceType>,
+    /// Whether the planet builds rockets against asteroids.
+    pub defends: bool,
+}
+
+impl Default for MockConfig {
+    /// Every basic resource, no recipes and no defense.
+    fn default() -> Self {
+        MockConfig {
+            resources: vec![
+                BasicResourceType::Oxygen,
+                BasicResourceType::Hydrogen,
+                BasicResourceType::Silicon,
+                BasicResourceType::Carbon,
+            ],
+            recipes: vec![],
+            defends: false,
+        }
+    }
+}
+
+/// Resource-provider planet AI used to spin up partner planets next to AirFryer.
+///
+/// It charges the first empty cell with every sunray, serves explorers with
+/// any charged cell and, when configured to defend itself, keeps a rocket ready.
+pub struct MockAI {
+    resources: HashSet<BasicResourceType>,
+    recipes: HashSet<ComplexResourceType>,
+    defends: bool,
+}
+
+impl MockAI {
+    pub fn new() -> Self {
+        Self::with_config(MockConfig::default())
+    }
+
+    pub fn with_config(config: MockConfig) -> Self {
+        Self {
+            resources: config.resources.into_iter().collect(),
+            recipes: config.recipes.into_iter().collect(),
+            defends: config.defends,
+        }
+    }
+}
+
+impl Default for MockAI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn charged_cell(state: &PlanetState) -> Option<usize> {
+    (0..state.cells_count()).find(|i| state.cell(*i).is_charged())
+}
+
+impl planet::PlanetAI for MockAI {
+    fn handle_sunray(
+        &mut self,
+        state: &mut PlanetState,
+        _generator: &Generator,
+        _combinator: &Combinator,
+        sunray: Sunray,
+    ) {
+        // With every cell charged the sunray would be wasted, so one of them becomes a rocket
+        let all_charged = (0..state.cells_count()).all(|i| state.cell(i).is_charged());
+        if all_charged && self.defends && !state.has_rocket() {
+            let _ = state.build_rocket(0);
+        }
+        state.charge_cell(sunray);
+    }
+
+    fn handle_asteroid(
+        &mut self,
+        state: &mut PlanetState,
+        _generator: &Generator,
+        _combinator: &Combinator,
+    ) -> Option<Rocket> {
+        if !self.defends {
+            return None;
+        }
+        if !state.has_rocket() {
+            let cell = charged_cell(state)?;
+            state.build_rocket(cell).ok()?;
+        }
+        state.take_rocket()
+    }
+
+    fn handle_internal_state_req(
+        &mut self,
+        state: &mut PlanetState,
+        _generator: &Generator,
+        _combinator: &Combinator,
+    ) -> DummyPlanetState {
+        state.to_dummy()
+    }
+
+    fn handle_explorer_msg(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        combinator: &Combinator,
+        msg: ExplorerToPlanet,
+    ) -> Option<PlanetToExplorer> {
+        match msg {
+            ExplorerToPlanet::SupportedResourceRequest { explorer_id: _ } => {
+                Some(PlanetToExplorer::SupportedResourceResponse {
+                    resource_list: self.resources.clone(),
+                })
+            }
+            ExplorerToPlanet::SupportedCombinationRequest { explorer_id: _ } => {
+                Some(PlanetToExplorer::SupportedCombinationResponse {
+                    combination_list: self.recipes.clone(),
+                })
+            }
+            ExplorerToPlanet::GenerateResourceRequest {
+                explorer_id: _,
+                resource,
+            } => {
+                if !self.resources.contains(&resource) {
+                    return Some(PlanetToExplorer::GenerateResourceResponse { resource: None });
+                }
+                let generated = match state.full_cell() {
+                    Some((energy_cell, _)) => match resource {
+                        BasicResourceType::Oxygen => generator
+                            .make_oxygen(energy_cell)
+                            .map(BasicResource::Oxygen),
+                        BasicResourceType::Hydrogen => generator
+                            .make_hydrogen(energy_cell)
+                            .map(BasicResource::Hydrogen),
+                        BasicResourceType::Carbon => generator
+                            .make_carbon(energy_cell)
+                            .map(BasicResource::Carbon),
+                        BasicResourceType::Silicon => generator
+                            .make_silicon(energy_cell)
+                            .map(BasicResource::Silicon),
+                    },
+                    None => Err("No charged energy cell".to_string()),
+                };
+                Some(PlanetToExplorer::GenerateResourceResponse {
+                    resource: generated.ok(),
+                })
+            }
+            ExplorerToPlanet::CombineResourceRequest {
+                explorer_id: _,
+                msg,
+            } => {
+                let complex_response = if self.recipes.contains(&recipes::request_target(&msg)) {
+                    // Without a charged cell, cell 0 makes the combinator fail
+                    // with its usual "not charged" error
+                    let cell = charged_cell(state).unwrap_or(0);
+                    combine(state, cell, combinator, msg)
+                } else {
+                    let (left, right) = recipes::into_inputs(msg);
+                    Err((PlanetError::UnsupportedResource.to_string(), left, right))
+                };
+                Some(PlanetToExplorer::CombineResourceResponse { complex_response })
+            }
+            ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: _ } => {
+                let available_cells = (0..state.cells_count())
+                    .filter(|i| state.cell(*i).is_charged())
+                    .count() as u32;
+                Some(PlanetToExplorer::AvailableEnergyCellResponse { available_cells })
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+/// What the [`PlanetAI`](crate::PlanetAI) does with a sunray that arrives when
+/// every energy cell is charged and no rocket can be built.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the sunray and count it as wasted.
+    #[default]
+    Waste,
+    /// Spend a charged cell on Carbon kept in an internal stock, then recharge
+    /// the cell with the sunray.
+    StockCarbon,
+}
+
+/// Sunray statistics, as exposed in the [`PlanetReport`](crate::PlanetReport).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SunrayStats {
+    /// Sunrays received since the planet was created.
+    pub received: u32,
+    /// Overflowing sunrays whose energy went to the Carbon stock.
+    pub stocked: u32,
+    /// Overflowing sunrays dropped.
+    pub wasted: u32,
+}
+
+impl SunrayStats {
+    /// Fraction of the received sunrays that were wasted, 0 before the first one.
+    pub fn waste_rate(&self) -> f64 {
+        if self.received == 0 {
+            return 0.0;
+        }
+        self.wasted as f64 / self.received as f64
+    }
+}
@@ -13,277 +13,988 @@ use common_game::protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer
 use common_game::utils::ID;
 use std::collections::HashSet;
 
+use crate::alliance::{AllianceConfig, Tier};
+use crate::bandit::{AsteroidPolicy, Bandit, SunrayPolicy};
+use crate::clock::{Clock, SystemClock};
+use crate::config::PlanetConfig;
+use crate::covert::CovertChannel;
+use crate::credits::CreditLedger;
+use crate::energy::{EnergyConsumer, EnergyLedger};
+use crate::error::PlanetError;
+use crate::explain::{Callback, Explanation, ExplanationHandle, FiredRule, Observation, Outcome};
+use crate::extension::{
+    ExtensionClient, ExtensionConfig, ExtensionRequest, ExtensionResponse, ExtensionServer,
+    PlanetStatus, Reservations,
+};
+use crate::forecast::{EnergyForecast, SunrayRate};
+use crate::lifecycle::{Lifecycle, LifecycleEvent};
+use crate::overflow::{OverflowPolicy, SunrayStats};
+use crate::recipes;
+use crate::report::{PlanetReport, ReportHandle};
+use crate::reputation::ReputationBook;
+use crate::rules::{AsteroidRate, Facts, RuleAction, RuleSet};
+use crate::stock::CarbonStock;
+use crate::threat::{ThreatBus, ThreatLink};
+use crate::warning::WarningBook;
+
 #[allow(dead_code)]
 pub struct PlanetAI {
-    has_explorer: bool,
-    started: bool,
-    pending_warning: bool, // To warn the explorer
+    lifecycle: Lifecycle,
+    warnings: WarningBook, // To warn the explorers
+    reputation: ReputationBook,
+    credits: CreditLedger,
+    energy: EnergyLedger,
+    overflow: OverflowPolicy,
+    sunrays: SunrayStats,
+    carbon_stock: CarbonStock,
+    bandit: Bandit,
+    rules: RuleSet,
+    covert: Option<CovertChannel>,
+    alliance: AllianceConfig,
+    asteroid_rate: AsteroidRate,
+    threat: Option<ThreatLink>,
+    sibling_sightings: u32,
+    extension: Option<ExtensionServer>,
+    extension_config: ExtensionConfig,
+    reservations: Reservations,
+    sunray_rate: SunrayRate,
+    clock: Box<dyn Clock>,
+    explanations: ExplanationHandle,
+    report: ReportHandle,
 }
 
 #[allow(dead_code)]
 impl PlanetAI {
     pub fn new() -> PlanetAI {
+        Self::with_config(PlanetConfig::default())
+    }
+
+    /// Creates the AI with custom tuning, see [`PlanetConfig`].
+    pub fn with_config(config: PlanetConfig) -> PlanetAI {
         PlanetAI {
-            has_explorer: false,
-            started: false,
-            pending_warning: false,
+            lifecycle: Lifecycle::new(),
+            warnings: WarningBook::new(config.warning),
+            reputation: ReputationBook::new(config.reputation),
+            credits: CreditLedger::new(config.credits),
+            energy: EnergyLedger::new(config.energy),
+            overflow: config.overflow,
+            sunrays: SunrayStats::default(),
+            carbon_stock: CarbonStock::new(config.stock),
+            bandit: Bandit::new(config.bandit),
+            rules: config.rules,
+            covert: CovertChannel::new(config.covert),
+            alliance: config.alliance,
+            asteroid_rate: AsteroidRate::default(),
+            threat: config.threat_bus.map(|bus| bus.join()),
+            sibling_sightings: 0,
+            extension: None,
+            reservations: Reservations::new(&config.extension),
+            extension_config: config.extension,
+            sunray_rate: SunrayRate::new(config.forecast),
+            clock: Box::new(SystemClock),
+            explanations: ExplanationHandle::new(config.explanations),
+            report: ReportHandle::default(),
         }
     }
-}
 
-impl Default for PlanetAI {
-    fn default() -> Self {
-        Self::new()
+    /// Returns a handle to the report published on every `InternalStateRequest`.
+    ///
+    /// Take it before passing the AI to [`create_planet`](crate::create_planet),
+    /// since the AI is moved into the planet thread.
+    pub fn report_handle(&self) -> ReportHandle {
+        self.report.clone()
     }
-}
 
-impl planet::PlanetAI for PlanetAI {
-    fn handle_sunray(
-        &mut self,
-        state: &mut PlanetState,
-        _generator: &Generator,
-        _combinator: &Combinator,
-        sunray: Sunray,
-    ) {
-        if !state.cell(0).is_charged() {
-            state.charge_cell(sunray);
-        } else if !state.has_rocket() {
-            let _ = state.build_rocket(0);
-            state.charge_cell(sunray);
+    /// Replaces the clock the planet keeps time with: sunray arrivals (see
+    /// [`EnergyForecast`]), warnings, reservations, request windows, asteroid
+    /// rate and the timestamps of the report all read it.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> PlanetAI {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Returns a handle to the explanations of the decisions, see [`Explanation`].
+    ///
+    /// Like the [`report_handle`](PlanetAI::report_handle), take it before
+    /// passing the AI to [`create_planet`](crate::create_planet).
+    pub fn explanation_handle(&self) -> ExplanationHandle {
+        self.explanations.clone()
+    }
+
+    /// Joins the bus, see [`create_planet_with_threat_bus`](crate::create_planet_with_threat_bus).
+    pub(crate) fn join_threat_bus(&mut self, bus: &ThreatBus) {
+        self.threat = Some(bus.join());
+    }
+
+    /// Opens the extension channel pair, see [`create_planet_with_extension`](crate::create_planet_with_extension).
+    pub(crate) fn attach_extension(&mut self) -> ExtensionClient {
+        let (server, client) = ExtensionServer::pair();
+        self.extension = Some(server);
+        client
+    }
+
+    fn forecast(&self, state: &PlanetState) -> EnergyForecast {
+        self.sunray_rate.forecast(
+            charged_cells(state) as u32,
+            self.defense_hold(state),
+            self.clock.now(),
+        )
+    }
+
+    fn build_report(&self, state: &PlanetState) -> PlanetReport {
+        PlanetReport {
+            started: self.lifecycle.is_running(),
+            explorers: self.lifecycle.explorers(),
+            lifecycle: self.lifecycle.phase(),
+            transitions: self.lifecycle.history(),
+            pending_warning: self.warnings.pending(self.clock.now()),
+            warnings: self.warnings.snapshot(),
+            reputations: self.reputation.snapshot(),
+            credits: self.credits.snapshot(),
+            energy: self.energy.snapshot(),
+            sunrays: self.sunrays,
+            carbon_stock: self.carbon_stock.snapshot(),
+            strategy: self.bandit.snapshot(),
+            sibling_sightings: self.sibling_sightings,
+            explanations: self.explanations.snapshot(),
+            energy_forecast: self.forecast(state),
         }
     }
 
-    fn handle_asteroid(
+    /// Moves the energy of a charged cell into the Carbon stock, making room for a sunray.
+    ///
+    /// The stock never competes with the defense: nothing is banked while the
+    /// planet could still store that energy in a rocket.
+    fn stock_carbon(&mut self, state: &mut PlanetState, generator: &Generator) {
+        if self.carbon_stock.is_full() || (state.can_have_rocket() && !state.has_rocket()) {
+            return;
+        }
+        let Some((energy_cell, cell)) = state.full_cell() else {
+            return;
+        };
+        if let Ok(carbon) = generator.make_carbon(energy_cell) {
+            self.carbon_stock.bank(carbon);
+            self.sunrays.stocked += 1;
+            self.energy
+                .consumed(cell, EnergyConsumer::CarbonStock, self.clock.now());
+        }
+    }
+
+    fn facts(&mut self, state: &PlanetState, sunray: bool) -> Facts {
+        Facts {
+            sunray,
+            asteroid: !sunray,
+            rocket: state.has_rocket(),
+            charged_cells: charged_cells(state),
+            cells: state.cells_count(),
+            explorers: self.lifecycle.explorer_count(),
+            pending_warning: self.warnings.pending(self.clock.now()),
+            asteroid_rate: self.asteroid_rate.per_minute(self.clock.now()),
+            rocket_first: self.bandit.sunray_policy() == SunrayPolicy::RocketFirst,
+            build_on_demand: self.bandit.asteroid_policy() == AsteroidPolicy::BuildOnDemand,
+            stock_overflow: self.overflow == OverflowPolicy::StockCarbon,
+            stock_full: self.carbon_stock.is_full(),
+            threat: self
+                .threat
+                .as_ref()
+                .is_some_and(|threat| threat.threatened(self.clock.now())),
+        }
+    }
+
+    /// Fires every rule matching a sunray or an asteroid, see [`RuleSet`].
+    ///
+    /// Returns the rules that fired.
+    fn apply_rules(
         &mut self,
         state: &mut PlanetState,
-        _generator: &Generator,
-        _combinator: &Combinator,
-    ) -> Option<Rocket> {
-        if state.has_rocket() {
-            // reset warning flags after using the rocket
-            self.pending_warning = false;
-            state.take_rocket()
-        } else {
-            // Try to build a rocket
-            if state.build_rocket(0).is_ok() {
-                self.pending_warning = false;
-                return state.take_rocket();
+        generator: &Generator,
+        sunray: bool,
+    ) -> Vec<FiredRule> {
+        let mut fired = Vec::new();
+        let mut from = 0;
+        loop {
+            let facts = self.facts(state, sunray);
+            let Some(index) = self.rules.next_match(from, &facts) else {
+                return fired;
+            };
+            let rule = &self.rules.rules[index];
+            log::trace!("rule {} fired: {}", index + 1, rule);
+            fired.push(FiredRule {
+                number: index + 1,
+                rule: rule.to_string(),
+            });
+            match rule.action {
+                RuleAction::BuildRocket => self.build_rocket(state),
+                RuleAction::StockCarbon => self.stock_carbon(state, generator),
             }
+            from = index + 1;
+        }
+    }
 
-            // Couldn't build the rocket -> warn the explorer
-            self.pending_warning = true;
-            None
+    fn observe(&self, state: &PlanetState) -> Observation {
+        Observation {
+            charged_cells: charged_cells(state),
+            cells: state.cells_count(),
+            rocket: state.has_rocket(),
+            pending_warning: self.warnings.pending(self.clock.now()),
+            explorers: self.lifecycle.explorer_count(),
         }
     }
 
-    fn handle_internal_state_req(
+    fn explain(
+        &self,
+        callback: Callback,
+        explorer: Option<ID>,
+        observed: Observation,
+        fired: Vec<FiredRule>,
+        outcome: Outcome,
+    ) {
+        self.explanations.record(Explanation {
+            at: self.clock.now(),
+            callback,
+            explorer,
+            observed,
+            fired,
+            outcome,
+        });
+    }
+
+    fn explain_lifecycle(
         &mut self,
-        state: &mut PlanetState,
-        _generator: &Generator,
-        _combinator: &Combinator,
-    ) -> DummyPlanetState {
-        state.to_dummy()
+        state: &PlanetState,
+        callback: Callback,
+        event: LifecycleEvent,
+    ) -> bool {
+        let observed = self.observe(state);
+        let explorer = match event {
+            LifecycleEvent::Arrival(explorer_id) | LifecycleEvent::Departure(explorer_id) => {
+                Some(explorer_id)
+            }
+            _ => None,
+        };
+        let result = self.lifecycle.apply(event, self.clock.now());
+        let outcome = match result {
+            Ok(()) => Outcome::Applied,
+            Err(rejection) => Outcome::Rejected(rejection),
+        };
+        self.explain(callback, explorer, observed, Vec::new(), outcome);
+        result.is_ok()
     }
 
-    fn handle_explorer_msg(
+    /// Answers an explorer request, along with the outcome to explain.
+    fn answer(
         &mut self,
         state: &mut PlanetState,
         generator: &Generator,
         combinator: &Combinator,
         msg: ExplorerToPlanet,
-    ) -> Option<PlanetToExplorer> {
+    ) -> (PlanetToExplorer, Outcome) {
+        let explorer_id = explorer_id(&msg);
+        self.reputation.received(explorer_id);
+        let tier = self.alliance.tier(explorer_id);
+        let may_spend = self.spending_refusal(state, explorer_id, tier).is_none();
+        if consumes_energy(&msg) {
+            let combination = matches!(msg, ExplorerToPlanet::CombineResourceRequest { .. });
+            if let Some(error) = self.refusal(state, explorer_id, combination) {
+                log::debug!("request of explorer {} refused: {}", explorer_id, error);
+                return (refuse(msg, error), Outcome::Refused(error));
+            }
+        }
+
         match msg {
             ExplorerToPlanet::SupportedResourceRequest { explorer_id: _ } => {
                 let mut hs = HashSet::new();
-                hs.insert(BasicResourceType::Carbon);
-                Some(PlanetToExplorer::SupportedResourceResponse { resource_list: hs })
+                // Rivals only see the Carbon they could get right now
+                if tier != Tier::Rival || may_spend {
+                    hs.insert(BasicResourceType::Carbon);
+                }
+                (
+                    PlanetToExplorer::SupportedResourceResponse { resource_list: hs },
+                    Outcome::Answered,
+                )
             }
-            ExplorerToPlanet::SupportedCombinationRequest { explorer_id: _ } => {
-                let mut hs = HashSet::new();
-                hs.insert(ComplexResourceType::AIPartner);
-                hs.insert(ComplexResourceType::Diamond);
-                hs.insert(ComplexResourceType::Dolphin);
-                hs.insert(ComplexResourceType::Life);
-                hs.insert(ComplexResourceType::Robot);
-                hs.insert(ComplexResourceType::Water);
-
-                // Secret channel:
-                // If an asteroid is incoming, remove one element to signal danger.
-                // We remove AIPartner to encode bit = 1 ("asteroid arriving").
-                if self.pending_warning {
-                    hs.remove(&ComplexResourceType::AIPartner);
-                    // Reset flag
-                    self.pending_warning = false;
+            ExplorerToPlanet::SupportedCombinationRequest { explorer_id } => {
+                // The recipes depend on the planet type, see `create_planet`
+                let mut hs = combinator.all_available_recipes();
+
+                match &mut self.covert {
+                    _ if tier == Tier::Rival => hs.clear(),
+                    // Keyed channel: only allies can read the warning, see `CovertConfig`
+                    Some(covert) if tier == Tier::Ally => {
+                        let warning = self.warnings.deliver(explorer_id, self.clock.now());
+                        hs = covert.encode(explorer_id, &hs, warning);
+                    }
+                    Some(_) => {}
+                    // Secret channel:
+                    // If an asteroid is incoming, remove one element to signal danger.
+                    // We remove AIPartner to encode bit = 1 ("asteroid arriving").
+                    None => {
+                        if self.warnings.deliver(explorer_id, self.clock.now()) {
+                            hs.remove(&ComplexResourceType::AIPartner);
+                        }
+                    }
                 }
-                Some(PlanetToExplorer::SupportedCombinationResponse {
-                    combination_list: hs,
-                })
+                (
+                    PlanetToExplorer::SupportedCombinationResponse {
+                        combination_list: hs,
+                    },
+                    Outcome::Answered,
+                )
             }
             ExplorerToPlanet::GenerateResourceRequest {
-                explorer_id: _,
+                explorer_id,
                 resource,
             } => {
-                if resource != BasicResourceType::Carbon {
-                    Some(PlanetToExplorer::GenerateResourceResponse { resource: None })
-                } else {
-                    let generated = generator.make_carbon(state.cell_mut(0));
-                    match generated {
-                        Ok(carbon) => Some(PlanetToExplorer::GenerateResourceResponse {
-                            resource: Some(BasicResource::Carbon(carbon)),
-                        }),
-                        Err(_) => {
-                            Some(PlanetToExplorer::GenerateResourceResponse { resource: None })
-                        }
+                let generated = self.generate(state, generator, explorer_id, resource);
+                let outcome = match &generated {
+                    Ok(_) => Outcome::Served,
+                    Err(error) => {
+                        log::debug!(
+                            "{:?} not generated for explorer {}: {}",
+                            resource,
+                            explorer_id,
+                            error
+                        );
+                        Outcome::Refused(*error)
                     }
-                }
+                };
+                (
+                    PlanetToExplorer::GenerateResourceResponse {
+                        resource: generated.ok(),
+                    },
+                    outcome,
+                )
             }
             ExplorerToPlanet::AvailableEnergyCellRequest {
                 explorer_id: _explorer_id,
-            } => match state.full_cell() {
-                Some(_) => Some(PlanetToExplorer::AvailableEnergyCellResponse {
-                    available_cells: 1u32,
-                }),
-                None => Some(PlanetToExplorer::AvailableEnergyCellResponse {
-                    available_cells: 0u32,
-                }),
-            },
-            ExplorerToPlanet::CombineResourceRequest {
-                explorer_id: _,
-                msg,
-            } => match msg {
-                ComplexResourceRequest::Water(hydrogen, oxygen) => {
-                    match combinator.make_water(hydrogen, oxygen, state.cell_mut(0)) {
-                        Ok(water) => Some(PlanetToExplorer::CombineResourceResponse {
-                            complex_response: Ok(ComplexResource::Water(water)),
-                        }),
-                        Err((str, hydrogen, oxygen)) => {
-                            Some(PlanetToExplorer::CombineResourceResponse {
-                                complex_response: Err((
-                                    str,
-                                    GenericResource::BasicResources(BasicResource::Hydrogen(
-                                        hydrogen,
-                                    )),
-                                    GenericResource::BasicResources(BasicResource::Oxygen(oxygen)),
-                                )),
-                            })
-                        }
-                    }
-                }
-                ComplexResourceRequest::Diamond(carbon, carbon1) => {
-                    match combinator.make_diamond(carbon, carbon1, state.cell_mut(0)) {
-                        Ok(diamond) => Some(PlanetToExplorer::CombineResourceResponse {
-                            complex_response: Ok(ComplexResource::Diamond(diamond)),
-                        }),
-                        Err((str, carbon, carbon1)) => {
-                            Some(PlanetToExplorer::CombineResourceResponse {
-                                complex_response: Err((
-                                    str,
-                                    GenericResource::BasicResources(BasicResource::Carbon(carbon)),
-                                    GenericResource::BasicResources(BasicResource::Carbon(carbon1)),
-                                )),
-                            })
+            } => (
+                PlanetToExplorer::AvailableEnergyCellResponse {
+                    available_cells: self.spendable_cells(state) as u32,
+                },
+                Outcome::Answered,
+            ),
+            ExplorerToPlanet::CombineResourceRequest { explorer_id, msg } => {
+                let complex_response = self.combine(state, combinator, explorer_id, msg);
+                let outcome = match &complex_response {
+                    Ok(_) => Outcome::Served,
+                    Err((error, _, _)) => Outcome::Refused(
+                        PlanetError::parse(error).unwrap_or(PlanetError::WrongInputs),
+                    ),
+                };
+                (
+                    PlanetToExplorer::CombineResourceResponse { complex_response },
+                    outcome,
+                )
+            }
+        }
+    }
+
+    fn build_rocket(&mut self, state: &mut PlanetState) {
+        if !state.has_rocket()
+            && let Some(cell) = rocket_cell(state)
+            && state.build_rocket(cell).is_ok()
+        {
+            self.energy
+                .consumed(cell, EnergyConsumer::Rocket, self.clock.now());
+        }
+    }
+
+    /// Reads the sightings of the sibling planets, see [`ThreatBus`](crate::ThreatBus).
+    ///
+    /// Without a rocket, the explorers are warned before the asteroid gets here.
+    fn listen(&mut self, state: &PlanetState) {
+        let now = self.clock.now();
+        let Some(threat) = &mut self.threat else {
+            return;
+        };
+        let sightings = threat.sightings(now);
+        if sightings == 0 {
+            return;
+        }
+        self.sibling_sightings += sightings as u32;
+        if !state.has_rocket() {
+            log::debug!("sibling sighted an asteroid, warning the explorers");
+            self.warnings.issue(self.warning_targets(), now);
+        }
+    }
+
+    /// Explorers on the planet that can read the asteroid warning.
+    fn warning_targets(&self) -> Vec<ID> {
+        self.lifecycle
+            .explorers()
+            .into_iter()
+            .filter(|explorer_id| self.may_be_warned(*explorer_id))
+            .collect()
+    }
+
+    /// Why a request of the explorer spending energy is refused, if it is.
+    ///
+    /// Requests that get past the lifecycle count toward the explorer's rate.
+    fn refusal(
+        &mut self,
+        state: &PlanetState,
+        explorer_id: ID,
+        combination: bool,
+    ) -> Option<PlanetError> {
+        let tier = self.alliance.tier(explorer_id);
+        if let Err(error) = self.lifecycle.request(explorer_id, self.clock.now()) {
+            Some(error)
+        } else if !self.reputation.admit(explorer_id, self.clock.now()) {
+            self.reputation.throttled(explorer_id);
+            Some(PlanetError::QuotaExceeded)
+        } else if tier == Tier::Rival && combination {
+            // Rivals are not offered combinations at all
+            Some(PlanetError::UnsupportedResource)
+        } else {
+            self.spending_refusal(state, explorer_id, tier)
+        }
+    }
+
+    /// Why the explorer may not spend a charged cell, if it may not: the
+    /// alliance reserve, or the cells reserved by the others.
+    fn spending_refusal(
+        &mut self,
+        state: &PlanetState,
+        explorer_id: ID,
+        tier: Tier,
+    ) -> Option<PlanetError> {
+        let charged = charged_cells(state);
+        let reserved = self
+            .reservations
+            .held_by_others(explorer_id, self.clock.now()) as usize;
+        if !self.alliance.may_spend(tier, charged) {
+            Some(PlanetError::ReservedForAllies)
+        } else if charged > 0 && charged <= reserved {
+            Some(PlanetError::ReservedByOthers)
+        } else {
+            None
+        }
+    }
+
+    /// Serves the requests waiting on the extension channel.
+    fn serve_extension(&mut self, state: &mut PlanetState, generator: &Generator) {
+        let Some(server) = self.extension.take() else {
+            return;
+        };
+        while let Some(request) = server.next() {
+            let response = self.extension_response(state, generator, request);
+            server.respond(response);
+        }
+        self.extension = Some(server);
+    }
+
+    fn extension_response(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        request: ExtensionRequest,
+    ) -> ExtensionResponse {
+        let now = self.clock.now();
+        match request {
+            ExtensionRequest::BatchCarbon {
+                explorer_id,
+                amount,
+            } => {
+                self.reputation.received(explorer_id);
+                let mut carbon = Vec::new();
+                let mut error = None;
+                for unit in 0..amount.min(self.extension_config.max_batch) {
+                    // Every unit counts against the quota, as a request of its own
+                    if let Some(refusal) = self.refusal(state, explorer_id, false) {
+                        if unit == 0 {
+                            return ExtensionResponse::Refused(refusal);
                         }
+                        error = Some(refusal);
+                        break;
                     }
-                }
-                ComplexResourceRequest::Life(water, carbon) => {
-                    match combinator.make_life(water, carbon, state.cell_mut(0)) {
-                        Ok(life) => Some(PlanetToExplorer::CombineResourceResponse {
-                            complex_response: Ok(ComplexResource::Life(life)),
-                        }),
-                        Err((str, water, carbon)) => {
-                            Some(PlanetToExplorer::CombineResourceResponse {
-                                complex_response: Err((
-                                    str,
-                                    GenericResource::ComplexResources(ComplexResource::Water(
-                                        water,
-                                    )),
-                                    GenericResource::BasicResources(BasicResource::Carbon(carbon)),
-                                )),
-                            })
+                    match self.generate(state, generator, explorer_id, BasicResourceType::Carbon) {
+                        Ok(BasicResource::Carbon(unit)) => carbon.push(unit),
+                        Ok(_) => unreachable!("the planet only generates Carbon"),
+                        Err(failure) => {
+                            error = Some(failure);
+                            break;
                         }
                     }
                 }
-                ComplexResourceRequest::Robot(silicon, life) => {
-                    match combinator.make_robot(silicon, life, state.cell_mut(0)) {
-                        Ok(robot) => Some(PlanetToExplorer::CombineResourceResponse {
-                            complex_response: Ok(ComplexResource::Robot(robot)),
-                        }),
-                        Err((str, silicon, life)) => {
-                            Some(PlanetToExplorer::CombineResourceResponse {
-                                complex_response: Err((
-                                    str,
-                                    GenericResource::BasicResources(BasicResource::Silicon(
-                                        silicon,
-                                    )),
-                                    GenericResource::ComplexResources(ComplexResource::Life(life)),
-                                )),
-                            })
-                        }
-                    }
+                ExtensionResponse::Carbon { carbon, error }
+            }
+            ExtensionRequest::EnergyEta => ExtensionResponse::EnergyEta(self.forecast(state)),
+            ExtensionRequest::Reserve { explorer_id, cells } => {
+                if let Err(error) = self.lifecycle.request(explorer_id, self.clock.now()) {
+                    return ExtensionResponse::Refused(error);
                 }
-                ComplexResourceRequest::Dolphin(water, life) => {
-                    match combinator.make_dolphin(water, life, state.cell_mut(0)) {
-                        Ok(dolphin) => Some(PlanetToExplorer::CombineResourceResponse {
-                            complex_response: Ok(ComplexResource::Dolphin(dolphin)),
-                        }),
-                        Err((str, water, life)) => {
-                            Some(PlanetToExplorer::CombineResourceResponse {
-                                complex_response: Err((
-                                    str,
-                                    GenericResource::ComplexResources(ComplexResource::Water(
-                                        water,
-                                    )),
-                                    GenericResource::ComplexResources(ComplexResource::Life(life)),
-                                )),
-                            })
-                        }
-                    }
+                if self.alliance.tier(explorer_id) == Tier::Rival {
+                    return ExtensionResponse::Refused(PlanetError::ReservedForAllies);
                 }
-                ComplexResourceRequest::AIPartner(robot, diamond) => match combinator
-                    .make_aipartner(robot, diamond, state.cell_mut(0))
-                {
-                    Ok(aipartner) => Some(PlanetToExplorer::CombineResourceResponse {
-                        complex_response: Ok(ComplexResource::AIPartner(aipartner)),
-                    }),
-                    Err((str, robot, diamond)) => Some(PlanetToExplorer::CombineResourceResponse {
-                        complex_response: Err((
-                            str,
-                            GenericResource::ComplexResources(ComplexResource::Robot(robot)),
-                            GenericResource::ComplexResources(ComplexResource::Diamond(diamond)),
-                        )),
-                    }),
-                },
+                let charged = charged_cells(state) as u32;
+                let cells = self.reservations.reserve(explorer_id, cells, charged, now);
+                ExtensionResponse::Reserved { cells }
+            }
+            ExtensionRequest::Release { explorer_id } => {
+                self.reservations.release(explorer_id);
+                ExtensionResponse::Released
+            }
+            ExtensionRequest::Status => ExtensionResponse::Status(PlanetStatus {
+                lifecycle: self.lifecycle.phase(),
+                cells: state.cells_count() as u32,
+                charged_cells: charged_cells(state) as u32,
+                reserved_cells: self.reservations.total(now),
+                rocket: state.has_rocket(),
+                explorers: self.lifecycle.explorer_count() as u32,
+                carbon_stock: self.carbon_stock.snapshot().level,
+                pending_warning: self.warnings.pending(now),
+            }),
+        }
+    }
+
+    /// Whether the explorer can read the asteroid warning: rivals never can,
+    /// and only allies can once the covert channel is keyed.
+    fn may_be_warned(&self, explorer_id: ID) -> bool {
+        match self.alliance.tier(explorer_id) {
+            Tier::Rival => false,
+            Tier::Ally => true,
+            Tier::Neutral => self.covert.is_none(),
+        }
+    }
+
+    fn carbon_sold(&mut self, explorer_id: ID) {
+        self.reputation.resource_taken(explorer_id);
+        self.credits.carbon_sold(explorer_id);
+        self.bandit.served();
+    }
+
+    /// Generates a basic resource for an explorer, charging its credits.
+    fn generate(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        explorer_id: ID,
+        resource: BasicResourceType,
+    ) -> Result<BasicResource, PlanetError> {
+        if resource != BasicResourceType::Carbon {
+            return Err(PlanetError::UnsupportedResource);
+        }
+        if !self.credits.can_buy_carbon(explorer_id) {
+            return Err(PlanetError::QuotaExceeded);
+        }
+        // Banked units first: their energy was already attributed to the stock
+        if let Some(carbon) = self.carbon_stock.take() {
+            self.carbon_sold(explorer_id);
+            return Ok(BasicResource::Carbon(carbon));
+        }
+        let cell = self.spendable_cell(state)?;
+        let carbon = generator
+            .make_carbon(state.cell_mut(cell))
+            .map_err(|_| PlanetError::NoEnergy)?;
+
+        self.energy.consumed(
+            cell,
+            EnergyConsumer::Generation {
+                explorer_id,
+                resource,
             },
+            self.clock.now(),
+        );
+        self.reservations.spent(explorer_id);
+        self.carbon_sold(explorer_id);
+        Ok(BasicResource::Carbon(carbon))
+    }
+
+    /// Combines a complex resource for an explorer, crediting it on success.
+    fn combine(
+        &mut self,
+        state: &mut PlanetState,
+        combinator: &Combinator,
+        explorer_id: ID,
+        msg: ComplexResourceRequest,
+    ) -> Result<ComplexResource, CombineError> {
+        let recipe = recipes::request_target(&msg);
+        let supported = combinator.all_available_recipes().contains(&recipe);
+        // The combined resource, along with the cell that powered it
+        let result = if !supported {
+            Err(refuse_combination(msg, PlanetError::UnsupportedResource))
+        } else {
+            match self.spendable_cell(state) {
+                Ok(cell) => combine(state, cell, combinator, msg)
+                    .map(|resource| (resource, cell))
+                    .map_err(|(message, left, right)| {
+                        (combinator_error(&message).to_string(), left, right)
+                    }),
+                Err(error) => Err(refuse_combination(msg, error)),
+            }
+        };
+
+        match result {
+            Ok((resource, cell)) => {
+                self.reputation.combination(explorer_id, true);
+                self.energy.consumed(
+                    cell,
+                    EnergyConsumer::Combination {
+                        explorer_id,
+                        recipe,
+                    },
+                    self.clock.now(),
+                );
+                self.credits.combination(explorer_id);
+                self.reservations.spent(explorer_id);
+                self.bandit.served();
+                Ok(resource)
+            }
+            Err((error, left, right)) => {
+                log::debug!(
+                    "{:?} not combined for explorer {}: {}",
+                    recipe,
+                    explorer_id,
+                    error
+                );
+                // Only the explorer's own mistakes count against it, not the refusals of the planet
+                if !supported || PlanetError::parse(&error) == Some(PlanetError::WrongInputs) {
+                    self.reputation.combination(explorer_id, false);
+                }
+                Err((error, left, right))
+            }
+        }
+    }
+
+    /// Whether the last charged cell is kept for the rocket: the planet has
+    /// none while an asteroid is announced, by its own warning or by a sibling.
+    fn defense_hold(&self, state: &PlanetState) -> bool {
+        let now = self.clock.now();
+        state.can_have_rocket()
+            && !state.has_rocket()
+            && (self.warnings.active(now)
+                || self
+                    .threat
+                    .as_ref()
+                    .is_some_and(|threat| threat.threatened(now)))
+    }
+
+    /// Charged cells explorers may spend, see [`defense_hold`](Self::defense_hold).
+    fn spendable_cells(&self, state: &PlanetState) -> usize {
+        let charged = charged_cells(state);
+        if self.defense_hold(state) {
+            charged.saturating_sub(1)
+        } else {
+            charged
+        }
+    }
+
+    /// The charged cell an explorer request may spend, the first one.
+    fn spendable_cell(&self, state: &PlanetState) -> Result<usize, PlanetError> {
+        if charged_cells(state) == 0 {
+            Err(PlanetError::NoEnergy)
+        } else if self.spendable_cells(state) == 0 {
+            Err(PlanetError::ReservedForDefense)
+        } else {
+            (0..state.cells_count())
+                .find(|i| state.cell(*i).is_charged())
+                .ok_or(PlanetError::NoEnergy)
+        }
+    }
+}
+
+impl Default for PlanetAI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl planet::PlanetAI for PlanetAI {
+    fn handle_sunray(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        _combinator: &Combinator,
+        sunray: Sunray,
+    ) {
+        self.sunrays.received += 1;
+        self.sunray_rate.record(self.clock.now());
+        self.serve_extension(state, generator);
+        self.listen(state);
+        let observed = self.observe(state);
+
+        // The rules make room for the sunray, see `DEFAULT_RULES`
+        let fired = self.apply_rules(state, generator, true);
+        let outcome = match state.empty_cell().map(|(_, cell)| cell) {
+            Some(cell) => {
+                state.charge_cell(sunray);
+                self.energy.charged(cell, self.clock.now());
+                Outcome::Charged
+            }
+            None => {
+                self.sunrays.wasted += 1;
+                self.energy.wasted(self.clock.now());
+                Outcome::Wasted
+            }
+        };
+        self.explain(Callback::Sunray, None, observed, fired, outcome);
+
+        if let Some(threat) = &mut self.threat {
+            threat.rocket(state.has_rocket());
+        }
+    }
+
+    fn handle_asteroid(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        _combinator: &Combinator,
+    ) -> Option<Rocket> {
+        self.serve_extension(state, generator);
+        self.listen(state);
+        self.asteroid_rate.record(self.clock.now());
+        let observed = self.observe(state);
+        let fired = self.apply_rules(state, generator, false);
+        let rocket = state.take_rocket();
+        let outcome = if rocket.is_some() {
+            Outcome::Deflected
+        } else {
+            Outcome::Hit
+        };
+        self.explain(Callback::Asteroid, None, observed, fired, outcome);
+
+        // No rocket -> warn the explorers, otherwise the older warnings are moot
+        let now = self.clock.now();
+        if rocket.is_some() {
+            self.warnings.cancel(now);
+        } else {
+            self.warnings.issue(self.warning_targets(), now);
+        }
+        if let Some(threat) = &mut self.threat {
+            threat.asteroid(now, rocket.is_some());
+            threat.rocket(state.has_rocket());
         }
+        self.bandit.asteroid(rocket.is_some());
+        rocket
+    }
+
+    fn handle_internal_state_req(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        _combinator: &Combinator,
+    ) -> DummyPlanetState {
+        self.serve_extension(state, generator);
+        let observed = self.observe(state);
+        self.explain(
+            Callback::InternalStateRequest,
+            None,
+            observed,
+            Vec::new(),
+            Outcome::Answered,
+        );
+        self.report.publish(self.build_report(state));
+        state.to_dummy()
+    }
+
+    fn handle_explorer_msg(
+        &mut self,
+        state: &mut PlanetState,
+        generator: &Generator,
+        combinator: &Combinator,
+        msg: ExplorerToPlanet,
+    ) -> Option<PlanetToExplorer> {
+        self.serve_extension(state, generator);
+        self.listen(state);
+        let explorer_id = explorer_id(&msg);
+        let observed = self.observe(state);
+        let (response, outcome) = self.answer(state, generator, combinator, msg);
+        self.explain(
+            Callback::ExplorerRequest,
+            Some(explorer_id),
+            observed,
+            Vec::new(),
+            outcome,
+        );
+        Some(response)
     }
 
     fn on_explorer_arrival(
         &mut self,
-        _state: &mut PlanetState,
+        state: &mut PlanetState,
         _generator: &Generator,
         _combinator: &Combinator,
-        _explorer_id: ID,
+        explorer_id: ID,
     ) {
-        self.has_explorer = true;
+        if !self.explain_lifecycle(
+            state,
+            Callback::ExplorerArrival,
+            LifecycleEvent::Arrival(explorer_id),
+        ) {
+            return;
+        }
+        let high_energy = (0..state.cells_count()).all(|i| state.cell(i).is_charged());
+        self.credits
+            .arrival(explorer_id, high_energy.then(|| self.energy.charges()));
     }
 
     fn on_explorer_departure(
         &mut self,
-        _state: &mut PlanetState,
+        state: &mut PlanetState,
         _generator: &Generator,
         _combinator: &Combinator,
-        _explorer_id: ID,
+        explorer_id: ID,
     ) {
-        self.has_explorer = false;
+        if self.explain_lifecycle(
+            state,
+            Callback::ExplorerDeparture,
+            LifecycleEvent::Departure(explorer_id),
+        ) {
+            self.warnings.departed(explorer_id);
+            self.reservations.release(explorer_id);
+        }
+    }
+
+    fn on_start(&mut self, state: &PlanetState, _generator: &Generator, _combinator: &Combinator) {
+        self.explain_lifecycle(state, Callback::Start, LifecycleEvent::Start);
     }
 
-    fn on_start(&mut self, _state: &PlanetState, _generator: &Generator, _combinator: &Combinator) {
-        self.started = true;
-        self.has_explorer = false;
+    fn on_stop(&mut self, state: &PlanetState, _generator: &Generator, _combinator: &Combinator) {
+        self.explain_lifecycle(state, Callback::Stop, LifecycleEvent::Stop);
     }
+}
 
-    fn on_stop(&mut self, _state: &PlanetState, _generator: &Generator, _combinator: &Combinator) {
-        self.started = false;
-        self.has_explorer = false;
+fn charged_cells(state: &PlanetState) -> usize {
+    (0..state.cells_count())
+        .filter(|i| state.cell(*i).is_charged())
+        .count()
+}
+
+/// Cell to turn into a rocket: the last charged one, so that explorers keep
+/// being served from the first ones.
+///
+/// `None` when the planet type can't have rockets or no cell is charged.
+fn rocket_cell(state: &PlanetState) -> Option<usize> {
+    if !state.can_have_rocket() {
+        return None;
+    }
+    (0..state.cells_count())
+        .rev()
+        .find(|i| state.cell(*i).is_charged())
+}
+
+/// Error of a combination: the message and the two inputs handed back to the explorer.
+pub(crate) type CombineError = (String, GenericResource, GenericResource);
+
+/// Runs a combination request using the energy of the given cell.
+pub(crate) fn combine(
+    state: &mut PlanetState,
+    cell: usize,
+    combinator: &Combinator,
+    msg: ComplexResourceRequest,
+) -> Result<ComplexResource, CombineError> {
+    match msg {
+        ComplexResourceRequest::Water(hydrogen, oxygen) => combinator
+            .make_water(hydrogen, oxygen, state.cell_mut(cell))
+            .map(ComplexResource::Water)
+            .map_err(|(str, hydrogen, oxygen)| {
+                (
+                    str,
+                    GenericResource::BasicResources(BasicResource::Hydrogen(hydrogen)),
+                    GenericResource::BasicResources(BasicResource::Oxygen(oxygen)),
+                )
+            }),
+        ComplexResourceRequest::Diamond(carbon, carbon1) => combinator
+            .make_diamond(carbon, carbon1, state.cell_mut(cell))
+            .map(ComplexResource::Diamond)
+            .map_err(|(str, carbon, carbon1)| {
+                (
+                    str,
+                    GenericResource::BasicResources(BasicResource::Carbon(carbon)),
+                    GenericResource::BasicResources(BasicResource::Carbon(carbon1)),
+                )
+            }),
+        ComplexResourceRequest::Life(water, carbon) => combinator
+            .make_life(water, carbon, state.cell_mut(cell))
+            .map(ComplexResource::Life)
+            .map_err(|(str, water, carbon)| {
+                (
+                    str,
+                    GenericResource::ComplexResources(ComplexResource::Water(water)),
+                    GenericResource::BasicResources(BasicResource::Carbon(carbon)),
+                )
+            }),
+        ComplexResourceRequest::Robot(silicon, life) => combinator
+            .make_robot(silicon, life, state.cell_mut(cell))
+            .map(ComplexResource::Robot)
+            .map_err(|(str, silicon, life)| {
+                (
+                    str,
+                    GenericResource::BasicResources(BasicResource::Silicon(silicon)),
+                    GenericResource::ComplexResources(ComplexResource::Life(life)),
+                )
+            }),
+        ComplexResourceRequest::Dolphin(water, life) => combinator
+            .make_dolphin(water, life, state.cell_mut(cell))
+            .map(ComplexResource::Dolphin)
+            .map_err(|(str, water, life)| {
+                (
+                    str,
+                    GenericResource::ComplexResources(ComplexResource::Water(water)),
+                    GenericResource::ComplexResources(ComplexResource::Life(life)),
+                )
+            }),
+        ComplexResourceRequest::AIPartner(robot, diamond) => combinator
+            .make_aipartner(robot, diamond, state.cell_mut(cell))
+            .map(ComplexResource::AIPartner)
+            .map_err(|(str, robot, diamond)| {
+                (
+                    str,
+                    GenericResource::ComplexResources(ComplexResource::Robot(robot)),
+                    GenericResource::ComplexResources(ComplexResource::Diamond(diamond)),
+                )
+            }),
+    }
+}
+
+fn explorer_id(msg: &ExplorerToPlanet) -> ID {
+    match msg {
+        ExplorerToPlanet::SupportedResourceRequest { explorer_id }
+        | ExplorerToPlanet::SupportedCombinationRequest { explorer_id }
+        | ExplorerToPlanet::GenerateResourceRequest { explorer_id, .. }
+        | ExplorerToPlanet::CombineResourceRequest { explorer_id, .. }
+        | ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id } => *explorer_id,
+    }
+}
+
+/// Requests that discharge an energy cell when served.
+fn consumes_energy(msg: &ExplorerToPlanet) -> bool {
+    matches!(
+        msg,
+        ExplorerToPlanet::GenerateResourceRequest { .. }
+            | ExplorerToPlanet::CombineResourceRequest { .. }
+    )
+}
+
+/// The [`PlanetError`] matching a failure message of the combinator.
+///
+/// The recipe and the cell are checked before combining, so these are
+/// unexpected; anything else is a problem with the inputs.
+pub(crate) fn combinator_error(message: &str) -> PlanetError {
+    let message = message.to_lowercase();
+    if message.contains("charged") || message.contains("energy") {
+        PlanetError::NoEnergy
+    } else if message.contains("recipe") {
+        PlanetError::UnsupportedResource
+    } else {
+        PlanetError::WrongInputs
+    }
+}
+
+/// Refusal of a combination, handing the inputs back to the explorer.
+fn refuse_combination(msg: ComplexResourceRequest, error: PlanetError) -> CombineError {
+    let (left, right) = recipes::into_inputs(msg);
+    (error.to_string(), left, right)
+}
+
+/// Refusal sent for a request that is not served at all.
+fn refuse(msg: ExplorerToPlanet, error: PlanetError) -> PlanetToExplorer {
+    match msg {
+        ExplorerToPlanet::CombineResourceRequest { msg, .. } => {
+            PlanetToExplorer::CombineResourceResponse {
+                complex_response: Err(refuse_combination(msg, error)),
+            }
+        }
+        _ => PlanetToExplorer::GenerateResourceResponse { resource: None },
     }
 }
@@ -0,0 +1,156 @@
+use common_game::components::resource::{
+    BasicResource, BasicResourceType, ComplexResource, ComplexResourceRequest, ComplexResourceType,
+    GenericResource,
+};
+
+/// One of the two inputs of a combination recipe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Ingredient {
+    Basic(BasicResourceType),
+    Complex(ComplexResourceType),
+}
+
+/// Returns the two inputs needed to combine `target`.
+pub(crate) fn ingredients(target: ComplexResourceType) -> (Ingredient, Ingredient) {
+    use Ingredient::{Basic, Complex};
+    match target {
+        ComplexResourceType::Water => (
+            Basic(BasicResourceType::Hydrogen),
+            Basic(BasicResourceType::Oxygen),
+        ),
+        ComplexResourceType::Diamond => (
+            Basic(BasicResourceType::Carbon),
+            Basic(BasicResourceType::Carbon),
+        ),
+        ComplexResourceType::Life => (
+            Complex(ComplexResourceType::Water),
+            Basic(BasicResourceType::Carbon),
+        ),
+        ComplexResourceType::Robot => (
+            Basic(BasicResourceType::Silicon),
+            Complex(ComplexResourceType::Life),
+        ),
+        ComplexResourceType::Dolphin => (
+            Complex(ComplexResourceType::Water),
+            Complex(ComplexResourceType::Life),
+        ),
+        ComplexResourceType::AIPartner => (
+            Complex(ComplexResourceType::Robot),
+            Complex(ComplexResourceType::Diamond),
+        ),
+    }
+}
+
+/// Returns the complex resource a combination request asks for.
+pub(crate) fn request_target(request: &ComplexResourceRequest) -> ComplexResourceType {
+    match request {
+        ComplexResourceRequest::Water(..) => ComplexResourceType::Water,
+        ComplexResourceRequest::Diamond(..) => ComplexResourceType::Diamond,
+        ComplexResourceRequest::Life(..) => ComplexResourceType::Life,
+        ComplexResourceRequest::Robot(..) => ComplexResourceType::Robot,
+        ComplexResourceRequest::Dolphin(..) => ComplexResourceType::Dolphin,
+        ComplexResourceRequest::AIPartner(..) => ComplexResourceType::AIPartner,
+    }
+}
+
+/// Unpacks a combination request, so its inputs can be handed back to the explorer.
+pub(crate) fn into_inputs(request: ComplexResourceRequest) -> (GenericResource, GenericResource) {
+    use GenericResource::{BasicResources, ComplexResources};
+    match request {
+        ComplexResourceRequest::Water(hydrogen, oxygen) => (
+            BasicResources(BasicResource::Hydrogen(hydrogen)),
+            BasicResources(BasicResource::Oxygen(oxygen)),
+        ),
+        ComplexResourceRequest::Diamond(carbon, carbon1) => (
+            BasicResources(BasicResource::Carbon(carbon)),
+            BasicResources(BasicResource::Carbon(carbon1)),
+        ),
+        ComplexResourceRequest::Life(water, carbon) => (
+            ComplexResources(ComplexResource::Water(water)),
+            BasicResources(BasicResource::Carbon(carbon)),
+        ),
+        ComplexResourceRequest::Robot(silicon, life) => (
+            BasicResources(BasicResource::Silicon(silicon)),
+            ComplexResources(ComplexResource::Life(life)),
+        ),
+        ComplexResourceRequest::Dolphin(water, life) => (
+            ComplexResources(ComplexResource::Water(water)),
+            ComplexResources(ComplexResource::Life(life)),
+        ),
+        ComplexResourceRequest::AIPartner(robot, diamond) => (
+            ComplexResources(ComplexResource::Robot(robot)),
+            ComplexResources(ComplexResource::Diamond(diamond)),
+        ),
+    }
+}
+
+pub(crate) fn basic_type(resource: &BasicResource) -> BasicResourceType {
+    match resource {
+        BasicResource::Oxygen(_) => BasicResourceType::Oxygen,
+        BasicResource::Hydrogen(_) => BasicResourceType::Hydrogen,
+        BasicResource::Carbon(_) => BasicResourceType::Carbon,
+        BasicResource::Silicon(_) => BasicResourceType::Silicon,
+    }
+}
+
+pub(crate) fn complex_type(resource: &ComplexResource) -> ComplexResourceType {
+    match resource {
+        ComplexResource::Diamond(_) => ComplexResourceType::Diamond,
+        ComplexResource::Water(_) => ComplexResourceType::Water,
+        ComplexResource::Life(_) => ComplexResourceType::Life,
+        ComplexResource::Robot(_) => ComplexResourceType::Robot,
+        ComplexResource::Dolphin(_) => ComplexResourceType::Dolphin,
+        ComplexResource::AIPartner(_) => ComplexResourceType::AIPartner,
+    }
+}
+
+pub(crate) fn ingredient_of(resource: &GenericResource) -> Ingredient {
+    match resource {
+        GenericResource::BasicResources(basic) => Ingredient::Basic(basic_type(basic)),
+        GenericResource::ComplexResources(complex) => Ingredient::Complex(complex_type(complex)),
+    }
+}
+
+/// Packs two inputs into the request that combines them into `target`.
+///
+/// The inputs are handed back unchanged if they don't match the recipe.
+pub(crate) fn build_request(
+    target: ComplexResourceType,
+    left: GenericResource,
+    right: GenericResource,
+) -> Result<ComplexResourceRequest, (GenericResource, GenericResource)> {
+    use GenericResource::{BasicResources, ComplexResources};
+    match (target, left, right) {
+        (
+            ComplexResourceType::Water,
+            BasicResources(BasicResource::Hydrogen(hydrogen)),
+            BasicResources(BasicResource::Oxygen(oxygen)),
+        ) => Ok(ComplexResourceRequest::Water(hydrogen, oxygen)),
+        (
+            ComplexResourceType::Diamond,
+            BasicResources(BasicResource::Carbon(carbon)),
+            BasicResources(BasicResource::Carbon(carbon1)),
+        ) => Ok(ComplexResourceRequest::Diamond(carbon, carbon1)),
+        (
+            ComplexResourceType::Life,
+            ComplexResources(ComplexResource::Water(water)),
+            BasicResources(BasicResource::Carbon(carbon)),
+        ) => Ok(ComplexResourceRequest::Life(water, carbon)),
+        (
+            ComplexResourceType::Robot,
+            BasicResources(BasicResource::Silicon(silicon)),
+            ComplexResources(ComplexResource::Life(life)),
+        ) => Ok(ComplexResourceRequest::Robot(silicon, life)),
+        (
+            ComplexResourceType::Dolphin,
+            ComplexResources(ComplexResource::Water(water)),
+            ComplexResources(ComplexResource::Life(life)),
+        ) => Ok(ComplexResourceRequest::Dolphin(water, life)),
+        (
+            ComplexResourceType::AIPartner,
+            ComplexResources(ComplexResource::Robot(robot)),
+            ComplexResources(ComplexResource::Diamond(diamond)),
+        ) => Ok(ComplexResourceRequest::AIPartner(robot, diamond)),
+        (_, left, right) => Err((left, right)),
+    }
+}
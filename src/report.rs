@@ -0,0 +1,77 @@
+use common_game::utils::ID;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::bandit::BanditStats;
+use crate::credits::CreditAccount;
+use crate::energy::EnergyReport;
+use crate::explain::Explanation;
+use crate::forecast::EnergyForecast;
+use crate::lifecycle::{LifecyclePhase, Transition};
+use crate::overflow::SunrayStats;
+use crate::reputation::ExplorerReputation;
+use crate::stock::StockReport;
+use crate::warning::Warning;
+
+/// Snapshot of the [`PlanetAI`](crate::PlanetAI) bookkeeping that is not part of
+/// the `DummyPlanetState` sent to the orchestrator.
+///
+/// It is refreshed every time the planet handles an `InternalStateRequest`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanetReport {
+    /// Whether the planet AI is currently running.
+    pub started: bool,
+    /// Explorers currently on the planet, sorted by ID.
+    pub explorers: Vec<ID>,
+    /// Phase of the lifecycle; `started` is `true` while it is `Running`.
+    pub lifecycle: LifecyclePhase,
+    /// The last lifecycle transitions, rejected ones included, oldest first.
+    pub transitions: Vec<Transition>,
+    /// Whether an asteroid warning is waiting to be delivered to the explorers.
+    pub pending_warning: bool,
+    /// The last asteroid warnings, oldest first.
+    pub warnings: Vec<Warning>,
+    /// Reputation of every explorer that sent at least one request, sorted by ID.
+    pub reputations: Vec<ExplorerReputation>,
+    /// Carbon credit accounts, sorted by explorer ID.
+    pub credits: Vec<CreditAccount>,
+    /// Where the energy of the cells went.
+    pub energy: EnergyReport,
+    /// What happened to the sunrays received so far.
+    pub sunrays: SunrayStats,
+    /// Carbon banked with the overflowing sunrays.
+    pub carbon_stock: StockReport,
+    /// Statistics of the sunray/asteroid policies, see [`BanditConfig`](crate::BanditConfig).
+    pub strategy: BanditStats,
+    /// Asteroids sighted by sibling planets, see [`ThreatBus`](crate::ThreatBus).
+    pub sibling_sightings: u32,
+    /// The last decisions of the AI, oldest first, see [`Explanation`].
+    pub explanations: Vec<Explanation>,
+    /// When a charged cell will be free for the explorers.
+    pub energy_forecast: EnergyForecast,
+}
+
+/// Cloneable handle used to read the latest [`PlanetReport`] from another thread.
+///
+/// The handle must be taken with [`PlanetAI::report_handle`](crate::PlanetAI::report_handle)
+/// before the AI is moved into the planet.
+#[derive(Debug, Clone, Default)]
+pub struct ReportHandle(Arc<Mutex<PlanetReport>>);
+
+impl ReportHandle {
+    /// Returns a copy of the last published report.
+    pub fn snapshot(&self) -> PlanetReport {
+        self.lock().clone()
+    }
+
+    pub(crate) fn publish(&self, report: PlanetReport) {
+        *self.lock() = report;
+    }
+
+    // A panic while holding the lock can't leave the report half-written,
+    // so a poisoned mutex is still safe to read
+    fn lock(&self) -> MutexGuard<'_, PlanetReport> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use common_game::utils::ID;
+
+/// Tuning of the explorer reputation system.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReputationConfig {
+    /// Sliding window used to measure the request frequency.
+    pub window: Duration,
+    /// Requests spending energy accepted per window from an explorer in good
+    /// standing; the other requests are never throttled.
+    pub max_requests: u32,
+    /// Requests spending energy accepted per window from an explorer whose
+    /// score is below [`min_score`](Self::min_score).
+    pub probation_requests: u32,
+    /// Score under which an explorer is put on probation.
+    pub min_score: f64,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        ReputationConfig {
+            window: Duration::from_secs(10),
+            max_requests: 20,
+            probation_requests: 2,
+            min_score: 0.3,
+        }
+    }
+}
+
+/// What the planet knows about an explorer, as exposed in the
+/// [`PlanetReport`](crate::PlanetReport).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExplorerReputation {
+    pub explorer_id: ID,
+    pub combinations_ok: u32,
+    pub combinations_failed: u32,
+    /// Basic resources generated for the explorer.
+    pub resources_taken: u32,
+    /// Resources the explorer brought and got combined.
+    pub resources_contributed: u32,
+    /// Requests of any kind received so far.
+    pub requests: u32,
+    /// Requests spending energy refused because the explorer was over its rate.
+    pub throttled: u32,
+    /// Score in `[0, 1]`, see [`ExplorerReputation::score`].
+    pub score: f64,
+}
+
+impl ExplorerReputation {
+    /// Weighted mix of the combination success rate (70%) and of the
+    /// contributed/taken balance (30%).
+    ///
+    /// Both ratios are Laplace-smoothed, so a newcomer starts at 0.5.
+    fn score(&self) -> f64 {
+        let ok = self.combinations_ok as f64;
+        let failed = self.combinations_failed as f64;
+        let taken = self.resources_taken as f64;
+        let contributed = self.resources_contributed as f64;
+
+        let success = (ok + 1.0) / (ok + failed + 2.0);
+        let balance = (contributed + 1.0) / (contributed + taken + 2.0);
+        0.7 * success + 0.3 * balance
+    }
+}
+
+#[derive(Default)]
+struct Record {
+    reputation: ExplorerReputation,
+    recent: VecDeque<Instant>,
+}
+
+/// Per-explorer reputation kept by the [`PlanetAI`](crate::PlanetAI).
+///
+/// Records survive the explorer departure, so a returning explorer keeps its score.
+pub(crate) struct ReputationBook {
+    config: ReputationConfig,
+    records: HashMap<ID, Record>,
+}
+
+impl ReputationBook {
+    pub(crate) fn new(config: ReputationConfig) -> ReputationBook {
+        ReputationBook {
+            config,
+            records: HashMap::new(),
+        }
+    }
+
+    fn record(&mut self, explorer_id: ID) -> &mut Record {
+        self.records.entry(explorer_id).or_insert_with(|| Record {
+            reputation: ExplorerReputation {
+                explorer_id,
+                ..ExplorerReputation::default()
+            },
+            recent: VecDeque::new(),
+        })
+    }
+
+    /// Counts a request of any kind, see [`ExplorerReputation::requests`].
+    pub(crate) fn received(&mut self, explorer_id: ID) {
+        self.record(explorer_id).reputation.requests += 1;
+    }
+
+    /// Tells whether a request spending energy is still within the explorer's
+    /// rate, and counts it toward the rate if it is.
+    ///
+    /// Refused requests are not counted: an explorer retrying is served again
+    /// as soon as its earlier requests leave the window.
+    pub(crate) fn admit(&mut self, explorer_id: ID, now: Instant) -> bool {
+        let window = self.config.window;
+        let (max_requests, probation_requests, min_score) = (
+            self.config.max_requests,
+            self.config.probation_requests,
+            self.config.min_score,
+        );
+
+        let record = self.record(explorer_id);
+        while record
+            .recent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > window)
+        {
+            record.recent.pop_front();
+        }
+
+        let allowed = if record.reputation.score() < min_score {
+            probation_requests
+        } else {
+            max_requests
+        };
+        let admitted = record.recent.len() < allowed as usize;
+        if admitted {
+            record.recent.push_back(now);
+        }
+        admitted
+    }
+
+    pub(crate) fn throttled(&mut self, explorer_id: ID) {
+        self.record(explorer_id).reputation.throttled += 1;
+    }
+
+    pub(crate) fn resource_taken(&mut self, explorer_id: ID) {
+        self.record(explorer_id).reputation.resources_taken += 1;
+    }
+
+    /// Records a combination, served or failed because of the explorer's request.
+    ///
+    /// Failures of the planet, such as a missing charged cell, are not the
+    /// explorer's fault and must not be recorded.
+    pub(crate) fn combination(&mut self, explorer_id: ID, ok: bool) {
+        let reputation = &mut self.record(explorer_id).reputation;
+        if ok {
+            reputation.combinations_ok += 1;
+            reputation.resources_contributed += 2;
+        } else {
+            reputation.combinations_failed += 1;
+        }
+    }
+
+    /// Reputation of every explorer seen so far, sorted by ID.
+    pub(crate) fn snapshot(&self) -> Vec<ExplorerReputation> {
+        let mut reputations: Vec<ExplorerReputation> = self
+            .records
+            .values()
+            .map(|record| ExplorerReputation {
+                score: record.reputation.score(),
+                ..record.reputation.clone()
+            })
+            .collect();
+        reputations.sort_by_key(|reputation| reputation.explorer_id);
+        reputations
+    }
+}
@@ -0,0 +1,591 @@
+//! Rule language driving the sunray and asteroid decisions.
+//!
+//! A rule set is plain text, one rule per line:
+//!
+//! ```text
+//! # Keep a rocket ready once the cells are full
+//! when sunray and not rocket and not cell_empty -> build_rocket
+//! when asteroid and not rocket and cell_charged -> build_rocket
+//! when sunray and asteroid_rate >= 2 and not rocket -> build_rocket
+//! ```
+//!
+//! On every sunray and asteroid the rules are checked top to bottom against
+//! the current [`Fact`]s, and every rule whose condition holds fires its
+//! [`RuleAction`]. Facts are read again before each rule, so a rule sees what the
+//! previous ones did. Whatever the rules decide, the sunray then charges an
+//! empty cell (or is wasted), and the asteroid is answered with the rocket, if any.
+//!
+//! A condition is made of facts joined by `and` and `or` (`and` binds
+//! tighter), each optionally negated with `not` or compared to a number with
+//! `<`, `<=`, `>`, `>=`, `==` or `!=`. A bare fact holds when it isn't zero.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Rules reproducing the built-in behaviour of the [`PlanetAI`](crate::PlanetAI).
+pub const DEFAULT_RULES: &str = "\
+# A sunray with every cell charged would be wasted: store its energy in a rocket
+when sunray and not rocket and not cell_empty -> build_rocket
+when sunray and not rocket and rocket_first -> build_rocket
+when sunray and not rocket and threat -> build_rocket
+when sunray and not cell_empty and stock_overflow -> stock_carbon
+when asteroid and not rocket and build_on_demand -> build_rocket
+";
+
+/// Facts through which the bandit and the overflow policy drive the rules.
+const POLICY_FACTS: [Fact; 3] = [Fact::RocketFirst, Fact::BuildOnDemand, Fact::StockOverflow];
+
+/// Something the planet knows when a rule is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fact {
+    /// The event is a sunray.
+    Sunray,
+    /// The event is an asteroid.
+    Asteroid,
+    /// A rocket is ready.
+    Rocket,
+    /// At least one cell is charged.
+    CellCharged,
+    /// At least one cell is empty.
+    CellEmpty,
+    /// Number of charged cells.
+    ChargedCells,
+    /// Number of explorers on the planet.
+    Explorers,
+    /// An asteroid hit the planet and no explorer was warned yet.
+    PendingWarning,
+    /// Asteroids per minute over the last minute, the current one included.
+    AsteroidRate,
+    /// The bandit plays [`SunrayPolicy::RocketFirst`](crate::SunrayPolicy::RocketFirst).
+    RocketFirst,
+    /// The bandit plays [`AsteroidPolicy::BuildOnDemand`](crate::AsteroidPolicy::BuildOnDemand).
+    BuildOnDemand,
+    /// The overflow policy is [`OverflowPolicy::StockCarbon`](crate::OverflowPolicy::StockCarbon).
+    StockOverflow,
+    /// The Carbon stock is full.
+    StockFull,
+    /// A sibling planet sighted an asteroid lately, see [`ThreatBus`](crate::ThreatBus).
+    Threat,
+}
+
+impl Fact {
+    const ALL: [Fact; 14] = [
+        Fact::Sunray,
+        Fact::Asteroid,
+        Fact::Rocket,
+        Fact::CellCharged,
+        Fact::CellEmpty,
+        Fact::ChargedCells,
+        Fact::Explorers,
+        Fact::PendingWarning,
+        Fact::AsteroidRate,
+        Fact::RocketFirst,
+        Fact::BuildOnDemand,
+        Fact::StockOverflow,
+        Fact::StockFull,
+        Fact::Threat,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Fact::Sunray => "sunray",
+            Fact::Asteroid => "asteroid",
+            Fact::Rocket => "rocket",
+            Fact::CellCharged => "cell_charged",
+            Fact::CellEmpty => "cell_empty",
+            Fact::ChargedCells => "charged_cells",
+            Fact::Explorers => "explorers",
+            Fact::PendingWarning => "pending_warning",
+            Fact::AsteroidRate => "asteroid_rate",
+            Fact::RocketFirst => "rocket_first",
+            Fact::BuildOnDemand => "build_on_demand",
+            Fact::StockOverflow => "stock_overflow",
+            Fact::StockFull => "stock_full",
+            Fact::Threat => "threat",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Fact> {
+        Fact::ALL.into_iter().find(|fact| fact.name() == name)
+    }
+}
+
+/// What a rule makes the planet do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleAction {
+    /// Turn a charged cell into a rocket, if the planet can have one and has none.
+    BuildRocket,
+    /// Spend a charged cell on Carbon kept in stock, see [`StockConfig`](crate::StockConfig).
+    StockCarbon,
+}
+
+impl RuleAction {
+    const ALL: [RuleAction; 2] = [RuleAction::BuildRocket, RuleAction::StockCarbon];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleAction::BuildRocket => "build_rocket",
+            RuleAction::StockCarbon => "stock_carbon",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<RuleAction> {
+        RuleAction::ALL
+            .into_iter()
+            .find(|action| action.name() == name)
+    }
+}
+
+/// Comparison between a fact and a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    const ALL: [Comparison; 6] = [
+        Comparison::LessOrEqual,
+        Comparison::GreaterOrEqual,
+        Comparison::Equal,
+        Comparison::NotEqual,
+        Comparison::Less,
+        Comparison::Greater,
+    ];
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+        }
+    }
+
+    fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Less => value < threshold,
+            Comparison::LessOrEqual => value <= threshold,
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterOrEqual => value >= threshold,
+            Comparison::Equal => value == threshold,
+            Comparison::NotEqual => value != threshold,
+        }
+    }
+}
+
+/// A single, possibly negated, test of a fact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleTest {
+    pub negated: bool,
+    pub fact: Fact,
+    /// `None` for a bare fact, which holds when it isn't zero.
+    pub comparison: Option<(Comparison, f64)>,
+}
+
+impl RuleTest {
+    fn holds(&self, facts: &Facts) -> bool {
+        let value = facts.value(self.fact);
+        let holds = match self.comparison {
+            Some((comparison, threshold)) => comparison.holds(value, threshold),
+            None => value != 0.0,
+        };
+        holds != self.negated
+    }
+}
+
+impl fmt::Display for RuleTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            write!(f, "not ")?;
+        }
+        write!(f, "{}", self.fact.name())?;
+        if let Some((comparison, threshold)) = self.comparison {
+            write!(f, " {} {}", comparison.symbol(), threshold)?;
+        }
+        Ok(())
+    }
+}
+
+/// `when <condition> -> <action>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    /// Alternatives joined by `or`, each made of tests joined by `and`.
+    pub condition: Vec<Vec<RuleTest>>,
+    pub action: RuleAction,
+}
+
+impl Rule {
+    fn holds(&self, facts: &Facts) -> bool {
+        self.condition
+            .iter()
+            .any(|tests| tests.iter().all(|test| test.holds(facts)))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "when ")?;
+        for (i, tests) in self.condition.iter().enumerate() {
+            if i > 0 {
+                write!(f, " or ")?;
+            }
+            for (j, test) in tests.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " and ")?;
+                }
+                write!(f, "{}", test)?;
+            }
+        }
+        write!(f, " -> {}", self.action.name())
+    }
+}
+
+/// Ordered rules, see the [module documentation](self).
+///
+/// The default set is [`DEFAULT_RULES`]. The bandit policies and
+/// [`OverflowPolicy::StockCarbon`](crate::OverflowPolicy::StockCarbon) only act
+/// through the rules reading `rocket_first`, `build_on_demand` and
+/// `stock_overflow`: a custom set should repeat them, and parsing one that
+/// reads none of these facts logs a warning.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{PlanetConfig, RuleSet};
+///
+/// let rules = RuleSet::parse("when asteroid and not rocket and cell_charged -> build_rocket").unwrap();
+/// let config = PlanetConfig { rules, ..PlanetConfig::default() };
+///
+/// let err = RuleSet::parse("when asteroid and not rokcet -> build_rocket").unwrap_err();
+/// assert_eq!(err, "line 1, column 23: unknown fact \"rokcet\"");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::parse(DEFAULT_RULES).expect("the default rules are valid")
+    }
+}
+
+impl RuleSet {
+    /// Parses a rule set; errors point at the line and column of the problem.
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<RuleSet, String> {
+        let mut rules = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let rule = Parser::new(line)
+                .and_then(|mut parser| parser.rule())
+                .map_err(|(column, message)| {
+                    format!("line {}, column {}: {}", index + 1, column, message)
+                })?;
+            rules.push(rule);
+        }
+        let rule_set = RuleSet { rules };
+        if !POLICY_FACTS.iter().any(|fact| rule_set.reads(*fact)) {
+            log::warn!(
+                "the rules read none of {}: the bandit and the overflow policy are ignored",
+                POLICY_FACTS.map(|fact| fact.name()).join(", ")
+            );
+        }
+        Ok(rule_set)
+    }
+
+    /// Whether a rule condition tests the fact.
+    pub fn reads(&self, fact: Fact) -> bool {
+        self.rules
+            .iter()
+            .flat_map(|rule| rule.condition.iter().flatten())
+            .any(|test| test.fact == fact)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<RuleSet, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("can't read {}: {}", path.display(), err))?;
+        RuleSet::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    /// Index of the first rule at or after `from` whose condition holds.
+    pub(crate) fn next_match(&self, from: usize, facts: &Facts) -> Option<usize> {
+        (from..self.rules.len()).find(|i| self.rules[*i].holds(facts))
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for rule in &self.rules {
+            writeln!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+/// Values of every [`Fact`] at the time a rule is checked.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Facts {
+    pub(crate) sunray: bool,
+    pub(crate) asteroid: bool,
+    pub(crate) rocket: bool,
+    pub(crate) charged_cells: usize,
+    pub(crate) cells: usize,
+    pub(crate) explorers: usize,
+    pub(crate) pending_warning: bool,
+    pub(crate) asteroid_rate: f64,
+    pub(crate) rocket_first: bool,
+    pub(crate) build_on_demand: bool,
+    pub(crate) stock_overflow: bool,
+    pub(crate) stock_full: bool,
+    pub(crate) threat: bool,
+}
+
+impl Facts {
+    fn value(&self, fact: Fact) -> f64 {
+        let flag = |b: bool| if b { 1.0 } else { 0.0 };
+        match fact {
+            Fact::Sunray => flag(self.sunray),
+            Fact::Asteroid => flag(self.asteroid),
+            Fact::Rocket => flag(self.rocket),
+            Fact::CellCharged => flag(self.charged_cells > 0),
+            Fact::CellEmpty => flag(self.charged_cells < self.cells),
+            Fact::ChargedCells => self.charged_cells as f64,
+            Fact::Explorers => self.explorers as f64,
+            Fact::PendingWarning => flag(self.pending_warning),
+            Fact::AsteroidRate => self.asteroid_rate,
+            Fact::RocketFirst => flag(self.rocket_first),
+            Fact::BuildOnDemand => flag(self.build_on_demand),
+            Fact::StockOverflow => flag(self.stock_overflow),
+            Fact::StockFull => flag(self.stock_full),
+            Fact::Threat => flag(self.threat),
+        }
+    }
+}
+
+/// Asteroids seen in the last minute, for the `asteroid_rate` fact.
+#[derive(Default)]
+pub(crate) struct AsteroidRate {
+    hits: VecDeque<Instant>,
+}
+
+impl AsteroidRate {
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    pub(crate) fn record(&mut self, now: Instant) {
+        self.hits.push_back(now);
+    }
+
+    /// Asteroids per minute.
+    pub(crate) fn per_minute(&mut self, now: Instant) -> f64 {
+        while self
+            .hits
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > Self::WINDOW)
+        {
+            self.hits.pop_front();
+        }
+        self.hits.len() as f64
+    }
+}
+
+// =========================================================================
+// PARSER
+// =========================================================================
+
+/// Error of the parser: 1-based column and message.
+type ParseError = (usize, String);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Number(f64),
+    Arrow,
+    Comparison(Comparison),
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{:?}", word),
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Arrow => write!(f, "'->'"),
+            Token::Comparison(comparison) => write!(f, "'{}'", comparison.symbol()),
+        }
+    }
+}
+
+struct Parser<'a> {
+    /// Tokens with their 1-based column.
+    tokens: Vec<(usize, Token<'a>)>,
+    position: usize,
+    /// Column right after the last character, reported for a missing token.
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(line: &'a str) -> Result<Parser<'a>, ParseError> {
+        let mut tokens = Vec::new();
+        let mut rest = line.char_indices().peekable();
+        while let Some(&(start, c)) = rest.peek() {
+            let column = start + 1;
+            if c.is_whitespace() {
+                rest.next();
+            } else if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                let mut end = start;
+                while let Some(&(i, c)) = rest.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    rest.next();
+                }
+                let text = &line[start..end];
+                let token = if c.is_ascii_digit() || c == '.' {
+                    Token::Number(
+                        text.parse()
+                            .map_err(|_| (column, format!("invalid number {:?}", text)))?,
+                    )
+                } else {
+                    Token::Word(text)
+                };
+                tokens.push((column, token));
+            } else if line[start..].starts_with("->") {
+                tokens.push((column, Token::Arrow));
+                rest.next();
+                rest.next();
+            } else if let Some(comparison) = Comparison::ALL
+                .into_iter()
+                .find(|comparison| line[start..].starts_with(comparison.symbol()))
+            {
+                tokens.push((column, Token::Comparison(comparison)));
+                for _ in 0..comparison.symbol().len() {
+                    rest.next();
+                }
+            } else {
+                return Err((column, format!("unexpected character {:?}", c)));
+            }
+        }
+        Ok(Parser {
+            tokens,
+            position: 0,
+            end: line.len() + 1,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end, |(column, _)| *column)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<(usize, Token<'a>), ParseError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => Err((
+                self.end,
+                format!("expected {}, found the end of the line", expected),
+            )),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if self.peek() == Some(&Token::Word(keyword)) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn rule(&mut self) -> Result<Rule, ParseError> {
+        match self.next("'when'")? {
+            (_, Token::Word("when")) => {}
+            (column, token) => return Err((column, format!("expected 'when', found {}", token))),
+        }
+
+        let mut condition = vec![vec![self.test()?]];
+        loop {
+            if self.keyword("and") {
+                let test = self.test()?;
+                condition.last_mut().unwrap().push(test);
+            } else if self.keyword("or") {
+                condition.push(vec![self.test()?]);
+            } else {
+                break;
+            }
+        }
+
+        match self.next("'->'")? {
+            (_, Token::Arrow) => {}
+            (column, token) => {
+                return Err((
+                    column,
+                    format!("expected 'and', 'or' or '->', found {}", token),
+                ));
+            }
+        }
+        let action = match self.next("an action")? {
+            (column, Token::Word(name)) => RuleAction::from_name(name)
+                .ok_or_else(|| (column, format!("unknown action {:?}", name)))?,
+            (column, token) => {
+                return Err((column, format!("expected an action, found {}", token)));
+            }
+        };
+        if self.peek().is_some() {
+            let column = self.column();
+            let (_, token) = self.next("")?;
+            return Err((column, format!("unexpected {} after the action", token)));
+        }
+        Ok(Rule { condition, action })
+    }
+
+    fn test(&mut self) -> Result<RuleTest, ParseError> {
+        let negated = self.keyword("not");
+        let fact = match self.next("a fact")? {
+            (column, Token::Word(name)) => {
+                Fact::from_name(name).ok_or_else(|| (column, format!("unknown fact {:?}", name)))?
+            }
+            (column, token) => return Err((column, format!("expected a fact, found {}", token))),
+        };
+        let comparison = match self.peek() {
+            Some(Token::Comparison(comparison)) => {
+                let comparison = *comparison;
+                self.position += 1;
+                match self.next("a number")? {
+                    (_, Token::Number(threshold)) => Some((comparison, threshold)),
+                    (column, token) => {
+                        return Err((column, format!("expected a number, found {}", token)));
+                    }
+                }
+            }
+            _ => None,
+        };
+        Ok(RuleTest {
+            negated,
+            fact,
+            comparison,
+        })
+    }
+}
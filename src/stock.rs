@@ -0,0 +1,70 @@
+use common_game::components::resource::Carbon;
+
+/// Tuning of the Carbon stock filled by [`OverflowPolicy::StockCarbon`](crate::OverflowPolicy::StockCarbon).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StockConfig {
+    /// Units of Carbon the stock can hold; the overflowing sunrays are wasted
+    /// once it's full.
+    pub cap: usize,
+}
+
+impl Default for StockConfig {
+    fn default() -> Self {
+        StockConfig { cap: 16 }
+    }
+}
+
+/// State of the Carbon stock, as exposed in the [`PlanetReport`](crate::PlanetReport).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StockReport {
+    /// Units currently in stock.
+    pub level: u32,
+    pub cap: u32,
+    /// Units banked since the planet was created.
+    pub banked: u32,
+    /// Units handed to explorers since the planet was created.
+    pub served: u32,
+}
+
+/// Carbon produced with spare energy, handed to explorers before any energy cell is spent.
+pub(crate) struct CarbonStock {
+    config: StockConfig,
+    units: Vec<Carbon>,
+    banked: u32,
+    served: u32,
+}
+
+impl CarbonStock {
+    pub(crate) fn new(config: StockConfig) -> CarbonStock {
+        CarbonStock {
+            config,
+            units: Vec::new(),
+            banked: 0,
+            served: 0,
+        }
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.units.len() >= self.config.cap
+    }
+
+    pub(crate) fn bank(&mut self, carbon: Carbon) {
+        self.units.push(carbon);
+        self.banked += 1;
+    }
+
+    pub(crate) fn take(&mut self) -> Option<Carbon> {
+        let carbon = self.units.pop()?;
+        self.served += 1;
+        Some(carbon)
+    }
+
+    pub(crate) fn snapshot(&self) -> StockReport {
+        StockReport {
+            level: self.units.len() as u32,
+            cap: self.config.cap as u32,
+            banked: self.banked,
+            served: self.served,
+        }
+    }
+}
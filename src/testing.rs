@@ -0,0 +1,566 @@
+//! Helpers for crates testing their explorers and orchestrators against AirFryer.
+//!
+//! Enabled by the `testing` feature:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! air_fryer = { ..., features = ["testing"] }
+//! ```
+//!
+//! Everything in this module is stable API: it follows the same versioning
+//! rules as the rest of the crate.
+//!
+//! - [`spawn_planet`], [`spawn_planet_with_ai`], [`spawn_planet_of_type`],
+//!   [`spawn_resource_planet`] and [`spawn_mock_planet`] start a planet in its own thread and return the
+//!   [`TestContext`] holding both ends of its channels.
+//! - [`spawn_with_config`] does the same for a [`PlanetAI`] built from a
+//!   [`PlanetConfig`], keeping its [`ReportHandle`]; [`report`] refreshes and
+//!   reads the [`PlanetReport`].
+//! - [`register_explorer_with_planet`], [`charge_planet_with_sunrays`],
+//!   [`send_asteroid`], [`get_basic_resource`] and [`combine_resources`] drive the planet.
+//! - The `extract_*` helpers unwrap a specific resource out of the
+//!   `BasicResource` / `ComplexResource` enums.
+//! - [`MockAI`] is a configurable partner planet: it generates the basic
+//!   resources and combines the recipes listed in its [`MockConfig`], so a
+//!   test can provide everything AirFryer itself can't.
+//!
+//! No helper blocks forever: every wait for a planet message gives up after
+//! [`TestContext::timeout`] and panics with a message saying what was missing,
+//! so a broken planet fails the test instead of hanging it.
+//!
+//! # The shared Forge
+//!
+//! `Forge` allows a single instance per process, and the test harness runs
+//! every test of a crate in the same process. All the helpers take their
+//! sunrays and asteroids from the Forge returned by [`forge`]: a test crate
+//! using this module must get its Forge from there too, never from `Forge::new()`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use air_fryer::testing::{
+//!     charge_planet_with_sunrays, extract_carbon, get_basic_resource,
+//!     register_explorer_with_planet, spawn_planet,
+//! };
+//! use common_game::components::resource::BasicResourceType;
+//!
+//! let planet = spawn_planet();
+//! register_explorer_with_planet(&planet, 7);
+//! charge_planet_with_sunrays(&planet, 1);
+//!
+//! let carbon = get_basic_resource(&planet, 7, BasicResourceType::Carbon);
+//! assert!(extract_carbon(carbon).is_some());
+//! ```
+
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common_game::{
+    components::{
+        forge::Forge,
+        planet as common_planet,
+        resource::{
+            AIPartner, BasicResource, BasicResourceType, Carbon, ComplexResource,
+            ComplexResourceRequest, Diamond, Dolphin, GenericResource, Hydrogen, Life, Oxygen,
+            Robot, Silicon, Water,
+        },
+    },
+    protocols::{
+        orchestrator_planet::{OrchestratorToPlanet, PlanetToOrchestrator},
+        planet_explorer::{ExplorerToPlanet, PlanetToExplorer},
+    },
+    utils::ID,
+};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+
+pub use crate::mock_planet::{MockAI, MockConfig};
+use crate::{
+    ExtensionClient, ExtensionRequest, ExtensionResponse, PlanetAI, PlanetConfig, PlanetReport,
+    ReportHandle, create_planet, create_planet_with_extension,
+};
+
+/// How long the helpers wait for a planet message before failing the test.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// ID of the AirFryer planets spawned by this module.
+pub const PLANET_ID: ID = 0;
+
+/// ID of the planets spawned by [`spawn_resource_planet`] and [`spawn_mock_planet`].
+pub const MOCK_PLANET_ID: ID = 1;
+
+static FORGE: OnceLock<Result<Forge, String>> = OnceLock::new();
+
+/// The process-wide Forge used by every helper of this module.
+///
+/// # Panics
+///
+/// Panics if a Forge was already created with `Forge::new()` somewhere else
+/// in the process, see [`try_forge`].
+pub fn forge() -> &'static Forge {
+    match try_forge() {
+        Ok(forge) => forge,
+        Err(err) => panic!("the shared test Forge can't be created: {}", err),
+    }
+}
+
+/// Same as [`forge`], but returns the `Forge::new()` error instead of panicking.
+///
+/// The outcome of the first call is kept: once the Forge could not be created,
+/// every later call returns the same error.
+pub fn try_forge() -> Result<&'static Forge, String> {
+    FORGE.get_or_init(Forge::new).as_ref().map_err(Clone::clone)
+}
+
+/// Both ends of the channels of a planet running in its own thread.
+///
+/// The test plays the orchestrator through `snd_orc_to_planet` /
+/// `rcv_planet_to_orc` and the explorers through `snd_exp_to_planet` /
+/// `rcv_planet_to_exp`. `snd_planet_to_exp` is the sender handed to the planet
+/// when an explorer arrives.
+pub struct TestContext {
+    pub snd_orc_to_planet: Sender<OrchestratorToPlanet>,
+    pub snd_exp_to_planet: Sender<ExplorerToPlanet>,
+    pub snd_planet_to_exp: Sender<PlanetToExplorer>,
+    pub rcv_planet_to_exp: Receiver<PlanetToExplorer>,
+    pub rcv_planet_to_orc: Receiver<PlanetToOrchestrator>,
+    /// How long the helpers wait for each planet message, [`DEFAULT_TIMEOUT`] by default.
+    pub timeout: Duration,
+}
+
+impl TestContext {
+    /// Next message sent to the orchestrator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if nothing arrives within the timeout or the planet is gone.
+    pub fn recv_orchestrator_msg(&self) -> PlanetToOrchestrator {
+        match self.rcv_planet_to_orc.recv_timeout(self.timeout) {
+            Ok(msg) => msg,
+            Err(err) => panic!("no message for the orchestrator: {}", describe(err)),
+        }
+    }
+
+    /// Next message sent to the explorers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if nothing arrives within the timeout or the planet is gone.
+    pub fn recv_explorer_msg(&self) -> PlanetToExplorer {
+        match self.rcv_planet_to_exp.recv_timeout(self.timeout) {
+            Ok(msg) => msg,
+            Err(err) => panic!("no message for the explorer: {}", describe(err)),
+        }
+    }
+
+    /// Waits for the orchestrator message picked by `select`, discarding the
+    /// ones sent before it.
+    fn wait_orchestrator_msg<T>(
+        &self,
+        expected: &str,
+        select: impl Fn(PlanetToOrchestrator) -> Option<T>,
+    ) -> T {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.rcv_planet_to_orc.recv_timeout(left) {
+                Ok(msg) => {
+                    if let Some(value) = select(msg) {
+                        return value;
+                    }
+                }
+                Err(err) => panic!("{} not received: {}", expected, describe(err)),
+            }
+        }
+    }
+}
+
+fn describe(err: RecvTimeoutError) -> &'static str {
+    match err {
+        RecvTimeoutError::Timeout => "timed out",
+        RecvTimeoutError::Disconnected => "the planet is gone",
+    }
+}
+
+/// Spawns an AirFryer planet with the default [`PlanetAI`] and starts its AI.
+///
+/// # Panics
+///
+/// Panics if the planet can't be created or doesn't confirm the start.
+pub fn spawn_planet() -> TestContext {
+    spawn_planet_with_ai(PlanetAI::new())
+}
+
+/// Same as [`spawn_planet`], but lets the test keep handles taken from the AI.
+///
+/// # Panics
+///
+/// Panics if the planet can't be created or doesn't confirm the start.
+pub fn spawn_planet_with_ai(ai: impl common_planet::PlanetAI + 'static) -> TestContext {
+    spawn_planet_of_type(common_planet::PlanetType::C, ai)
+}
+
+/// Spawns an AirFryer planet whose AI is built from `config`.
+///
+/// Returns the [`ReportHandle`] of the AI along with the planet, see [`report`].
+///
+/// # Panics
+///
+/// Panics if the planet can't be created or doesn't confirm the start.
+pub fn spawn_with_config(config: PlanetConfig) -> (TestContext, ReportHandle) {
+    let ai = PlanetAI::with_config(config);
+    let report = ai.report_handle();
+    (spawn_planet_with_ai(ai), report)
+}
+
+/// Same as [`spawn_planet_with_ai`], for an AirFryer planet of any type.
+///
+/// # Panics
+///
+/// Panics if the planet can't be created or doesn't confirm the start.
+pub fn spawn_planet_of_type(
+    planet_type: common_planet::PlanetType,
+    ai: impl common_planet::PlanetAI + 'static,
+) -> TestContext {
+    spawn_with(|orchestrator_channels, explorers_receiver| {
+        create_planet(
+            PLANET_ID,
+            planet_type,
+            ai,
+            orchestrator_channels,
+            explorers_receiver,
+        )
+    })
+}
+
+/// Same as [`spawn_planet_with_ai`], with the extension channel pair of
+/// [`create_planet_with_extension`].
+///
+/// # Panics
+///
+/// Panics if the planet can't be created or doesn't confirm the start.
+pub fn spawn_planet_with_extension(ai: PlanetAI) -> (TestContext, ExtensionClient) {
+    let mut extension = None;
+    let planet = spawn_with(|orchestrator_channels, explorers_receiver| {
+        let (planet, client) = create_planet_with_extension(
+            PLANET_ID,
+            common_planet::PlanetType::C,
+            ai,
+            orchestrator_channels,
+            explorers_receiver,
+        )?;
+        extension = Some(client);
+        Ok(planet)
+    });
+    let extension = extension.expect("the planet was created");
+    (planet, extension)
+}
+
+/// Sends an extension request and returns the response.
+///
+/// The planet serves the extension channel when it handles a message, so an
+/// `InternalStateRequest` is sent along; its answer is discarded.
+///
+/// # Panics
+///
+/// Panics if the planet doesn't answer within the timeout.
+pub fn extension_request(
+    planet: &TestContext,
+    extension: &ExtensionClient,
+    request: ExtensionRequest,
+) -> ExtensionResponse {
+    if let Err(err) = extension.send(request) {
+        panic!("{:?} not sent: {}", request, err);
+    }
+    let _ = planet
+        .snd_orc_to_planet
+        .send(OrchestratorToPlanet::InternalStateRequest);
+    planet.wait_orchestrator_msg("InternalStateResponse", |msg| match msg {
+        PlanetToOrchestrator::InternalStateResponse { .. } => Some(()),
+        _ => None,
+    });
+    match extension.recv_timeout(planet.timeout) {
+        Some(response) => response,
+        None => panic!("no response to {:?}", request),
+    }
+}
+
+/// Spawns a [`MockAI`] planet providing every basic resource.
+///
+/// AirFryer only generates Carbon: this planet provides the other basic
+/// resources needed by the combinations.
+///
+/// # Panics
+///
+/// Panics if the planet can't be created or doesn't confirm the start.
+pub fn spawn_resource_planet() -> TestContext {
+    spawn_mock_planet(common_planet::PlanetType::B, MockConfig::default())
+}
+
+/// Spawns a [`MockAI`] planet whose rules are the ones of its config.
+///
+/// # Panics
+///
+/// Panics if the config doesn't fit `planet_type`, or if the planet doesn't
+/// confirm the start.
+pub fn spawn_mock_planet(
+    planet_type: common_planet::PlanetType,
+    config: MockConfig,
+) -> TestContext {
+    spawn_with(|orchestrator_channels, explorers_receiver| {
+        common_planet::Planet::new(
+            MOCK_PLANET_ID,
+            planet_type,
+            Box::new(MockAI::with_config(config.clone())),
+            config.resources,
+            config.recipes,
+            orchestrator_channels,
+            explorers_receiver,
+        )
+    })
+}
+
+type OrchestratorChannels = (Receiver<OrchestratorToPlanet>, Sender<PlanetToOrchestrator>);
+
+fn spawn_with(
+    new_planet: impl FnOnce(
+        OrchestratorChannels,
+        Receiver<ExplorerToPlanet>,
+    ) -> Result<common_planet::Planet, String>,
+) -> TestContext {
+    let (snd_exp_to_planet, rcv_exp_to_planet) = unbounded::<ExplorerToPlanet>();
+    let (snd_planet_to_exp, rcv_planet_to_exp) = unbounded::<PlanetToExplorer>();
+    let (snd_planet_to_orc, rcv_planet_to_orc) = unbounded::<PlanetToOrchestrator>();
+    let (snd_orc_to_planet, rcv_orc_to_planet) = unbounded::<OrchestratorToPlanet>();
+
+    let mut planet = match new_planet((rcv_orc_to_planet, snd_planet_to_orc), rcv_exp_to_planet) {
+        Ok(planet) => planet,
+        Err(err) => panic!("Error while creating the planet: \n {}", err),
+    };
+    thread::spawn(move || {
+        let _ = planet.run();
+    });
+
+    let planet = TestContext {
+        snd_orc_to_planet,
+        snd_exp_to_planet,
+        snd_planet_to_exp,
+        rcv_planet_to_exp,
+        rcv_planet_to_orc,
+        timeout: DEFAULT_TIMEOUT,
+    };
+    let _ = planet
+        .snd_orc_to_planet
+        .send(OrchestratorToPlanet::StartPlanetAI);
+    planet.wait_orchestrator_msg("StartPlanetAIResult", |msg| match msg {
+        PlanetToOrchestrator::StartPlanetAIResult { .. } => Some(()),
+        _ => None,
+    });
+    planet
+}
+
+/// Registers an explorer with a planet so it can send/receive messages.
+///
+/// Orchestrator messages received before the `IncomingExplorerResponse` are discarded.
+///
+/// # Panics
+///
+/// Panics if the planet doesn't answer within the timeout.
+pub fn register_explorer_with_planet(planet: &TestContext, explorer_id: ID) {
+    let _ = planet
+        .snd_orc_to_planet
+        .send(OrchestratorToPlanet::IncomingExplorerRequest {
+            explorer_id,
+            new_sender: planet.snd_planet_to_exp.clone(),
+        });
+    planet.wait_orchestrator_msg("IncomingExplorerResponse", |msg| match msg {
+        PlanetToOrchestrator::IncomingExplorerResponse { .. } => Some(()),
+        _ => None,
+    });
+}
+
+/// Charges a planet with `count` sunrays and waits for every `SunrayAck`.
+///
+/// Orchestrator messages received before the last ack are discarded.
+///
+/// # Panics
+///
+/// Panics if the acks don't arrive within the timeout.
+pub fn charge_planet_with_sunrays(planet: &TestContext, count: usize) {
+    for _ in 0..count {
+        let _ = planet
+            .snd_orc_to_planet
+            .send(OrchestratorToPlanet::Sunray(forge().generate_sunray()));
+    }
+    for _ in 0..count {
+        planet.wait_orchestrator_msg("SunrayAck", |msg| match msg {
+            PlanetToOrchestrator::SunrayAck { .. } => Some(()),
+            _ => None,
+        });
+    }
+}
+
+/// Sends an asteroid to a planet and tells whether it was deflected.
+///
+/// Orchestrator messages received before the `AsteroidAck` are discarded.
+///
+/// # Panics
+///
+/// Panics if the ack doesn't arrive within the timeout.
+pub fn send_asteroid(planet: &TestContext) -> bool {
+    let _ = planet
+        .snd_orc_to_planet
+        .send(OrchestratorToPlanet::Asteroid(forge().generate_asteroid()));
+    planet.wait_orchestrator_msg("AsteroidAck", |msg| match msg {
+        PlanetToOrchestrator::AsteroidAck { rocket, .. } => Some(rocket.is_some()),
+        _ => None,
+    })
+}
+
+/// Refreshes the report of a planet with an `InternalStateRequest` and returns it.
+///
+/// Orchestrator messages received before the `InternalStateResponse` are discarded.
+///
+/// # Panics
+///
+/// Panics if the planet doesn't answer within the timeout.
+pub fn report(planet: &TestContext, handle: &ReportHandle) -> PlanetReport {
+    let _ = planet
+        .snd_orc_to_planet
+        .send(OrchestratorToPlanet::InternalStateRequest);
+    planet.wait_orchestrator_msg("InternalStateResponse", |msg| match msg {
+        PlanetToOrchestrator::InternalStateResponse { .. } => Some(()),
+        _ => None,
+    });
+    handle.snapshot()
+}
+
+/// Requests a basic resource from a planet and returns the response.
+///
+/// # Panics
+///
+/// Panics if the planet doesn't answer with a `GenerateResourceResponse`
+/// within the timeout.
+pub fn get_basic_resource(
+    planet: &TestContext,
+    explorer_id: ID,
+    resource_type: BasicResourceType,
+) -> Option<BasicResource> {
+    let _ = planet
+        .snd_exp_to_planet
+        .send(ExplorerToPlanet::GenerateResourceRequest {
+            explorer_id,
+            resource: resource_type,
+        });
+
+    match planet.recv_explorer_msg() {
+        PlanetToExplorer::GenerateResourceResponse { resource } => resource,
+        _ => panic!("Unexpected response type for GenerateResourceRequest"),
+    }
+}
+
+/// Requests a complex resource from a planet and returns the response.
+///
+/// The error type is a tuple: (error_message, left_resource, right_resource).
+///
+/// # Panics
+///
+/// Panics if the planet doesn't answer with a `CombineResourceResponse`
+/// within the timeout.
+pub fn combine_resources(
+    planet: &TestContext,
+    explorer_id: ID,
+    request: ComplexResourceRequest,
+) -> Result<ComplexResource, (String, GenericResource, GenericResource)> {
+    let _ = planet
+        .snd_exp_to_planet
+        .send(ExplorerToPlanet::CombineResourceRequest {
+            explorer_id,
+            msg: request,
+        });
+
+    match planet.recv_explorer_msg() {
+        PlanetToExplorer::CombineResourceResponse { complex_response } => complex_response,
+        _ => panic!("Unexpected response type for CombineResourceRequest"),
+    }
+}
+
+/// Helper to extract Carbon from BasicResource enum
+pub fn extract_carbon(resource: Option<BasicResource>) -> Option<Carbon> {
+    match resource {
+        Some(BasicResource::Carbon(c)) => Some(c),
+        _ => None,
+    }
+}
+
+/// Helper to extract Oxygen from BasicResource enum
+pub fn extract_oxygen(resource: Option<BasicResource>) -> Option<Oxygen> {
+    match resource {
+        Some(BasicResource::Oxygen(o)) => Some(o),
+        _ => None,
+    }
+}
+
+/// Helper to extract Hydrogen from BasicResource enum
+pub fn extract_hydrogen(resource: Option<BasicResource>) -> Option<Hydrogen> {
+    match resource {
+        Some(BasicResource::Hydrogen(h)) => Some(h),
+        _ => None,
+    }
+}
+
+/// Helper to extract Silicon from BasicResource enum
+pub fn extract_silicon(resource: Option<BasicResource>) -> Option<Silicon> {
+    match resource {
+        Some(BasicResource::Silicon(s)) => Some(s),
+        _ => None,
+    }
+}
+
+/// Helper to extract Water from ComplexResource enum
+pub fn extract_water(resource: ComplexResource) -> Option<Water> {
+    match resource {
+        ComplexResource::Water(w) => Some(w),
+        _ => None,
+    }
+}
+
+/// Helper to extract Life from ComplexResource enum
+pub fn extract_life(resource: ComplexResource) -> Option<Life> {
+    match resource {
+        ComplexResource::Life(l) => Some(l),
+        _ => None,
+    }
+}
+
+/// Helper to extract Dolphin from ComplexResource enum
+pub fn extract_dolphin(resource: ComplexResource) -> Option<Dolphin> {
+    match resource {
+        ComplexResource::Dolphin(d) => Some(d),
+        _ => None,
+    }
+}
+
+/// Helper to extract Robot from ComplexResource enum
+pub fn extract_robot(resource: ComplexResource) -> Option<Robot> {
+    match resource {
+        ComplexResource::Robot(r) => Some(r),
+        _ => None,
+    }
+}
+
+/// Helper to extract Diamond from ComplexResource enum
+pub fn extract_diamond(resource: ComplexResource) -> Option<Diamond> {
+    match resource {
+        ComplexResource::Diamond(d) => Some(d),
+        _ => None,
+    }
+}
+
+/// Helper to extract AIPartner from ComplexResource enum
+pub fn extract_aipartner(resource: ComplexResource) -> Option<AIPartner> {
+    match resource {
+        ComplexResource::AIPartner(a) => Some(a),
+        _ => None,
+    }
+}
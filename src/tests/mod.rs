@@ -1,2 +1 @@
-pub mod mock_planet;
 pub mod test;
@@ -1,144 +1,31 @@
 use common_game::{
     components::{
-        forge::Forge,
         planet as common_planet,
         resource::{
-            self, BasicResource, BasicResourceType, Carbon, ComplexResource,
-            ComplexResourceRequest, ComplexResourceType, GenericResource,
+            BasicResource, BasicResourceType, ComplexResourceRequest, ComplexResourceType,
+            GenericResource,
         },
     },
     protocols::orchestrator_planet::*,
     protocols::planet_explorer::*,
 };
 
-use crossbeam_channel::{Receiver, RecvError, Sender, unbounded};
-use lazy_static::lazy_static;
+use crossbeam_channel::RecvError;
 
 use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
-use std::{
-    thread::{self, sleep},
-    time::Duration,
-};
+use std::{thread::sleep, time::Duration};
 
-use super::mock_planet;
+use crate::PlanetError;
+use crate::mock_planet;
 use crate::planet;
+use crate::testing::*;
+
 // =========================================================================
-// GLOBAL STATIC, STRUCT & FUNCTIONS (to create planets) FOR TEST OPERATIONS
+// FUNCTIONS (to create planets) FOR TEST OPERATIONS
 // =========================================================================
-
-// Forge enforces a single global instance (see `forge::Forge`), so the tests
-// share one lazily-initialized Forge
-lazy_static! {
-    static ref GENERATOR: Forge = Forge::new().expect("Failed to create Forge");
-}
-
-pub struct TestContext {
-    pub snd_orc_to_planet: Sender<OrchestratorToPlanet>,
-    pub snd_exp_to_planet: Sender<ExplorerToPlanet>,
-    pub snd_planet_to_exp: Sender<PlanetToExplorer>,
-    pub rcv_planet_to_exp: Receiver<PlanetToExplorer>,
-    pub rcv_planet_to_orc: Receiver<PlanetToOrchestrator>,
-}
-
-fn spawn_planet() -> TestContext {
-    let ia = planet::PlanetAI::new();
-
-    let gene: Vec<BasicResourceType> = vec![BasicResourceType::Carbon];
-
-    let compl: Vec<ComplexResourceType> = vec![
-        ComplexResourceType::Water,
-        ComplexResourceType::Life,
-        ComplexResourceType::Dolphin,
-        ComplexResourceType::Robot,
-        ComplexResourceType::AIPartner,
-        ComplexResourceType::Diamond,
-    ];
-
-    let (sdr_expl_to_planet, rcv_expl_to_planet) = unbounded::<ExplorerToPlanet>();
-    let (sdr_planet_to_expl, rcv_planet_to_expl) = unbounded::<PlanetToExplorer>();
-    let (sdr_planet_to_orc, rcv_planet_to_orc) = unbounded::<PlanetToOrchestrator>();
-    let (sdr_orc_to_planet, rcv_orc_to_planet) = unbounded::<OrchestratorToPlanet>();
-
-    let planet = common_planet::Planet::new(
-        0,
-        common_planet::PlanetType::C,
-        Box::new(ia),
-        gene,
-        compl,
-        (rcv_orc_to_planet, sdr_planet_to_orc),
-        rcv_expl_to_planet,
-    );
-    let _ = sdr_orc_to_planet.send(OrchestratorToPlanet::StartPlanetAI);
-    let _t1 = thread::spawn(move || {
-        let _ = planet.unwrap().run();
-    });
-    sleep(Duration::from_millis(10));
-
-    // StartPlanetAIResponse message consumed from the queue
-    let _ = rcv_planet_to_orc.recv();
-
-    TestContext {
-        snd_orc_to_planet: sdr_orc_to_planet,
-        snd_exp_to_planet: sdr_expl_to_planet,
-        snd_planet_to_exp: sdr_planet_to_expl,
-        rcv_planet_to_orc, // NOTE: for tests we don't need this channel
-        rcv_planet_to_exp: rcv_planet_to_expl,
-    }
-}
-
-/// This method spawns the MockPlanet which provides all the possible
-/// kind of basic resources. This is required because [air_frier] planet
-/// can only generate 'Carbon' and in order to test the `CombineResourceRequest`
-/// we need to have also the others `BasicResouce`s
-fn spawn_resource_planet() -> TestContext {
-    let ia = mock_planet::MockAI::new();
-
-    // This planet generates everything except Carbon
-    let gen_rules: Vec<BasicResourceType> = vec![
-        BasicResourceType::Oxygen,
-        BasicResourceType::Hydrogen,
-        BasicResourceType::Silicon,
-        BasicResourceType::Carbon,
-    ];
-
-    let comb_rules: Vec<ComplexResourceType> = vec![];
-
-    let (sdr_expl_to_planet, rcv_expl_to_planet) = unbounded::<ExplorerToPlanet>();
-    let (sdr_planet_to_expl, rcv_planet_to_expl) = unbounded::<PlanetToExplorer>();
-    let (sdr_planet_to_orc, rcv_planet_to_orc) = unbounded::<PlanetToOrchestrator>();
-    let (sdr_orc_to_planet, rcv_orc_to_planet) = unbounded::<OrchestratorToPlanet>();
-
-    let new_planet = common_planet::Planet::new(
-        1,
-        common_planet::PlanetType::B,
-        Box::new(ia),
-        gen_rules,
-        comb_rules,
-        (rcv_orc_to_planet, sdr_planet_to_orc),
-        rcv_expl_to_planet,
-    );
-
-    let _ = sdr_orc_to_planet.send(OrchestratorToPlanet::StartPlanetAI);
-
-    match new_planet {
-        Ok(mut planet) => {
-            let _t1 = thread::spawn(move || {
-                let _ = planet.run();
-            });
-        }
-        Err(err) => panic!("Error while creating the planet: \n {}", err),
-    }
-
-    sleep(Duration::from_millis(10));
-
-    TestContext {
-        snd_orc_to_planet: sdr_orc_to_planet,
-        snd_exp_to_planet: sdr_expl_to_planet,
-        snd_planet_to_exp: sdr_planet_to_expl,
-        rcv_planet_to_orc, // NOTE: for tests we don't need this channel
-        rcv_planet_to_exp: rcv_planet_to_expl,
-    }
-}
+//
+// The spawn helpers, the shared Forge and the helpers for the common test
+// operations live in `crate::testing`, so other crates can use them too
 
 /// Used to have 2 planets:
 /// 1. [air_frier] => our planet
@@ -150,168 +37,6 @@ fn spawn_dual_planets() -> (TestContext, TestContext) {
     (main_planet, resource_planet)
 }
 
-// ===========================================
-// HELPER FUNCTIONS FOR COMMON TEST OPERATIONS
-// ===========================================
-//
-// TODO: it would be ideal to use the common test operation inside
-// all the test methods
-
-/// Registers an explorer with a planet so it can send/receive messages
-fn register_explorer_with_planet(planet: &TestContext, explorer_id: u32) {
-    let _ = planet
-        .snd_orc_to_planet
-        .send(OrchestratorToPlanet::IncomingExplorerRequest {
-            explorer_id,
-            new_sender: planet.snd_planet_to_exp.clone(),
-        });
-
-    // IncomingExplorerResponse message consumed from the queue
-    let _ = planet.rcv_planet_to_orc.recv();
-}
-
-/// Charges a planet with N sunrays
-/// NOTE: the [air_frier::PlanetAI] of [air_frier] waits to build the rocket until an asteroid is coming
-fn charge_planet_with_sunrays(planet: &TestContext, count: usize) {
-    for _ in 0..count {
-        let _ = planet
-            .snd_orc_to_planet
-            .send(OrchestratorToPlanet::Sunray(GENERATOR.generate_sunray()));
-    }
-    sleep(Duration::from_millis(50));
-}
-
-/// Requests a basic resource from a planet and returns the response
-fn get_basic_resource(
-    planet: &TestContext,
-    explorer_id: u32,
-    resource_type: BasicResourceType,
-) -> Option<BasicResource> {
-    let _ = planet
-        .snd_exp_to_planet
-        .send(ExplorerToPlanet::GenerateResourceRequest {
-            explorer_id,
-            resource: resource_type,
-        });
-
-    let response = planet.rcv_planet_to_exp.recv().unwrap();
-    match response {
-        PlanetToExplorer::GenerateResourceResponse { resource } => resource,
-        _ => None,
-    }
-}
-
-/// Requests a complex resource from a planet and returns the response
-/// The error type is a tuple: (error_message, left_resource, right_resource)
-fn combine_resources(
-    planet: &TestContext,
-    explorer_id: u32,
-    request: ComplexResourceRequest,
-) -> Result<ComplexResource, (String, GenericResource, GenericResource)> {
-    let _ = planet
-        .snd_exp_to_planet
-        .send(ExplorerToPlanet::CombineResourceRequest {
-            explorer_id,
-            msg: request,
-        });
-
-    let response = planet.rcv_planet_to_exp.recv().unwrap();
-    match response {
-        PlanetToExplorer::CombineResourceResponse { complex_response } => complex_response,
-        _ => panic!("Unexpected response type for CombineResourceRequest"),
-    }
-}
-
-/// Helper to extract Carbon from BasicResource enum
-#[allow(dead_code)]
-fn extract_carbon(resource: Option<BasicResource>) -> Option<Carbon> {
-    match resource {
-        Some(BasicResource::Carbon(c)) => Some(c),
-        _ => None,
-    }
-}
-
-/// Helper to extract Oxygen from BasicResource enum
-#[allow(dead_code)]
-fn extract_oxygen(resource: Option<BasicResource>) -> Option<resource::Oxygen> {
-    match resource {
-        Some(BasicResource::Oxygen(o)) => Some(o),
-        _ => None,
-    }
-}
-
-/// Helper to extract Hydrogen from BasicResource enum
-#[allow(dead_code)]
-fn extract_hydrogen(resource: Option<BasicResource>) -> Option<resource::Hydrogen> {
-    match resource {
-        Some(BasicResource::Hydrogen(h)) => Some(h),
-        _ => None,
-    }
-}
-
-/// Helper to extract Silicon from BasicResource enum
-#[allow(dead_code)]
-fn extract_silicon(resource: Option<BasicResource>) -> Option<resource::Silicon> {
-    match resource {
-        Some(BasicResource::Silicon(s)) => Some(s),
-        _ => None,
-    }
-}
-
-/// Helper to extract Water from ComplexResource enum
-#[allow(dead_code)]
-fn extract_water(resource: ComplexResource) -> Option<resource::Water> {
-    match resource {
-        ComplexResource::Water(w) => Some(w),
-        _ => None,
-    }
-}
-
-/// Helper to extract Life from ComplexResource enum
-#[allow(dead_code)]
-fn extract_life(resource: ComplexResource) -> Option<resource::Life> {
-    match resource {
-        ComplexResource::Life(l) => Some(l),
-        _ => None,
-    }
-}
-
-/// Helper to extract Dolphin from ComplexResource enum
-#[allow(dead_code)]
-fn extract_dolphin(resource: ComplexResource) -> Option<resource::Dolphin> {
-    match resource {
-        ComplexResource::Dolphin(d) => Some(d),
-        _ => None,
-    }
-}
-
-/// Helper to extract Robot from ComplexResource enum
-#[allow(dead_code)]
-fn extract_robot(resource: ComplexResource) -> Option<resource::Robot> {
-    match resource {
-        ComplexResource::Robot(r) => Some(r),
-        _ => None,
-    }
-}
-
-/// Helper to extract Diamond from ComplexResource enum
-#[allow(dead_code)]
-fn extract_diamond(resource: ComplexResource) -> Option<resource::Diamond> {
-    match resource {
-        ComplexResource::Diamond(d) => Some(d),
-        _ => None,
-    }
-}
-
-/// Helper to extract AIPartner from ComplexResource enum
-#[allow(dead_code)]
-fn extract_aipartner(resource: ComplexResource) -> Option<resource::AIPartner> {
-    match resource {
-        ComplexResource::AIPartner(a) => Some(a),
-        _ => None,
-    }
-}
-
 // ===========================================
 // START OF TESTING
 // ===========================================
@@ -325,9 +50,7 @@ mod asteroid_tests {
         let planet = spawn_planet();
         let _ = planet
             .snd_orc_to_planet
-            .send(OrchestratorToPlanet::Asteroid(
-                GENERATOR.generate_asteroid(),
-            ));
+            .send(OrchestratorToPlanet::Asteroid(forge().generate_asteroid()));
         let res = planet.rcv_planet_to_orc.recv();
         match res {
             Ok(msg) => match msg {
@@ -352,12 +75,10 @@ mod asteroid_tests {
         let planet = spawn_planet();
         let _ = planet
             .snd_orc_to_planet
-            .send(OrchestratorToPlanet::Sunray(GENERATOR.generate_sunray()));
+            .send(OrchestratorToPlanet::Sunray(forge().generate_sunray()));
         let _ = planet
             .snd_orc_to_planet
-            .send(OrchestratorToPlanet::Asteroid(
-                GENERATOR.generate_asteroid(),
-            ));
+            .send(OrchestratorToPlanet::Asteroid(forge().generate_asteroid()));
         let res_sunray = planet.rcv_planet_to_orc.recv(); //Reading the response to the sunray
         match res_sunray {
             Ok(PlanetToOrchestrator::SunrayAck { .. }) => {
@@ -466,7 +187,7 @@ mod asteroid_tests {
             });
         let _ = planet
             .snd_orc_to_planet
-            .send(OrchestratorToPlanet::Sunray(GENERATOR.generate_sunray()));
+            .send(OrchestratorToPlanet::Sunray(forge().generate_sunray()));
         sleep(Duration::from_millis(100));
         let _ = planet
             .snd_exp_to_planet
@@ -610,13 +331,11 @@ mod secret_warning {
         // Sunray sent -> Energy cell recharged
         let _ = planet
             .snd_orc_to_planet
-            .send(OrchestratorToPlanet::Sunray(GENERATOR.generate_sunray()));
+            .send(OrchestratorToPlanet::Sunray(forge().generate_sunray()));
         // Send asteroid -> Build rocket -> Planet can defend itself -> no warning sent
         let _ = planet
             .snd_orc_to_planet
-            .send(OrchestratorToPlanet::Asteroid(
-                GENERATOR.generate_asteroid(),
-            ));
+            .send(OrchestratorToPlanet::Asteroid(forge().generate_asteroid()));
 
         let _ = planet
             .snd_exp_to_planet
@@ -667,9 +386,7 @@ mod secret_warning {
         // Send Asteroid
         let _ = planet
             .snd_orc_to_planet
-            .send(OrchestratorToPlanet::Asteroid(
-                GENERATOR.generate_asteroid(),
-            ));
+            .send(OrchestratorToPlanet::Asteroid(forge().generate_asteroid()));
         sleep(Duration::from_secs(1));
 
         // Explorer requests CombinationRules normally
@@ -778,6 +495,7 @@ mod explorer_lifecycle {
 
 mod planet_ai_state {
     use super::*;
+    use crate::PlanetConfig;
     use common_game::protocols::orchestrator_planet::{OrchestratorToPlanet, PlanetToOrchestrator};
 
     #[test]
@@ -802,6 +520,17 @@ mod planet_ai_state {
         }
     }
 
+    #[test]
+    fn internal_state_request_publishes_report() {
+        let (planet, handle) = spawn_with_config(PlanetConfig::default());
+
+        register_explorer_with_planet(&planet, 3);
+        let snapshot = report(&planet, &handle);
+        assert!(snapshot.started, "the planet AI should be running");
+        assert_eq!(snapshot.explorers, vec![3]);
+        assert!(!snapshot.pending_warning, "no asteroid was sent");
+    }
+
     #[test]
     fn multiple_start_ai_messages_are_ignored() {
         let planet = spawn_planet();
@@ -942,7 +671,8 @@ mod complex_resource_combination {
         /// Diamond = Carbon + Carbon
         /// Fail: The only possible error we can get when creating a combined resource
         /// is the planet not having the required energy cell (we can't have the
-        /// "recipe error", because our planet implements all the combination requests).
+        /// "recipe error", because our planet implements all the combination requests),
+        /// reported as [PlanetError::NoEnergy].
         /// NOTE: we only implemented this failure test because `make_complex_resource`
         /// is macro-generated, so the behavior is identical for all resource
         /// combination functions
@@ -975,7 +705,9 @@ mod complex_resource_combination {
 
                 match result {
                     Ok(_diamond) => panic!("Diamond created successfully... impossible!"),
-                    Err((str, _, _)) => assert_eq!("EnergyCell not charged!", str),
+                    Err((str, _, _)) => {
+                        assert_eq!(PlanetError::parse(&str), Some(PlanetError::NoEnergy))
+                    }
                 }
             } else {
                 panic!("Failed to extract Carbon resources");
@@ -1386,3 +1118,2822 @@ mod complex_resource_combination {
         }
     }
 }
+
+mod galaxy_simulation {
+    use super::*;
+    use crate::{Galaxy, GalaxyConfig, PlanetKind};
+
+    fn peaceful_galaxy(seed: u64) -> GalaxyConfig {
+        GalaxyConfig {
+            seed,
+            planets: vec![
+                PlanetKind::AirFryer,
+                PlanetKind::Provider(vec![BasicResourceType::Hydrogen, BasicResourceType::Oxygen]),
+                PlanetKind::Provider(vec![BasicResourceType::Silicon]),
+            ],
+            explorers: 2,
+            ticks: 80,
+            asteroid_probability: 0.0,
+            ..GalaxyConfig::default()
+        }
+    }
+
+    #[test]
+    fn air_fryer_serves_the_galaxy() {
+        let report = Galaxy::new(peaceful_galaxy(7)).run(forge()).unwrap();
+
+        assert_eq!(report.explorers_lost, 0);
+        assert!(report.planets.iter().all(|p| p.destroyed_at.is_none()));
+
+        let air_fryer = report.air_fryers().next().unwrap();
+        assert!(air_fryer.visits > 0, "no explorer visited AirFryer");
+        assert!(
+            air_fryer.resources_served + air_fryer.combinations_served > 0,
+            "AirFryer served nothing:\n{}",
+            report
+        );
+    }
+
+    #[test]
+    fn same_seed_same_report() {
+        let first = Galaxy::new(peaceful_galaxy(42)).run(forge()).unwrap();
+        let second = Galaxy::new(peaceful_galaxy(42)).run(forge()).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn planets_without_rockets_are_destroyed() {
+        let config = GalaxyConfig {
+            sunray_probability: 0.0,
+            asteroid_probability: 1.0,
+            asteroid_grace_ticks: 0,
+            ticks: 5,
+            ..peaceful_galaxy(1)
+        };
+        let report = Galaxy::new(config).run(forge()).unwrap();
+
+        assert!(report.planets.iter().all(|p| p.destroyed_at == Some(0)));
+        assert_eq!(report.explorers_lost, 2);
+    }
+}
+
+mod explorer_reputation {
+    use super::*;
+    use crate::{PlanetConfig, ReputationConfig};
+    use common_planet::PlanetType;
+
+    fn available_cells(planet: &TestContext, explorer_id: u32) -> u32 {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id });
+        match planet.rcv_planet_to_exp.recv() {
+            Ok(PlanetToExplorer::AvailableEnergyCellResponse { available_cells }) => {
+                available_cells
+            }
+            _ => panic!("Wrong message received"),
+        }
+    }
+
+    /// An explorer that keeps asking for a recipe the planet doesn't offer
+    /// ends up on probation and gets throttled
+    #[test]
+    fn failed_combinations_lower_the_reputation() {
+        // Type B planets only combine Diamonds
+        let ai = planet::PlanetAI::new();
+        let handle = ai.report_handle();
+        let planet = spawn_planet_of_type(PlanetType::B, ai);
+        let resource_planet = spawn_resource_planet();
+        let explorer_id = 0;
+        register_explorer_with_planet(&planet, explorer_id);
+        register_explorer_with_planet(&resource_planet, explorer_id);
+
+        charge_planet_with_sunrays(&resource_planet, 1);
+        let hydrogen =
+            get_basic_resource(&resource_planet, explorer_id, BasicResourceType::Hydrogen);
+        charge_planet_with_sunrays(&resource_planet, 1);
+        let oxygen = get_basic_resource(&resource_planet, explorer_id, BasicResourceType::Oxygen);
+        let (Some(mut h), Some(mut o)) = (extract_hydrogen(hydrogen), extract_oxygen(oxygen))
+        else {
+            panic!("Failed to extract Hydrogen and Oxygen");
+        };
+        charge_planet_with_sunrays(&planet, 1);
+
+        // Every Water request fails and hands the inputs back
+        let mut last_error = String::new();
+        for _ in 0..5 {
+            let result =
+                combine_resources(&planet, explorer_id, ComplexResourceRequest::Water(h, o));
+            match result {
+                Ok(_) => panic!("Water created on a Diamond planet"),
+                Err((
+                    str,
+                    GenericResource::BasicResources(BasicResource::Hydrogen(left)),
+                    GenericResource::BasicResources(BasicResource::Oxygen(right)),
+                )) => {
+                    last_error = str;
+                    h = left;
+                    o = right;
+                }
+                Err(e) => panic!("Unexpected resources handed back: {:?}", e),
+            }
+        }
+        assert_eq!(
+            PlanetError::parse(&last_error),
+            Some(PlanetError::QuotaExceeded)
+        );
+        assert_eq!(available_cells(&planet, explorer_id), 1);
+
+        let reputations = report(&planet, &handle).reputations;
+        assert_eq!(reputations.len(), 1);
+        let reputation = &reputations[0];
+        assert_eq!(reputation.explorer_id, explorer_id);
+        assert_eq!(reputation.combinations_ok, 0);
+        assert!(reputation.combinations_failed >= 2);
+        assert!(reputation.throttled > 0);
+        assert_eq!(
+            reputation.combinations_failed + reputation.throttled,
+            5,
+            "every combination is either failed or throttled"
+        );
+        assert!(reputation.score < ReputationConfig::default().min_score);
+    }
+
+    /// Combinations the planet can't power are not the explorer's fault
+    #[test]
+    fn planet_side_failures_keep_the_reputation() {
+        let (planet, handle) = spawn_with_config(PlanetConfig::default());
+        let explorer_id = 0;
+        register_explorer_with_planet(&planet, explorer_id);
+
+        charge_planet_with_sunrays(&planet, 1);
+        let carbon_1 = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+        charge_planet_with_sunrays(&planet, 1);
+        let carbon_2 = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+        let (Some(mut c1), Some(mut c2)) = (extract_carbon(carbon_1), extract_carbon(carbon_2))
+        else {
+            panic!("Failed to extract Carbon resources");
+        };
+
+        for _ in 0..5 {
+            match combine_resources(
+                &planet,
+                explorer_id,
+                ComplexResourceRequest::Diamond(c1, c2),
+            ) {
+                Err((
+                    str,
+                    GenericResource::BasicResources(BasicResource::Carbon(left)),
+                    GenericResource::BasicResources(BasicResource::Carbon(right)),
+                )) => {
+                    assert_eq!(PlanetError::parse(&str), Some(PlanetError::NoEnergy));
+                    c1 = left;
+                    c2 = right;
+                }
+                other => panic!("Expected the Carbon back, got {:?}", other.map(|_| ())),
+            }
+        }
+
+        let reputation = report(&planet, &handle).reputations[0].clone();
+        assert_eq!(reputation.combinations_failed, 0);
+        assert_eq!(reputation.throttled, 0);
+        assert!(reputation.score >= ReputationConfig::default().min_score);
+    }
+
+    /// Requests over the rate are refused without touching the energy cell,
+    /// and don't count toward the rate themselves
+    #[test]
+    fn flooding_explorer_is_throttled() {
+        let config = PlanetConfig {
+            reputation: ReputationConfig {
+                window: Duration::from_millis(500),
+                max_requests: 2,
+                ..ReputationConfig::default()
+            },
+            ..PlanetConfig::default()
+        };
+        let (planet, handle) = spawn_with_config(config);
+        let explorer_id = 0;
+        register_explorer_with_planet(&planet, explorer_id);
+
+        // Polling the recipes, as allies do to read the warning, is never throttled
+        for _ in 0..5 {
+            let _ = planet
+                .snd_exp_to_planet
+                .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id });
+            let _ = planet.recv_explorer_msg();
+        }
+        for _ in 0..2 {
+            charge_planet_with_sunrays(&planet, 1);
+            let carbon = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+            assert!(carbon.is_some());
+        }
+
+        charge_planet_with_sunrays(&planet, 1);
+        for _ in 0..3 {
+            let carbon = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+            assert!(carbon.is_none(), "the third request should be throttled");
+            assert_eq!(
+                available_cells(&planet, explorer_id),
+                1,
+                "the throttled request used energy"
+            );
+            sleep(Duration::from_millis(150));
+        }
+
+        // The served requests left the window, the refused retries never entered it
+        sleep(Duration::from_millis(150));
+        let carbon = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+        assert!(carbon.is_some(), "retrying keeps the explorer throttled");
+
+        let reputation = report(&planet, &handle).reputations[0].clone();
+        assert_eq!(reputation.throttled, 3);
+        assert_eq!(reputation.requests, 5 + 2 + 3 * 2 + 1);
+    }
+}
+
+mod carbon_credits {
+    use super::*;
+    use crate::credits::CreditLedger;
+    use crate::{CreditConfig, PlanetConfig};
+
+    #[test]
+    fn totals_saturate_at_the_bounds() {
+        let mut ledger = CreditLedger::new(CreditConfig {
+            initial_credits: u32::MAX,
+            per_combination: u32::MAX,
+            carbon_price: u32::MAX,
+            ..CreditConfig::default()
+        });
+        ledger.combination(1);
+        ledger.combination(1);
+        assert!(ledger.can_buy_carbon(1));
+        ledger.carbon_sold(1);
+        // Sold without a check: neither total wraps around
+        ledger.carbon_sold(1);
+
+        let account = &ledger.snapshot()[0];
+        assert_eq!(account.earned, u32::MAX);
+        assert_eq!(account.spent, u32::MAX);
+        assert_eq!(account.balance, 0);
+        assert!(!ledger.can_buy_carbon(1));
+    }
+
+    fn spawn_planet_with_credits(credits: CreditConfig) -> (TestContext, crate::ReportHandle) {
+        spawn_with_config(PlanetConfig {
+            credits,
+            ..PlanetConfig::default()
+        })
+    }
+
+    fn available_cells(planet: &TestContext, explorer_id: u32) -> u32 {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id });
+        match planet.rcv_planet_to_exp.recv() {
+            Ok(PlanetToExplorer::AvailableEnergyCellResponse { available_cells }) => {
+                available_cells
+            }
+            _ => panic!("Wrong message received"),
+        }
+    }
+
+    #[test]
+    fn carbon_is_refused_once_credit_runs_out() {
+        let (planet, handle) = spawn_planet_with_credits(CreditConfig {
+            initial_credits: 2,
+            carbon_price: 2,
+            ..CreditConfig::default()
+        });
+        let explorer_id = 0;
+        register_explorer_with_planet(&planet, explorer_id);
+
+        charge_planet_with_sunrays(&planet, 1);
+        let carbon = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+        assert!(extract_carbon(carbon).is_some(), "the first Carbon is paid");
+
+        charge_planet_with_sunrays(&planet, 1);
+        let carbon = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+        assert!(carbon.is_none(), "the second Carbon can't be paid");
+        assert_eq!(
+            available_cells(&planet, explorer_id),
+            1,
+            "the refused request kept the energy"
+        );
+
+        let account = report(&planet, &handle).credits[0].clone();
+        assert_eq!(account.balance, 0);
+        assert_eq!(account.spent, 2);
+        assert_eq!(account.refused, 1);
+    }
+
+    #[test]
+    fn arriving_during_high_energy_earns_credits() {
+        let (planet, _report) = spawn_planet_with_credits(CreditConfig {
+            arrival_bonus: 3,
+            carbon_price: 3,
+            ..CreditConfig::default()
+        });
+        let explorer_id = 0;
+
+        // The only cell is charged before the explorer lands
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, explorer_id);
+
+        let carbon = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+        assert!(
+            extract_carbon(carbon).is_some(),
+            "the bonus pays the Carbon"
+        );
+    }
+
+    #[test]
+    fn arrival_bonus_is_paid_once_per_charged_period() {
+        let (planet, handle) = spawn_planet_with_credits(CreditConfig {
+            arrival_bonus: 3,
+            carbon_price: 3,
+            ..CreditConfig::default()
+        });
+        let explorer_id = 0;
+        let balance = || report(&planet, &handle).credits[0].balance;
+        let come_back = || {
+            let _ = planet
+                .snd_orc_to_planet
+                .send(OrchestratorToPlanet::OutgoingExplorerRequest { explorer_id });
+            let _ = planet.recv_orchestrator_msg();
+            register_explorer_with_planet(&planet, explorer_id);
+        };
+
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, explorer_id);
+        come_back();
+        come_back();
+        assert_eq!(balance(), 3, "leaving and coming back earns nothing");
+
+        let carbon = get_basic_resource(&planet, explorer_id, BasicResourceType::Carbon);
+        assert!(extract_carbon(carbon).is_some());
+        charge_planet_with_sunrays(&planet, 1);
+        come_back();
+        assert_eq!(balance(), 3, "the cell was charged again");
+    }
+}
+
+mod panic_isolation {
+    use super::*;
+    use crate::GuardedAI;
+    use common_game::components::{
+        planet::{DummyPlanetState, PlanetState},
+        resource::{Combinator, Generator},
+        rocket::Rocket,
+        sunray::Sunray,
+    };
+
+    /// AI with a bug in the asteroid handler and in Carbon generation
+    struct BuggyAI;
+
+    impl common_planet::PlanetAI for BuggyAI {
+        fn handle_sunray(
+            &mut self,
+            state: &mut PlanetState,
+            _generator: &Generator,
+            _combinator: &Combinator,
+            sunray: Sunray,
+        ) {
+            state.charge_cell(sunray);
+        }
+
+        fn handle_asteroid(
+            &mut self,
+            _state: &mut PlanetState,
+            _generator: &Generator,
+            _combinator: &Combinator,
+        ) -> Option<Rocket> {
+            panic!("asteroid handler bug")
+        }
+
+        fn handle_internal_state_req(
+            &mut self,
+            state: &mut PlanetState,
+            _generator: &Generator,
+            _combinator: &Combinator,
+        ) -> DummyPlanetState {
+            state.to_dummy()
+        }
+
+        fn handle_explorer_msg(
+            &mut self,
+            _state: &mut PlanetState,
+            _generator: &Generator,
+            _combinator: &Combinator,
+            msg: ExplorerToPlanet,
+        ) -> Option<PlanetToExplorer> {
+            match msg {
+                ExplorerToPlanet::GenerateResourceRequest { .. } => {
+                    panic!("generation bug")
+                }
+                _ => Some(PlanetToExplorer::AvailableEnergyCellResponse { available_cells: 0 }),
+            }
+        }
+    }
+
+    #[test]
+    fn panics_are_caught_and_counted() {
+        let ai = GuardedAI::new(BuggyAI);
+        let incidents = ai.incident_handle();
+        let planet = spawn_planet_with_ai(ai);
+        register_explorer_with_planet(&planet, 0);
+
+        // The asteroid handler panics: no rocket, but the planet is still alive
+        let _ = planet
+            .snd_orc_to_planet
+            .send(OrchestratorToPlanet::Asteroid(forge().generate_asteroid()));
+        match planet.rcv_planet_to_orc.recv() {
+            Ok(PlanetToOrchestrator::AsteroidAck { rocket, .. }) => assert!(rocket.is_none()),
+            _ => panic!("Expected AsteroidAck"),
+        }
+
+        // The explorer handler panics: no response at all
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::GenerateResourceRequest {
+                explorer_id: 0,
+                resource: BasicResourceType::Carbon,
+            });
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 0 });
+        match planet
+            .rcv_planet_to_exp
+            .recv_timeout(Duration::from_secs(1))
+        {
+            Ok(PlanetToExplorer::AvailableEnergyCellResponse { .. }) => {}
+            _ => panic!("The planet should answer only the second request"),
+        }
+
+        let _ = planet
+            .snd_orc_to_planet
+            .send(OrchestratorToPlanet::InternalStateRequest);
+        match planet.rcv_planet_to_orc.recv() {
+            Ok(PlanetToOrchestrator::InternalStateResponse { .. }) => {}
+            _ => panic!("The planet thread died"),
+        }
+
+        assert_eq!(incidents.count(), 2);
+        let recent = incidents.recent();
+        assert_eq!(recent[0].callback, "handle_asteroid");
+        assert_eq!(recent[0].panic, "asteroid handler bug");
+        assert_eq!(recent[1].callback, "handle_explorer_msg");
+        assert!(
+            recent[1]
+                .input
+                .as_deref()
+                .is_some_and(|input| input.contains("GenerateResourceRequest"))
+        );
+    }
+
+    #[test]
+    fn healthy_ai_has_no_incidents() {
+        let ai = GuardedAI::new(planet::PlanetAI::new());
+        let incidents = ai.incident_handle();
+        let planet = spawn_planet_with_ai(ai);
+        register_explorer_with_planet(&planet, 0);
+        charge_planet_with_sunrays(&planet, 1);
+        let carbon = get_basic_resource(&planet, 0, BasicResourceType::Carbon);
+
+        assert!(extract_carbon(carbon).is_some());
+        assert!(!incidents.is_degraded());
+    }
+}
+
+mod mock_planet_ai {
+    use super::*;
+    use mock_planet::MockConfig;
+
+    /// Type C planet generating Hydrogen and combining Water, defending itself
+    fn spawn_water_planet() -> TestContext {
+        spawn_mock_planet(
+            common_planet::PlanetType::C,
+            MockConfig {
+                resources: vec![BasicResourceType::Hydrogen],
+                recipes: vec![ComplexResourceType::Water],
+                defends: true,
+            },
+        )
+    }
+
+    #[test]
+    fn supported_lists_follow_the_config() {
+        let planet = spawn_water_planet();
+        register_explorer_with_planet(&planet, 0);
+
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedResourceRequest { explorer_id: 0 });
+        match planet.rcv_planet_to_exp.recv() {
+            Ok(PlanetToExplorer::SupportedResourceResponse { resource_list }) => {
+                assert_eq!(resource_list.len(), 1);
+                assert!(resource_list.contains(&BasicResourceType::Hydrogen));
+            }
+            _ => panic!("Expected SupportedResourceResponse"),
+        }
+
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id: 0 });
+        match planet.rcv_planet_to_exp.recv() {
+            Ok(PlanetToExplorer::SupportedCombinationResponse { combination_list }) => {
+                assert_eq!(combination_list.len(), 1);
+                assert!(combination_list.contains(&ComplexResourceType::Water));
+            }
+            _ => panic!("Expected SupportedCombinationResponse"),
+        }
+
+        // Unsupported resources are refused even with a charged cell
+        charge_planet_with_sunrays(&planet, 1);
+        assert!(get_basic_resource(&planet, 0, BasicResourceType::Oxygen).is_none());
+        assert!(
+            extract_hydrogen(get_basic_resource(&planet, 0, BasicResourceType::Hydrogen)).is_some()
+        );
+    }
+
+    #[test]
+    fn unsupported_recipe_returns_the_inputs() {
+        let resource_planet = spawn_resource_planet();
+        register_explorer_with_planet(&resource_planet, 0);
+
+        charge_planet_with_sunrays(&resource_planet, 1);
+        let oxygen = extract_oxygen(get_basic_resource(
+            &resource_planet,
+            0,
+            BasicResourceType::Oxygen,
+        ))
+        .unwrap();
+        charge_planet_with_sunrays(&resource_planet, 1);
+        let hydrogen = extract_hydrogen(get_basic_resource(
+            &resource_planet,
+            0,
+            BasicResourceType::Hydrogen,
+        ))
+        .unwrap();
+
+        // The default mock has no recipes
+        let result = combine_resources(
+            &resource_planet,
+            0,
+            ComplexResourceRequest::Water(hydrogen, oxygen),
+        );
+        match result {
+            Err((
+                _,
+                GenericResource::BasicResources(left),
+                GenericResource::BasicResources(right),
+            )) => {
+                assert!(matches!(left, BasicResource::Hydrogen(_)));
+                assert!(matches!(right, BasicResource::Oxygen(_)));
+            }
+            _ => panic!("Expected the inputs back"),
+        }
+    }
+
+    #[test]
+    fn available_energy_counts_charged_cells() {
+        let planet = spawn_water_planet();
+        register_explorer_with_planet(&planet, 0);
+
+        let available = |planet: &TestContext| {
+            let _ = planet
+                .snd_exp_to_planet
+                .send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 0 });
+            match planet.rcv_planet_to_exp.recv() {
+                Ok(PlanetToExplorer::AvailableEnergyCellResponse { available_cells }) => {
+                    available_cells
+                }
+                _ => panic!("Expected AvailableEnergyCellResponse"),
+            }
+        };
+
+        assert_eq!(available(&planet), 0);
+        charge_planet_with_sunrays(&planet, 1);
+        assert_eq!(available(&planet), 1);
+    }
+
+    #[test]
+    fn asteroid_handling_follows_the_config() {
+        // The default mock doesn't defend itself
+        let resource_planet = spawn_resource_planet();
+        charge_planet_with_sunrays(&resource_planet, 1);
+        assert!(!send_asteroid(&resource_planet));
+
+        // A defending mock builds the rocket from its charged cell
+        let planet = spawn_water_planet();
+        charge_planet_with_sunrays(&planet, 1);
+        assert!(send_asteroid(&planet));
+    }
+}
+
+mod planet_types {
+    use super::*;
+    use common_planet::PlanetType;
+
+    fn available_cells(planet: &TestContext) -> u32 {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 0 });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::AvailableEnergyCellResponse { available_cells } => available_cells,
+            _ => panic!("Expected AvailableEnergyCellResponse"),
+        }
+    }
+
+    fn supported_combinations(planet: &TestContext) -> usize {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id: 0 });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::SupportedCombinationResponse { combination_list } => {
+                combination_list.len()
+            }
+            _ => panic!("Expected SupportedCombinationResponse"),
+        }
+    }
+
+    fn spawn(planet_type: PlanetType) -> TestContext {
+        let planet = spawn_planet_of_type(planet_type, planet::PlanetAI::new());
+        register_explorer_with_planet(&planet, 0);
+        planet
+    }
+
+    /// 5 cells and a rocket: every cell is used, the rocket takes one of them
+    #[test]
+    fn type_a_uses_every_cell() {
+        let planet = spawn(PlanetType::A);
+        assert_eq!(supported_combinations(&planet), 0);
+
+        charge_planet_with_sunrays(&planet, 5);
+        assert_eq!(available_cells(&planet), 5);
+
+        // A sixth sunray turns a charged cell into a rocket and recharges it
+        charge_planet_with_sunrays(&planet, 1);
+        assert_eq!(available_cells(&planet), 5);
+        assert!(send_asteroid(&planet));
+
+        // Without a rocket, the next asteroid costs one more cell
+        assert!(send_asteroid(&planet));
+        assert_eq!(available_cells(&planet), 4);
+
+        for _ in 0..4 {
+            assert!(
+                extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon)).is_some()
+            );
+        }
+        assert_eq!(available_cells(&planet), 0);
+        assert!(get_basic_resource(&planet, 0, BasicResourceType::Carbon).is_none());
+        assert!(!send_asteroid(&planet));
+    }
+
+    /// 1 cell, no rocket and Diamond as the only recipe
+    #[test]
+    fn type_b_combines_diamonds_only() {
+        let planet = spawn(PlanetType::B);
+        assert_eq!(supported_combinations(&planet), 1);
+
+        charge_planet_with_sunrays(&planet, 1);
+        let first = extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon));
+        charge_planet_with_sunrays(&planet, 1);
+        let second = extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon));
+
+        charge_planet_with_sunrays(&planet, 1);
+        let diamond = combine_resources(
+            &planet,
+            0,
+            ComplexResourceRequest::Diamond(first.unwrap(), second.unwrap()),
+        );
+        assert!(extract_diamond(diamond.unwrap()).is_some());
+
+        // The cell is charged, but B planets can't build rockets
+        charge_planet_with_sunrays(&planet, 1);
+        assert!(!send_asteroid(&planet));
+        assert_eq!(available_cells(&planet), 1);
+    }
+
+    /// The AirFryer layout: 1 cell, a rocket and every recipe
+    #[test]
+    fn type_c_builds_rockets_from_its_only_cell() {
+        let planet = spawn(PlanetType::C);
+        assert_eq!(supported_combinations(&planet), 6);
+
+        charge_planet_with_sunrays(&planet, 2);
+        // The second sunray moved the first charge into the rocket
+        assert_eq!(available_cells(&planet), 1);
+        assert!(send_asteroid(&planet));
+        assert_eq!(available_cells(&planet), 1);
+    }
+
+    /// 5 cells, no rocket and no recipes
+    #[test]
+    fn type_d_serves_carbon_from_any_cell() {
+        let planet = spawn(PlanetType::D);
+        assert_eq!(supported_combinations(&planet), 0);
+
+        charge_planet_with_sunrays(&planet, 3);
+        assert_eq!(available_cells(&planet), 3);
+        assert!(!send_asteroid(&planet));
+        assert_eq!(available_cells(&planet), 3);
+
+        let first = extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon));
+        let second = extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon));
+        assert_eq!(available_cells(&planet), 1);
+
+        // No recipes: the Carbon is handed back
+        let diamond = combine_resources(
+            &planet,
+            0,
+            ComplexResourceRequest::Diamond(first.unwrap(), second.unwrap()),
+        );
+        assert!(diamond.is_err());
+    }
+}
+
+mod energy_ledger {
+    use super::*;
+    use crate::{EnergyConfig, EnergyConsumer, PlanetConfig};
+    use std::time::Instant;
+
+    fn spawn_planet_with_energy(energy: EnergyConfig) -> (TestContext, crate::ReportHandle) {
+        spawn_with_config(PlanetConfig {
+            energy,
+            ..PlanetConfig::default()
+        })
+    }
+
+    #[test]
+    fn every_cell_is_attributed() {
+        let (planet, handle) = spawn_planet_with_energy(EnergyConfig::default());
+        register_explorer_with_planet(&planet, 3);
+
+        // Carbon for explorer 3
+        charge_planet_with_sunrays(&planet, 1);
+        assert!(get_basic_resource(&planet, 3, BasicResourceType::Carbon).is_some());
+
+        // The second sunray moves the first charge into a rocket
+        charge_planet_with_sunrays(&planet, 2);
+        send_asteroid(&planet);
+
+        // The asteroid took the ready rocket, so the next asteroid builds one
+        send_asteroid(&planet);
+
+        // Recharge, store a charge in a new rocket, and then there's nowhere
+        // left to store the last sunray
+        charge_planet_with_sunrays(&planet, 3);
+
+        let energy = report(&planet, &handle).energy;
+        assert_eq!(energy.charged, 5);
+        assert_eq!(
+            energy.total(EnergyConsumer::Generation {
+                explorer_id: 3,
+                resource: BasicResourceType::Carbon,
+            }),
+            1
+        );
+        assert_eq!(energy.explorer_total(3), 1);
+        assert_eq!(energy.total(EnergyConsumer::Rocket), 3);
+        assert_eq!(energy.total(EnergyConsumer::WastedSunray), 1);
+        assert_eq!(energy.history.len(), 10);
+        assert_eq!(
+            energy
+                .recent(Duration::from_secs(60), Instant::now())
+                .count(),
+            10
+        );
+    }
+
+    #[test]
+    fn combinations_are_attributed_to_the_recipe() {
+        let (planet, handle) = spawn_planet_with_energy(EnergyConfig::default());
+        register_explorer_with_planet(&planet, 5);
+
+        let mut carbon = Vec::new();
+        for _ in 0..2 {
+            charge_planet_with_sunrays(&planet, 1);
+            carbon.push(
+                extract_carbon(get_basic_resource(&planet, 5, BasicResourceType::Carbon)).unwrap(),
+            );
+        }
+        charge_planet_with_sunrays(&planet, 1);
+        let (right, left) = (carbon.pop().unwrap(), carbon.pop().unwrap());
+        assert!(
+            combine_resources(&planet, 5, ComplexResourceRequest::Diamond(left, right)).is_ok()
+        );
+
+        let energy = report(&planet, &handle).energy;
+        assert_eq!(
+            energy.total(EnergyConsumer::Combination {
+                explorer_id: 5,
+                recipe: ComplexResourceType::Diamond,
+            }),
+            1
+        );
+        assert_eq!(energy.explorer_total(5), 3);
+        // The two Carbon generations are the biggest consumer
+        assert_eq!(energy.consumed[0].cells, 2);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let (planet, handle) = spawn_planet_with_energy(EnergyConfig { history: 2 });
+        charge_planet_with_sunrays(&planet, 4);
+
+        let energy = report(&planet, &handle).energy;
+        assert_eq!(energy.history.len(), 2);
+        // Totals are kept for the whole life of the planet
+        assert_eq!(energy.charged, 2);
+        assert_eq!(energy.total(EnergyConsumer::Rocket), 1);
+        assert_eq!(energy.total(EnergyConsumer::WastedSunray), 2);
+    }
+}
+
+mod planet_errors {
+    use super::*;
+    use common_planet::PlanetType;
+
+    #[test]
+    fn error_codes_round_trip() {
+        for error in PlanetError::ALL {
+            assert_eq!(PlanetError::parse(&error.to_string()), Some(error));
+        }
+        assert_eq!(PlanetError::parse("EnergyCell not charged!"), None);
+        assert_eq!(PlanetError::parse("air_fryer:"), None);
+        assert_eq!(PlanetError::parse("air_fryer:no_energy_left"), None);
+    }
+
+    #[test]
+    fn combinator_failures_keep_their_cause() {
+        assert_eq!(
+            planet::combinator_error("EnergyCell not charged!"),
+            PlanetError::NoEnergy
+        );
+        assert_eq!(
+            planet::combinator_error("missing recipe"),
+            PlanetError::UnsupportedResource
+        );
+        assert_eq!(
+            planet::combinator_error("inputs don't match"),
+            PlanetError::WrongInputs
+        );
+    }
+
+    /// Type B planets only combine Diamonds
+    #[test]
+    fn unknown_recipe_is_unsupported() {
+        let planet = spawn_planet_of_type(PlanetType::B, planet::PlanetAI::new());
+        let resource_planet = spawn_resource_planet();
+        register_explorer_with_planet(&planet, 0);
+        register_explorer_with_planet(&resource_planet, 0);
+
+        charge_planet_with_sunrays(&resource_planet, 1);
+        let hydrogen = extract_hydrogen(get_basic_resource(
+            &resource_planet,
+            0,
+            BasicResourceType::Hydrogen,
+        ));
+        charge_planet_with_sunrays(&resource_planet, 1);
+        let oxygen = extract_oxygen(get_basic_resource(
+            &resource_planet,
+            0,
+            BasicResourceType::Oxygen,
+        ));
+
+        charge_planet_with_sunrays(&planet, 1);
+        let result = combine_resources(
+            &planet,
+            0,
+            ComplexResourceRequest::Water(hydrogen.unwrap(), oxygen.unwrap()),
+        );
+        match result {
+            Err((str, GenericResource::BasicResources(BasicResource::Hydrogen(_)), _)) => {
+                assert_eq!(
+                    PlanetError::parse(&str),
+                    Some(PlanetError::UnsupportedResource)
+                )
+            }
+            _ => panic!("Expected the Hydrogen and Oxygen back"),
+        }
+    }
+
+    /// After a hit, the only charged cell waits for the rocket
+    #[test]
+    fn last_cell_is_reserved_for_defense() {
+        let planet = spawn_planet();
+        register_explorer_with_planet(&planet, 0);
+        charge_planet_with_sunrays(&planet, 1);
+        let c1 = extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon));
+        charge_planet_with_sunrays(&planet, 1);
+        let c2 = extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon));
+
+        assert!(!send_asteroid(&planet));
+        charge_planet_with_sunrays(&planet, 1);
+        assert!(get_basic_resource(&planet, 0, BasicResourceType::Carbon).is_none());
+        let result = combine_resources(
+            &planet,
+            0,
+            ComplexResourceRequest::Diamond(c1.unwrap(), c2.unwrap()),
+        );
+        match result {
+            Err((str, _, _)) => assert_eq!(
+                PlanetError::parse(&str),
+                Some(PlanetError::ReservedForDefense)
+            ),
+            Ok(_) => panic!("Diamond made with the rocket's energy"),
+        }
+
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 0 });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::AvailableEnergyCellResponse { available_cells } => {
+                assert_eq!(available_cells, 0)
+            }
+            other => panic!("expected AvailableEnergyCellResponse, got {:?}", other),
+        }
+    }
+
+    /// The AI forgets its explorers when it's stopped
+    #[test]
+    fn explorers_are_forgotten_after_a_restart() {
+        let planet = spawn_planet();
+        register_explorer_with_planet(&planet, 0);
+        charge_planet_with_sunrays(&planet, 1);
+        let c1 = extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon));
+        charge_planet_with_sunrays(&planet, 1);
+        let c2 = extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon));
+
+        let _ = planet
+            .snd_orc_to_planet
+            .send(OrchestratorToPlanet::StopPlanetAI);
+        let _ = planet.recv_orchestrator_msg();
+        let _ = planet
+            .snd_orc_to_planet
+            .send(OrchestratorToPlanet::StartPlanetAI);
+        let _ = planet.recv_orchestrator_msg();
+
+        charge_planet_with_sunrays(&planet, 1);
+        let result = combine_resources(
+            &planet,
+            0,
+            ComplexResourceRequest::Diamond(c1.unwrap(), c2.unwrap()),
+        );
+        match result {
+            Err((str, _, _)) => assert_eq!(
+                PlanetError::parse(&str),
+                Some(PlanetError::ExplorerNotRegistered)
+            ),
+            Ok(_) => panic!("Diamond created for a forgotten explorer"),
+        }
+
+        // Arriving again is enough to be served
+        register_explorer_with_planet(&planet, 0);
+        assert!(get_basic_resource(&planet, 0, BasicResourceType::Carbon).is_some());
+    }
+}
+
+mod sunray_overflow {
+    use super::*;
+    use crate::{EnergyConsumer, OverflowPolicy, PlanetConfig};
+    use common_planet::PlanetType;
+
+    fn spawn_with_policy(
+        planet_type: PlanetType,
+        overflow: OverflowPolicy,
+    ) -> (TestContext, crate::ReportHandle) {
+        let ai = planet::PlanetAI::with_config(PlanetConfig {
+            overflow,
+            ..PlanetConfig::default()
+        });
+        let handle = ai.report_handle();
+        (spawn_planet_of_type(planet_type, ai), handle)
+    }
+
+    #[test]
+    fn burst_is_counted_as_waste() {
+        let (planet, handle) = spawn_with_policy(PlanetType::C, OverflowPolicy::Waste);
+        // One sunray charges the cell, one moves into the rocket, the others overflow
+        charge_planet_with_sunrays(&planet, 10);
+
+        let report = report(&planet, &handle);
+        assert_eq!(report.sunrays.received, 10);
+        assert_eq!(report.sunrays.wasted, 8);
+        assert_eq!(report.sunrays.stocked, 0);
+        assert_eq!(report.carbon_stock.level, 0);
+        assert!((report.sunrays.waste_rate() - 0.8).abs() < f64::EPSILON);
+        assert_eq!(report.energy.total(EnergyConsumer::WastedSunray), 8);
+    }
+
+    #[test]
+    fn burst_is_banked_as_carbon() {
+        let (planet, handle) = spawn_with_policy(PlanetType::C, OverflowPolicy::StockCarbon);
+        charge_planet_with_sunrays(&planet, 10);
+
+        let report = report(&planet, &handle);
+        assert_eq!(report.sunrays.received, 10);
+        assert_eq!(report.sunrays.wasted, 0);
+        assert_eq!(report.sunrays.stocked, 8);
+        assert_eq!(report.carbon_stock.level, 8);
+        assert_eq!(report.energy.total(EnergyConsumer::CarbonStock), 8);
+        // The cell is recharged after every Carbon: no sunray is lost
+        assert_eq!(report.energy.charged, 10);
+    }
+
+    /// Planets without rockets overflow as soon as every cell is charged
+    #[test]
+    fn burst_on_a_planet_without_rockets() {
+        let (planet, handle) = spawn_with_policy(PlanetType::D, OverflowPolicy::StockCarbon);
+        charge_planet_with_sunrays(&planet, 12);
+
+        let report = report(&planet, &handle);
+        assert_eq!(report.sunrays.stocked, 7);
+        assert_eq!(report.energy.total(EnergyConsumer::Rocket), 0);
+    }
+}
+
+mod carbon_stockpile {
+    use super::*;
+    use crate::{OverflowPolicy, PlanetConfig, StockConfig};
+
+    fn spawn_stocking_planet(cap: usize) -> (TestContext, crate::ReportHandle) {
+        let (planet, handle) = spawn_with_config(PlanetConfig {
+            overflow: OverflowPolicy::StockCarbon,
+            stock: StockConfig { cap },
+            ..PlanetConfig::default()
+        });
+        register_explorer_with_planet(&planet, 0);
+        (planet, handle)
+    }
+
+    fn available_cells(planet: &TestContext) -> u32 {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 0 });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::AvailableEnergyCellResponse { available_cells } => available_cells,
+            _ => panic!("Expected AvailableEnergyCellResponse"),
+        }
+    }
+
+    #[test]
+    fn banked_units_are_served_first() {
+        let (planet, handle) = spawn_stocking_planet(16);
+        // Cell, rocket, then 3 units banked
+        charge_planet_with_sunrays(&planet, 5);
+        assert_eq!(report(&planet, &handle).carbon_stock.level, 3);
+
+        for _ in 0..3 {
+            assert!(
+                extract_carbon(get_basic_resource(&planet, 0, BasicResourceType::Carbon)).is_some()
+            );
+            assert_eq!(
+                available_cells(&planet),
+                1,
+                "the cell was spent before the stock"
+            );
+        }
+        let stock = report(&planet, &handle).carbon_stock;
+        assert_eq!(stock.level, 0);
+        assert_eq!(stock.banked, 3);
+        assert_eq!(stock.served, 3);
+
+        // Stock empty: back to the energy cell
+        assert!(get_basic_resource(&planet, 0, BasicResourceType::Carbon).is_some());
+        assert_eq!(available_cells(&planet), 0);
+    }
+
+    #[test]
+    fn stock_is_capped() {
+        let (planet, handle) = spawn_stocking_planet(2);
+        charge_planet_with_sunrays(&planet, 10);
+
+        let report = report(&planet, &handle);
+        assert_eq!(report.carbon_stock.level, 2);
+        assert_eq!(report.carbon_stock.cap, 2);
+        // Published with the stock by the same request
+        assert_eq!(report.sunrays.wasted, 6);
+    }
+
+    /// After an asteroid, sunrays rebuild the rocket before filling the stock
+    #[test]
+    fn stock_never_starves_the_rocket() {
+        let (planet, handle) = spawn_stocking_planet(16);
+        charge_planet_with_sunrays(&planet, 1);
+        send_asteroid(&planet);
+
+        // Cell, rocket, one unit banked
+        charge_planet_with_sunrays(&planet, 3);
+        assert_eq!(report(&planet, &handle).carbon_stock.level, 1);
+        assert!(send_asteroid(&planet));
+        assert_eq!(available_cells(&planet), 1);
+    }
+}
+
+mod bandit_strategy {
+    use super::*;
+    use crate::{AsteroidPolicy, BanditConfig, BanditStats, PlanetConfig, SunrayPolicy};
+
+    fn spawn_with_bandit(bandit: BanditConfig) -> (TestContext, crate::ReportHandle) {
+        spawn_with_config(PlanetConfig {
+            bandit,
+            ..PlanetConfig::default()
+        })
+    }
+
+    /// One sunray, then an asteroid closing the episode
+    fn play_episode(planet: &TestContext) {
+        charge_planet_with_sunrays(planet, 1);
+        send_asteroid(planet);
+    }
+
+    #[test]
+    fn disabled_bandit_keeps_the_default_policies() {
+        let (planet, handle) = spawn_with_bandit(BanditConfig::default());
+        for _ in 0..3 {
+            play_episode(&planet);
+        }
+
+        let stats = report(&planet, &handle).strategy;
+        assert_eq!(stats.current, 0);
+        assert_eq!(stats.current_arm().sunray, SunrayPolicy::Hoard);
+        assert_eq!(stats.current_arm().asteroid, AsteroidPolicy::BuildOnDemand);
+        assert_eq!(stats.arms[0].pulls, 3);
+        assert_eq!(stats.arms[0].total_reward, 30.0);
+    }
+
+    #[test]
+    fn malformed_prior_is_ignored() {
+        let prior = BanditStats {
+            arms: Vec::new(),
+            current: 7,
+        };
+        let (planet, handle) = spawn_with_bandit(BanditConfig {
+            enabled: true,
+            prior: Some(prior),
+            ..BanditConfig::default()
+        });
+        play_episode(&planet);
+
+        let stats = report(&planet, &handle).strategy;
+        assert_eq!(stats.arms.len(), BanditStats::default().arms.len());
+        assert_eq!(stats.arms.iter().map(|arm| arm.pulls).sum::<u32>(), 1);
+    }
+
+    #[test]
+    fn every_arm_is_tried_and_the_best_is_kept() {
+        let (planet, handle) = spawn_with_bandit(BanditConfig {
+            enabled: true,
+            epsilon: 0.0,
+            ..BanditConfig::default()
+        });
+        for _ in 0..4 {
+            play_episode(&planet);
+        }
+
+        let stats = report(&planet, &handle).strategy;
+        assert!(stats.arms.iter().all(|arm| arm.pulls == 1));
+        // Hoarding without building on demand leaves the planet without a rocket
+        let ready_only_hoard = stats
+            .arms
+            .iter()
+            .find(|arm| {
+                arm.sunray == SunrayPolicy::Hoard && arm.asteroid == AsteroidPolicy::ReadyOnly
+            })
+            .unwrap();
+        assert_eq!(ready_only_hoard.total_reward, 0.0);
+        assert_eq!(stats.current_arm().mean(), 10.0);
+    }
+
+    #[test]
+    fn same_seed_same_choices() {
+        let config = BanditConfig {
+            enabled: true,
+            epsilon: 0.5,
+            seed: 99,
+            ..BanditConfig::default()
+        };
+        let (first, first_report) = spawn_with_bandit(config.clone());
+        let (second, second_report) = spawn_with_bandit(config);
+        for _ in 0..12 {
+            play_episode(&first);
+            play_episode(&second);
+        }
+        assert_eq!(
+            report(&first, &first_report).strategy,
+            report(&second, &second_report).strategy
+        );
+    }
+
+    #[test]
+    fn learned_stats_are_persisted_and_reused() {
+        let mut prior = BanditStats::default();
+        for (index, arm) in prior.arms.iter_mut().enumerate() {
+            arm.pulls = 4;
+            arm.total_reward = if index == 3 { 200.0 } else { 8.0 };
+        }
+
+        let path =
+            std::env::temp_dir().join(format!("air_fryer_bandit_{}.txt", std::process::id()));
+        prior.save(&path).unwrap();
+        let loaded = BanditStats::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded, prior);
+
+        // The best arm of the previous games is played right away
+        let (planet, handle) = spawn_with_bandit(BanditConfig {
+            enabled: true,
+            epsilon: 0.0,
+            prior: Some(loaded),
+            ..BanditConfig::default()
+        });
+        assert_eq!(report(&planet, &handle).strategy.current, 3);
+    }
+
+    #[test]
+    fn malformed_stats_are_rejected() {
+        assert!(BanditStats::parse("hoard build_on_demand 1 2").is_err());
+        let err = BanditStats::parse("# air_fryer bandit v1\nhoard build_on_demand 1").unwrap_err();
+        assert_eq!(err, "line 2: expected 4 fields, found 3");
+        let err = BanditStats::parse("# air_fryer bandit v1\nhoard never 1 2").unwrap_err();
+        assert_eq!(err, "line 2: unknown arm hoard never");
+    }
+}
+
+mod strategy_tournament {
+    use super::*;
+    use crate::{
+        AsteroidPolicy, BanditConfig, Entrant, Event, EventStream, OverflowPolicy, PlanetConfig,
+        StreamConfig, SunrayPolicy, Tournament,
+    };
+
+    /// Bandit stuck on the Hoard/ReadyOnly arm, which never builds a rocket on demand
+    fn ready_only() -> PlanetConfig {
+        PlanetConfig {
+            bandit: BanditConfig::fixed(SunrayPolicy::Hoard, AsteroidPolicy::ReadyOnly),
+            ..PlanetConfig::default()
+        }
+    }
+
+    #[test]
+    fn generated_streams_are_reproducible() {
+        let config = StreamConfig::default();
+        assert_eq!(
+            EventStream::generate(5, &config),
+            EventStream::generate(5, &config)
+        );
+        assert_ne!(
+            EventStream::generate(5, &config),
+            EventStream::generate(6, &config)
+        );
+    }
+
+    #[test]
+    fn recorded_streams_round_trip() {
+        let stream = EventStream::generate(1, &StreamConfig::default());
+        assert_eq!(EventStream::parse(&stream.to_string()), Ok(stream));
+
+        let stream = EventStream::parse("# warm up\nsunray\n\ncarbon 3\nasteroid").unwrap();
+        assert_eq!(
+            stream.events,
+            vec![
+                Event::Sunray,
+                Event::Carbon { explorer: 3 },
+                Event::Asteroid
+            ]
+        );
+        assert_eq!(stream.explorers(), 4);
+
+        let err = EventStream::parse("sunray\ncarbon many").unwrap_err();
+        assert_eq!(err, "line 2: invalid explorer \"many\"");
+        let err = EventStream::parse("meteor").unwrap_err();
+        assert_eq!(err, "line 1: unknown event \"meteor\"");
+    }
+
+    #[test]
+    fn entrants_are_scored_and_ranked() {
+        let stream = EventStream::parse(
+            "sunray\ncarbon 0\nsunray\nasteroid\nquery 1\ncarbon 1\nquery 1\nsunray\nsunray\nsunray\ncarbon 0",
+        )
+        .unwrap();
+        let entrants = vec![
+            Entrant::new("ready_only", ready_only()),
+            Entrant::new("default", PlanetConfig::default()),
+        ];
+        let report = Tournament::new(entrants, vec![stream])
+            .run(forge())
+            .unwrap();
+
+        let winner = report.winner().unwrap();
+        assert_eq!(winner.name, "default");
+        assert_eq!((winner.survived, winner.deflected), (1, 1));
+        // The last sunrays charged the cell, built a rocket and found nowhere to go
+        assert_eq!((winner.served, winner.refused, winner.wasted), (2, 1, 1));
+
+        let loser = &report.ranking[1];
+        assert_eq!(loser.name, "ready_only");
+        assert_eq!(
+            (loser.survived, loser.asteroids, loser.deflected),
+            (0, 1, 0)
+        );
+        // The requests already sent are answered, the charged cell kept for a rocket
+        assert_eq!((loser.served, loser.refused), (1, 1));
+        // Only the explorer asking learns of the asteroid, once
+        assert_eq!(loser.warnings, 1);
+        assert_eq!(winner.warnings, 0);
+        assert!(loser.score < winner.score);
+    }
+
+    #[test]
+    fn same_streams_same_scores() {
+        let streams: Vec<EventStream> = (0..3)
+            .map(|seed| EventStream::generate(seed, &StreamConfig::default()))
+            .collect();
+        let stock = PlanetConfig {
+            overflow: OverflowPolicy::StockCarbon,
+            ..PlanetConfig::default()
+        };
+        let entrants = vec![
+            Entrant::new("first", stock.clone()),
+            Entrant::new("second", stock),
+        ];
+        let report = Tournament::new(entrants, streams).run(forge()).unwrap();
+
+        let (first, second) = (&report.ranking[0], &report.ranking[1]);
+        assert_eq!(first.name, "first", "ties keep the entrants order");
+        assert_eq!(first.streams, 3);
+        assert_eq!(
+            (first.served, first.wasted, first.survived, first.score),
+            (second.served, second.wasted, second.survived, second.score)
+        );
+    }
+}
+
+mod rule_policies {
+    use super::*;
+    use crate::{EnergyConsumer, Fact, PlanetConfig, RuleSet};
+    use common_planet::PlanetType;
+
+    fn spawn_with_rules(
+        planet_type: PlanetType,
+        rules: &str,
+    ) -> (TestContext, crate::ReportHandle) {
+        let ai = planet::PlanetAI::with_config(PlanetConfig {
+            rules: RuleSet::parse(rules).unwrap(),
+            ..PlanetConfig::default()
+        });
+        let handle = ai.report_handle();
+        (spawn_planet_of_type(planet_type, ai), handle)
+    }
+
+    fn rockets_built(planet: &TestContext, handle: &crate::ReportHandle) -> u32 {
+        report(planet, handle).energy.total(EnergyConsumer::Rocket)
+    }
+
+    #[test]
+    fn default_rules_round_trip() {
+        let rules = RuleSet::default();
+        assert_eq!(rules.rules.len(), 5);
+        assert_eq!(RuleSet::parse(&rules.to_string()), Ok(rules.clone()));
+        assert_eq!(PlanetConfig::default().rules, rules);
+    }
+
+    #[test]
+    fn policy_facts_are_read_by_the_default_rules() {
+        let rules = RuleSet::default();
+        for fact in [Fact::RocketFirst, Fact::BuildOnDemand, Fact::StockOverflow] {
+            assert!(rules.reads(fact), "{} is read", fact.name());
+        }
+        let custom = RuleSet::parse("when asteroid and not rocket -> build_rocket").unwrap();
+        assert!(custom.reads(Fact::Rocket));
+        assert!(!custom.reads(Fact::BuildOnDemand));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let rules = RuleSet::parse("when sunray or asteroid and charged_cells > 2 -> build_rocket")
+            .unwrap();
+        let condition = &rules.rules[0].condition;
+        assert_eq!(condition.len(), 2);
+        assert_eq!(condition[1].len(), 2);
+        assert_eq!(
+            rules.to_string(),
+            "when sunray or asteroid and charged_cells > 2 -> build_rocket\n"
+        );
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_problem() {
+        let cases = [
+            (
+                "when asteroid and not rokcet -> build_rocket",
+                "line 1, column 23: unknown fact \"rokcet\"",
+            ),
+            (
+                "# comment\nif sunray -> build_rocket",
+                "line 2, column 1: expected 'when', found \"if\"",
+            ),
+            (
+                "when sunray rocket -> build_rocket",
+                "line 1, column 13: expected 'and', 'or' or '->', found \"rocket\"",
+            ),
+            (
+                "when sunray -> launch",
+                "line 1, column 16: unknown action \"launch\"",
+            ),
+            (
+                "when sunray and",
+                "line 1, column 16: expected a fact, found the end of the line",
+            ),
+            (
+                "when asteroid_rate > many -> build_rocket",
+                "line 1, column 22: expected a number, found \"many\"",
+            ),
+            (
+                "when sunray -> build_rocket now",
+                "line 1, column 29: unexpected \"now\" after the action",
+            ),
+            (
+                "when sunray & rocket -> build_rocket",
+                "line 1, column 13: unexpected character '&'",
+            ),
+        ];
+        for (text, error) in cases {
+            assert_eq!(RuleSet::parse(text).unwrap_err(), error, "{}", text);
+        }
+    }
+
+    #[test]
+    fn without_rules_no_rocket_is_built() {
+        let (planet, handle) = spawn_with_rules(PlanetType::C, "");
+        charge_planet_with_sunrays(&planet, 3);
+        assert!(!send_asteroid(&planet));
+        assert_eq!(rockets_built(&planet, &handle), 0);
+    }
+
+    #[test]
+    fn rocket_built_when_the_asteroid_comes() {
+        let (planet, handle) = spawn_with_rules(
+            PlanetType::C,
+            "when asteroid and not rocket and cell_charged -> build_rocket",
+        );
+        // The second sunray is wasted instead of becoming a rocket
+        charge_planet_with_sunrays(&planet, 2);
+        assert_eq!(rockets_built(&planet, &handle), 0);
+        assert!(send_asteroid(&planet));
+        assert_eq!(rockets_built(&planet, &handle), 1);
+    }
+
+    #[test]
+    fn numeric_facts_are_compared() {
+        let (planet, handle) = spawn_with_rules(
+            PlanetType::A,
+            "when sunray and charged_cells >= 2 and not rocket -> build_rocket",
+        );
+        charge_planet_with_sunrays(&planet, 2);
+        assert_eq!(rockets_built(&planet, &handle), 0);
+        charge_planet_with_sunrays(&planet, 1);
+        assert_eq!(rockets_built(&planet, &handle), 1);
+    }
+}
+
+mod lifecycle_machine {
+    use super::*;
+    use crate::lifecycle::Lifecycle;
+    use crate::{LifecycleEvent, LifecyclePhase, PlanetConfig, Rejection};
+    use common_game::utils::ID;
+    use std::time::Instant;
+
+    fn running_with(explorers: &[ID]) -> Lifecycle {
+        let mut lifecycle = Lifecycle::new();
+        lifecycle
+            .apply(LifecycleEvent::Start, Instant::now())
+            .unwrap();
+        for explorer_id in explorers {
+            lifecycle
+                .apply(LifecycleEvent::Arrival(*explorer_id), Instant::now())
+                .unwrap();
+        }
+        lifecycle
+    }
+
+    fn last_rejection(lifecycle: &Lifecycle) -> Option<Rejection> {
+        lifecycle.history().last().and_then(|t| t.rejected)
+    }
+
+    #[test]
+    fn created_to_running() {
+        let mut lifecycle = Lifecycle::new();
+        assert_eq!(lifecycle.phase(), LifecyclePhase::Created);
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Start, Instant::now()),
+            Ok(())
+        );
+        assert_eq!(lifecycle.phase(), LifecyclePhase::Running);
+
+        let transition = lifecycle.history()[0];
+        assert_eq!(transition.event, LifecycleEvent::Start);
+        assert_eq!(transition.from, LifecyclePhase::Created);
+        assert_eq!(transition.to, LifecyclePhase::Running);
+        assert_eq!(transition.rejected, None);
+    }
+
+    #[test]
+    fn running_to_stopped_forgets_explorers() {
+        let mut lifecycle = running_with(&[3]);
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Stop, Instant::now()),
+            Ok(())
+        );
+        assert_eq!(lifecycle.phase(), LifecyclePhase::Stopped);
+        assert!(lifecycle.explorers().is_empty());
+    }
+
+    #[test]
+    fn stopped_to_running() {
+        let mut lifecycle = running_with(&[]);
+        lifecycle
+            .apply(LifecycleEvent::Stop, Instant::now())
+            .unwrap();
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Start, Instant::now()),
+            Ok(())
+        );
+        assert_eq!(lifecycle.phase(), LifecyclePhase::Running);
+    }
+
+    #[test]
+    fn arrival_and_departure_while_running() {
+        let mut lifecycle = running_with(&[5, 2]);
+        assert_eq!(lifecycle.explorers(), vec![2, 5]);
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Departure(5), Instant::now()),
+            Ok(())
+        );
+        assert_eq!(lifecycle.explorers(), vec![2]);
+        assert_eq!(lifecycle.phase(), LifecyclePhase::Running);
+    }
+
+    #[test]
+    fn start_while_running_is_rejected() {
+        let mut lifecycle = running_with(&[1]);
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Start, Instant::now()),
+            Err(Rejection::AlreadyRunning)
+        );
+        // A rejected start doesn't forget the explorers
+        assert_eq!(lifecycle.explorers(), vec![1]);
+        assert_eq!(last_rejection(&lifecycle), Some(Rejection::AlreadyRunning));
+    }
+
+    #[test]
+    fn stop_while_not_running_is_rejected() {
+        let mut lifecycle = Lifecycle::new();
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Stop, Instant::now()),
+            Err(Rejection::NotRunning)
+        );
+        assert_eq!(lifecycle.phase(), LifecyclePhase::Created);
+
+        let mut lifecycle = running_with(&[]);
+        lifecycle
+            .apply(LifecycleEvent::Stop, Instant::now())
+            .unwrap();
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Stop, Instant::now()),
+            Err(Rejection::NotRunning)
+        );
+        assert_eq!(lifecycle.phase(), LifecyclePhase::Stopped);
+    }
+
+    #[test]
+    fn arrival_while_not_running_is_rejected() {
+        let mut lifecycle = Lifecycle::new();
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Arrival(1), Instant::now()),
+            Err(Rejection::NotRunning)
+        );
+        assert!(lifecycle.explorers().is_empty());
+    }
+
+    #[test]
+    fn double_arrival_is_rejected() {
+        let mut lifecycle = running_with(&[1]);
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Arrival(1), Instant::now()),
+            Err(Rejection::AlreadyOnPlanet)
+        );
+        assert_eq!(lifecycle.explorers(), vec![1]);
+    }
+
+    #[test]
+    fn departure_without_arrival_is_rejected() {
+        let mut lifecycle = running_with(&[1]);
+        assert_eq!(
+            lifecycle.apply(LifecycleEvent::Departure(2), Instant::now()),
+            Err(Rejection::NotOnPlanet)
+        );
+        assert_eq!(last_rejection(&lifecycle), Some(Rejection::NotOnPlanet));
+    }
+
+    #[test]
+    fn requests_need_a_running_planet_and_a_registered_explorer() {
+        let mut lifecycle = running_with(&[1]);
+        let recorded = lifecycle.history().len();
+        assert_eq!(lifecycle.request(1, Instant::now()), Ok(()));
+        assert_eq!(
+            lifecycle.history().len(),
+            recorded,
+            "accepted requests aren't transitions"
+        );
+        assert_eq!(
+            lifecycle.request(2, Instant::now()),
+            Err(PlanetError::ExplorerNotRegistered)
+        );
+
+        lifecycle
+            .apply(LifecycleEvent::Stop, Instant::now())
+            .unwrap();
+        assert_eq!(
+            lifecycle.request(1, Instant::now()),
+            Err(PlanetError::PlanetStopped)
+        );
+        assert_eq!(last_rejection(&lifecycle), Some(Rejection::NotRunning));
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut lifecycle = running_with(&[]);
+        for _ in 0..100 {
+            let _ = lifecycle.apply(LifecycleEvent::Departure(9), Instant::now());
+        }
+        let history = lifecycle.history();
+        assert_eq!(history.len(), 64);
+        assert!(history.iter().all(|t| t.rejected.is_some()));
+    }
+
+    #[test]
+    fn transitions_are_reported() {
+        let (planet, handle) = spawn_with_config(PlanetConfig::default());
+        register_explorer_with_planet(&planet, 1);
+        let _ = planet
+            .snd_orc_to_planet
+            .send(OrchestratorToPlanet::OutgoingExplorerRequest { explorer_id: 4 });
+        let _ = planet.recv_orchestrator_msg();
+
+        let snapshot = report(&planet, &handle);
+        assert_eq!(snapshot.lifecycle, LifecyclePhase::Running);
+        let events: Vec<(LifecycleEvent, Option<Rejection>)> = snapshot
+            .transitions
+            .iter()
+            .map(|t| (t.event, t.rejected))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (LifecycleEvent::Start, None),
+                (LifecycleEvent::Arrival(1), None),
+                (LifecycleEvent::Departure(4), Some(Rejection::NotOnPlanet)),
+            ]
+        );
+    }
+}
+
+mod covert_warning {
+    use super::*;
+    use crate::{AllianceConfig, CovertConfig, CovertDecoder, PlanetConfig, Signal, Tier};
+    use common_game::utils::ID;
+    use std::collections::HashSet;
+
+    const SECRET: u128 = 0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210;
+    const ALLY: ID = 7;
+    const RIVAL: ID = 9;
+
+    fn spawn_covert() -> TestContext {
+        let planet = spawn_planet_with_ai(planet::PlanetAI::with_config(PlanetConfig {
+            covert: CovertConfig {
+                secret: Some(SECRET),
+            },
+            alliance: AllianceConfig::default().member(ALLY, Tier::Ally),
+            ..PlanetConfig::default()
+        }));
+        register_explorer_with_planet(&planet, ALLY);
+        register_explorer_with_planet(&planet, RIVAL);
+        planet
+    }
+
+    /// An asteroid hits the uncharged planet, leaving a warning to deliver
+    fn hit(planet: &TestContext) {
+        assert!(!send_asteroid(planet), "expected a hit");
+    }
+
+    fn query(planet: &TestContext, explorer_id: ID) -> HashSet<ComplexResourceType> {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::SupportedCombinationResponse { combination_list } => combination_list,
+            other => panic!("expected SupportedCombinationResponse, got {:?}", other),
+        }
+    }
+
+    fn all_recipes() -> HashSet<ComplexResourceType> {
+        HashSet::from([
+            ComplexResourceType::Water,
+            ComplexResourceType::Diamond,
+            ComplexResourceType::Life,
+            ComplexResourceType::Robot,
+            ComplexResourceType::Dolphin,
+            ComplexResourceType::AIPartner,
+        ])
+    }
+
+    #[test]
+    fn allies_decode_the_warning() {
+        let planet = spawn_covert();
+        let mut decoder = CovertDecoder::new(SECRET, ALLY, all_recipes());
+
+        assert_eq!(decoder.decode(&query(&planet, ALLY)), Signal::Clear);
+        hit(&planet);
+        assert_eq!(decoder.decode(&query(&planet, ALLY)), Signal::Warning);
+        // The warning is delivered once
+        assert_eq!(decoder.decode(&query(&planet, ALLY)), Signal::Clear);
+        assert_eq!(decoder.counter(), 3);
+    }
+
+    #[test]
+    fn responses_rotate_with_the_counter() {
+        let planet = spawn_covert();
+        let responses: Vec<HashSet<ComplexResourceType>> =
+            (0..8).map(|_| query(&planet, ALLY)).collect();
+        assert!(
+            responses.iter().any(|response| *response != responses[0]),
+            "every clear response looks the same"
+        );
+    }
+
+    #[test]
+    fn replays_are_detected() {
+        let planet = spawn_covert();
+        let mut decoder = CovertDecoder::new(SECRET, ALLY, all_recipes());
+        let first = query(&planet, ALLY);
+        assert_eq!(decoder.decode(&first), Signal::Clear);
+        for _ in 0..4 {
+            decoder.decode(&query(&planet, ALLY));
+        }
+        assert_eq!(decoder.decode(&first), Signal::Replayed);
+        assert_eq!(decoder.counter(), 5, "a replay doesn't move the counter");
+    }
+
+    #[test]
+    fn missed_responses_are_skipped_over() {
+        let planet = spawn_covert();
+        let mut decoder = CovertDecoder::new(SECRET, ALLY, all_recipes());
+        assert_eq!(decoder.decode(&query(&planet, ALLY)), Signal::Clear);
+        // Lost on the way
+        query(&planet, ALLY);
+        hit(&planet);
+        assert_eq!(decoder.decode(&query(&planet, ALLY)), Signal::Warning);
+        assert_eq!(decoder.counter(), 3);
+        assert_eq!(decoder.decode(&query(&planet, ALLY)), Signal::Clear);
+    }
+
+    #[test]
+    fn other_keys_and_explorers_cant_decode() {
+        let planet = spawn_covert();
+        hit(&planet);
+        let response = query(&planet, ALLY);
+
+        let mut wrong_key = CovertDecoder::new(SECRET ^ 1, ALLY, all_recipes());
+        assert_eq!(wrong_key.decode(&response), Signal::Unrecognized);
+        let mut wrong_explorer = CovertDecoder::new(SECRET, RIVAL, all_recipes());
+        assert_eq!(wrong_explorer.decode(&response), Signal::Unrecognized);
+    }
+
+    #[test]
+    fn non_allies_get_indistinguishable_responses() {
+        let quiet = spawn_covert();
+        let warned = spawn_covert();
+        hit(&warned);
+
+        for _ in 0..4 {
+            let quiet_response = query(&quiet, RIVAL);
+            assert_eq!(quiet_response, all_recipes());
+            assert_eq!(query(&warned, RIVAL), quiet_response);
+        }
+
+        // Rival queries don't consume the warning meant for the allies
+        let mut decoder = CovertDecoder::new(SECRET, ALLY, all_recipes());
+        assert_eq!(decoder.decode(&query(&warned, ALLY)), Signal::Warning);
+    }
+}
+
+mod alliance_tiers {
+    use super::*;
+    use crate::recipes;
+    use crate::{AllianceConfig, PlanetConfig, Tier};
+    use common_game::components::resource::Carbon;
+    use common_game::utils::ID;
+    use std::collections::HashSet;
+
+    const ALLY: ID = 1;
+    const NEUTRAL: ID = 50;
+    const RIVAL: ID = 150;
+
+    fn spawn_with_reserve(reserve: usize) -> TestContext {
+        let alliance = AllianceConfig::default()
+            .member(ALLY, Tier::Ally)
+            .members(100..=199, Tier::Rival)
+            .reserve(reserve);
+        let planet = spawn_planet_with_ai(planet::PlanetAI::with_config(PlanetConfig {
+            alliance,
+            ..PlanetConfig::default()
+        }));
+        for explorer_id in [ALLY, NEUTRAL, RIVAL] {
+            register_explorer_with_planet(&planet, explorer_id);
+        }
+        planet
+    }
+
+    fn carbon_for(planet: &TestContext, explorer_id: ID) -> Option<Carbon> {
+        charge_planet_with_sunrays(planet, 1);
+        extract_carbon(get_basic_resource(
+            planet,
+            explorer_id,
+            BasicResourceType::Carbon,
+        ))
+    }
+
+    fn supported_resources(planet: &TestContext, explorer_id: ID) -> HashSet<BasicResourceType> {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedResourceRequest { explorer_id });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::SupportedResourceResponse { resource_list } => resource_list,
+            other => panic!("expected SupportedResourceResponse, got {:?}", other),
+        }
+    }
+
+    fn supported_combinations(planet: &TestContext, explorer_id: ID) -> usize {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::SupportedCombinationResponse { combination_list } => {
+                combination_list.len()
+            }
+            other => panic!("expected SupportedCombinationResponse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tiers_come_from_ids_and_ranges() {
+        let alliance = AllianceConfig::default()
+            .member(7, Tier::Rival)
+            .members(0..=9, Tier::Ally);
+        assert_eq!(alliance.tier(3), Tier::Ally);
+        assert_eq!(alliance.tier(7), Tier::Rival, "the first entry wins");
+        assert_eq!(alliance.tier(10), Tier::Neutral);
+        assert_eq!(AllianceConfig::default().tier(7), Tier::Neutral);
+    }
+
+    #[test]
+    fn reserved_energy_goes_to_the_allies() {
+        let planet = spawn_with_reserve(1);
+        assert!(carbon_for(&planet, NEUTRAL).is_none());
+        let first = carbon_for(&planet, ALLY).unwrap();
+        let second = carbon_for(&planet, ALLY).unwrap();
+
+        charge_planet_with_sunrays(&planet, 1);
+        let refused = combine_resources(
+            &planet,
+            NEUTRAL,
+            ComplexResourceRequest::Diamond(first, second),
+        );
+        let Err((error, left, right)) = refused else {
+            panic!("the neutral explorer got the reserved cell");
+        };
+        assert_eq!(
+            PlanetError::parse(&error),
+            Some(PlanetError::ReservedForAllies)
+        );
+
+        let request = recipes::build_request(ComplexResourceType::Diamond, left, right).unwrap();
+        assert!(combine_resources(&planet, ALLY, request).is_ok());
+    }
+
+    #[test]
+    fn without_reserve_neutrals_are_served_alike() {
+        let planet = spawn_with_reserve(0);
+        assert!(carbon_for(&planet, NEUTRAL).is_some());
+        assert_eq!(supported_combinations(&planet, NEUTRAL), 6);
+    }
+
+    #[test]
+    fn rivals_get_reduced_offers() {
+        let planet = spawn_with_reserve(1);
+        charge_planet_with_sunrays(&planet, 1);
+        assert_eq!(supported_combinations(&planet, RIVAL), 0);
+        assert!(supported_resources(&planet, RIVAL).is_empty());
+        assert_eq!(
+            supported_resources(&planet, ALLY),
+            HashSet::from([BasicResourceType::Carbon])
+        );
+
+        let planet = spawn_with_reserve(0);
+        charge_planet_with_sunrays(&planet, 1);
+        assert_eq!(
+            supported_resources(&planet, RIVAL),
+            HashSet::from([BasicResourceType::Carbon])
+        );
+        let first = carbon_for(&planet, RIVAL).unwrap();
+        let second = carbon_for(&planet, ALLY).unwrap();
+        charge_planet_with_sunrays(&planet, 1);
+        let refused = combine_resources(
+            &planet,
+            RIVAL,
+            ComplexResourceRequest::Diamond(first, second),
+        );
+        let Err((error, _, _)) = refused else {
+            panic!("a rival got a combination");
+        };
+        assert_eq!(
+            PlanetError::parse(&error),
+            Some(PlanetError::UnsupportedResource)
+        );
+    }
+}
+
+mod warning_lifecycle {
+    use super::*;
+    use crate::{PlanetConfig, WarningConfig};
+    use common_game::utils::ID;
+
+    fn spawn_with_ttl(ttl: Duration) -> (TestContext, crate::ReportHandle) {
+        let (planet, handle) = spawn_with_config(PlanetConfig {
+            warning: WarningConfig {
+                ttl,
+                ..WarningConfig::default()
+            },
+            ..PlanetConfig::default()
+        });
+        register_explorer_with_planet(&planet, 1);
+        register_explorer_with_planet(&planet, 2);
+        (planet, handle)
+    }
+
+    fn warned(planet: &TestContext, explorer_id: ID) -> bool {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::SupportedCombinationResponse { combination_list } => {
+                !combination_list.contains(&ComplexResourceType::AIPartner)
+            }
+            other => panic!("expected SupportedCombinationResponse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn every_target_is_warned_once() {
+        let (planet, handle) = spawn_with_ttl(Duration::from_secs(30));
+        send_asteroid(&planet);
+        assert!(warned(&planet, 1));
+        assert!(!warned(&planet, 1), "the warning is delivered once");
+
+        let snapshot = report(&planet, &handle);
+        assert!(snapshot.pending_warning);
+        let warning = &snapshot.warnings[0];
+        assert_eq!(warning.targets, vec![1, 2]);
+        assert_eq!(warning.delivered, vec![1]);
+        assert_eq!(warning.delivery_rate(), 0.5);
+
+        assert!(warned(&planet, 2));
+        let snapshot = report(&planet, &handle);
+        assert!(!snapshot.pending_warning);
+        assert_eq!(snapshot.warnings[0].delivery_rate(), 1.0);
+    }
+
+    #[test]
+    fn stale_warnings_are_not_sent() {
+        let (planet, handle) = spawn_with_ttl(Duration::from_millis(1));
+        send_asteroid(&planet);
+        sleep(Duration::from_millis(20));
+        assert!(!warned(&planet, 1));
+
+        let snapshot = report(&planet, &handle);
+        assert!(!snapshot.pending_warning);
+        let warning = &snapshot.warnings[0];
+        assert!(warning.is_expired(std::time::Instant::now()));
+        assert!(warning.delivered.is_empty());
+    }
+
+    #[test]
+    fn deflected_asteroid_cancels_the_warning() {
+        let (planet, handle) = spawn_with_ttl(Duration::from_secs(30));
+        send_asteroid(&planet);
+        charge_planet_with_sunrays(&planet, 1);
+        send_asteroid(&planet);
+        assert!(!warned(&planet, 1));
+
+        let snapshot = report(&planet, &handle);
+        assert_eq!(snapshot.warnings.len(), 1);
+        assert!(snapshot.warnings[0].cancelled);
+    }
+
+    #[test]
+    fn warned_explorers_leaving_acknowledge() {
+        let (planet, handle) = spawn_with_ttl(Duration::from_secs(30));
+        send_asteroid(&planet);
+        assert!(warned(&planet, 1));
+        for explorer_id in [1, 2] {
+            let _ = planet
+                .snd_orc_to_planet
+                .send(OrchestratorToPlanet::OutgoingExplorerRequest { explorer_id });
+            let _ = planet.recv_orchestrator_msg();
+        }
+
+        let warning = &report(&planet, &handle).warnings[0];
+        assert_eq!(warning.acknowledged, vec![1]);
+    }
+
+    #[test]
+    fn late_arrivals_are_not_targets() {
+        let (planet, handle) = spawn_with_ttl(Duration::from_secs(30));
+        send_asteroid(&planet);
+        register_explorer_with_planet(&planet, 3);
+        assert!(!warned(&planet, 3));
+        assert_eq!(report(&planet, &handle).warnings[0].targets, vec![1, 2]);
+    }
+}
+
+mod threat_bus {
+    use super::*;
+    use crate::{PlanetConfig, ThreatBus, ThreatEvent};
+    use common_game::utils::ID;
+
+    fn spawn_on(bus: &ThreatBus) -> (TestContext, crate::ReportHandle) {
+        spawn_with_config(PlanetConfig {
+            threat_bus: Some(bus.clone()),
+            ..PlanetConfig::default()
+        })
+    }
+
+    fn warned(planet: &TestContext, explorer_id: ID) -> bool {
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id });
+        match planet.recv_explorer_msg() {
+            PlanetToExplorer::SupportedCombinationResponse { combination_list } => {
+                !combination_list.contains(&ComplexResourceType::AIPartner)
+            }
+            other => panic!("expected SupportedCombinationResponse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sibling_hit_warns_the_explorers() {
+        let bus = ThreatBus::default();
+        let (first, first_report) = spawn_on(&bus);
+        let (second, second_report) = spawn_on(&bus);
+        register_explorer_with_planet(&first, 1);
+        register_explorer_with_planet(&second, 2);
+
+        assert!(!send_asteroid(&first));
+        assert!(warned(&second, 2), "warned before the asteroid gets there");
+        assert!(warned(&first, 1));
+
+        assert_eq!(report(&second, &second_report).sibling_sightings, 1);
+        // A planet doesn't hear its own sightings
+        assert_eq!(report(&first, &first_report).sibling_sightings, 0);
+    }
+
+    #[test]
+    fn sibling_hit_keeps_the_next_sunray_for_a_rocket() {
+        let bus = ThreatBus::default();
+        let (first, _) = spawn_on(&bus);
+        let (second, _) = spawn_on(&bus);
+        let (lonely, _) = spawn_on(&ThreatBus::default());
+        register_explorer_with_planet(&second, 1);
+        register_explorer_with_planet(&lonely, 1);
+
+        assert!(!send_asteroid(&first));
+        for planet in [&second, &lonely] {
+            charge_planet_with_sunrays(planet, 1);
+        }
+        // The lonely planet sells the energy it would build the rocket with
+        assert!(get_basic_resource(&second, 1, BasicResourceType::Carbon).is_none());
+        assert!(get_basic_resource(&lonely, 1, BasicResourceType::Carbon).is_some());
+        assert!(send_asteroid(&second));
+        assert!(!send_asteroid(&lonely));
+    }
+
+    #[test]
+    fn planets_publish_sightings_and_rockets() {
+        let bus = ThreatBus::default();
+        let (first, _) = spawn_on(&bus);
+        let (second, _) = spawn_on(&bus);
+        assert_eq!(bus.members(), 2);
+
+        assert!(!send_asteroid(&first));
+        charge_planet_with_sunrays(&second, 1);
+        assert_eq!(bus.rockets(), vec![false, false]);
+        // The `threat` rule turns the charged cell into a rocket
+        charge_planet_with_sunrays(&second, 1);
+        assert_eq!(bus.rockets(), vec![false, true]);
+
+        assert!(send_asteroid(&second));
+        assert_eq!(bus.rockets(), vec![false, false]);
+        let asteroids: Vec<(usize, bool)> = bus
+            .events()
+            .into_iter()
+            .filter_map(|event| match event {
+                ThreatEvent::Asteroid {
+                    planet, deflected, ..
+                } => Some((planet, deflected)),
+                ThreatEvent::Rocket { .. } => None,
+            })
+            .collect();
+        assert_eq!(asteroids, vec![(0, false), (1, true)]);
+    }
+
+    #[test]
+    fn stale_sightings_are_ignored() {
+        let bus = ThreatBus::new(Duration::from_millis(1));
+        let (first, _) = spawn_on(&bus);
+        let (second, _) = spawn_on(&bus);
+        register_explorer_with_planet(&second, 2);
+
+        assert!(!send_asteroid(&first));
+        sleep(Duration::from_millis(20));
+        assert!(!warned(&second, 2));
+        charge_planet_with_sunrays(&second, 1);
+        assert_eq!(bus.rockets(), vec![false, false]);
+    }
+}
+
+mod extension_protocol {
+    use super::*;
+    use crate::testing::{extension_request, spawn_planet_with_extension};
+    use crate::{
+        AllianceConfig, ExtensionConfig, ExtensionRequest, ExtensionResponse, LifecyclePhase,
+        PlanetConfig, PlanetError, ReputationConfig, Tier,
+    };
+
+    fn spawn_with_ttl(ttl: Duration) -> (TestContext, crate::ExtensionClient) {
+        spawn_planet_with_extension(planet::PlanetAI::with_config(PlanetConfig {
+            extension: ExtensionConfig {
+                reservation_ttl: ttl,
+                ..ExtensionConfig::default()
+            },
+            ..PlanetConfig::default()
+        }))
+    }
+
+    #[test]
+    fn status_mirrors_the_planet() {
+        let (planet, extension) = spawn_planet_with_extension(planet::PlanetAI::new());
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, 1);
+
+        match extension_request(&planet, &extension, ExtensionRequest::Status) {
+            ExtensionResponse::Status(status) => {
+                assert_eq!(status.lifecycle, LifecyclePhase::Running);
+                assert_eq!((status.cells, status.charged_cells), (1, 1));
+                assert_eq!(status.explorers, 1);
+                assert!(!status.rocket);
+                assert_eq!(status.reserved_cells, 0);
+            }
+            other => panic!("expected Status, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn batch_carbon_stops_when_the_energy_runs_out() {
+        let (planet, extension) = spawn_planet_with_extension(planet::PlanetAI::new());
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, 1);
+
+        let request = ExtensionRequest::BatchCarbon {
+            explorer_id: 1,
+            amount: 3,
+        };
+        match extension_request(&planet, &extension, request) {
+            ExtensionResponse::Carbon { carbon, error } => {
+                assert_eq!(carbon.len(), 1);
+                assert_eq!(error, Some(PlanetError::NoEnergy));
+            }
+            other => panic!("expected Carbon, got {:?}", other),
+        }
+
+        let stranger = ExtensionRequest::BatchCarbon {
+            explorer_id: 2,
+            amount: 1,
+        };
+        assert!(matches!(
+            extension_request(&planet, &extension, stranger),
+            ExtensionResponse::Refused(PlanetError::ExplorerNotRegistered)
+        ));
+    }
+
+    #[test]
+    fn batch_carbon_counts_every_unit_against_the_quota() {
+        let (planet, extension) =
+            spawn_planet_with_extension(planet::PlanetAI::with_config(PlanetConfig {
+                reputation: ReputationConfig {
+                    max_requests: 1,
+                    ..ReputationConfig::default()
+                },
+                ..PlanetConfig::default()
+            }));
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, 1);
+
+        let request = ExtensionRequest::BatchCarbon {
+            explorer_id: 1,
+            amount: 2,
+        };
+        match extension_request(&planet, &extension, request) {
+            ExtensionResponse::Carbon { carbon, error } => {
+                assert_eq!(carbon.len(), 1);
+                assert_eq!(error, Some(PlanetError::QuotaExceeded));
+            }
+            other => panic!("expected Carbon, got {:?}", other),
+        }
+        assert!(matches!(
+            extension_request(&planet, &extension, request),
+            ExtensionResponse::Refused(PlanetError::QuotaExceeded)
+        ));
+    }
+
+    #[test]
+    fn reserved_cells_are_kept_for_their_holder() {
+        let (planet, extension) = spawn_with_ttl(Duration::from_secs(30));
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, 1);
+        register_explorer_with_planet(&planet, 2);
+
+        let reserve = |explorer_id, cells| ExtensionRequest::Reserve { explorer_id, cells };
+        assert!(matches!(
+            extension_request(&planet, &extension, reserve(1, 2)),
+            ExtensionResponse::Reserved { cells: 1 }
+        ));
+        assert!(matches!(
+            extension_request(&planet, &extension, reserve(2, 1)),
+            ExtensionResponse::Reserved { cells: 0 }
+        ));
+        assert!(get_basic_resource(&planet, 2, BasicResourceType::Carbon).is_none());
+        assert!(get_basic_resource(&planet, 1, BasicResourceType::Carbon).is_some());
+
+        match extension_request(&planet, &extension, ExtensionRequest::Status) {
+            ExtensionResponse::Status(status) => assert_eq!(status.reserved_cells, 0),
+            other => panic!("expected Status, got {:?}", other),
+        }
+    }
+
+    /// The cells are held by a neutral: the ally isn't told they are kept for allies
+    #[test]
+    fn cells_held_by_others_are_reported_as_such() {
+        let (planet, extension) =
+            spawn_planet_with_extension(planet::PlanetAI::with_config(PlanetConfig {
+                alliance: AllianceConfig::default().member(2, Tier::Ally),
+                ..PlanetConfig::default()
+            }));
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, 1);
+        register_explorer_with_planet(&planet, 2);
+
+        let reserve = ExtensionRequest::Reserve {
+            explorer_id: 1,
+            cells: 1,
+        };
+        assert!(matches!(
+            extension_request(&planet, &extension, reserve),
+            ExtensionResponse::Reserved { cells: 1 }
+        ));
+        let batch = ExtensionRequest::BatchCarbon {
+            explorer_id: 2,
+            amount: 1,
+        };
+        assert!(matches!(
+            extension_request(&planet, &extension, batch),
+            ExtensionResponse::Refused(PlanetError::ReservedByOthers)
+        ));
+    }
+
+    #[test]
+    fn reservations_lapse() {
+        let (planet, extension) = spawn_with_ttl(Duration::from_millis(1));
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, 1);
+        register_explorer_with_planet(&planet, 2);
+
+        let reserve = ExtensionRequest::Reserve {
+            explorer_id: 1,
+            cells: 1,
+        };
+        assert!(matches!(
+            extension_request(&planet, &extension, reserve),
+            ExtensionResponse::Reserved { cells: 1 }
+        ));
+        sleep(Duration::from_millis(20));
+        assert!(get_basic_resource(&planet, 2, BasicResourceType::Carbon).is_some());
+    }
+
+    #[test]
+    fn leaving_releases_the_reservation() {
+        let (planet, extension) = spawn_with_ttl(Duration::from_secs(30));
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, 1);
+        register_explorer_with_planet(&planet, 2);
+
+        let reserve = ExtensionRequest::Reserve {
+            explorer_id: 1,
+            cells: 1,
+        };
+        let _ = extension_request(&planet, &extension, reserve);
+        let _ = planet
+            .snd_orc_to_planet
+            .send(OrchestratorToPlanet::OutgoingExplorerRequest { explorer_id: 1 });
+        let _ = planet.recv_orchestrator_msg();
+        assert!(get_basic_resource(&planet, 2, BasicResourceType::Carbon).is_some());
+    }
+
+    #[test]
+    fn energy_eta_counts_the_cell_kept_for_the_rocket() {
+        let (planet, extension) = spawn_planet_with_extension(planet::PlanetAI::new());
+        let forecast = |planet: &TestContext| match extension_request(
+            planet,
+            &extension,
+            ExtensionRequest::EnergyEta,
+        ) {
+            ExtensionResponse::EnergyEta(forecast) => forecast,
+            other => panic!("expected EnergyEta, got {:?}", other),
+        };
+        let fresh = forecast(&planet);
+        assert_eq!((fresh.free_cells, fresh.sunrays_needed), (0, 1));
+        assert_eq!(fresh.eta, None);
+
+        // Once an asteroid hit, the first charged cell goes to the rocket
+        register_explorer_with_planet(&planet, 1);
+        assert!(!send_asteroid(&planet));
+        let hit = forecast(&planet);
+        assert_eq!((hit.free_cells, hit.sunrays_needed), (0, 2));
+
+        charge_planet_with_sunrays(&planet, 1);
+        let charged = forecast(&planet);
+        assert_eq!((charged.free_cells, charged.sunrays_needed), (0, 1));
+
+        charge_planet_with_sunrays(&planet, 1);
+        let armed = forecast(&planet);
+        assert_eq!((armed.free_cells, armed.sunrays_needed), (1, 0));
+        assert_eq!(armed.eta, Some(Duration::ZERO));
+    }
+}
+
+mod recipe_graph {
+    use super::*;
+    use crate::RecipeGraph;
+    use common_game::components::planet::PlanetType;
+
+    #[test]
+    fn type_c_completes_diamonds_alone() {
+        let graph = RecipeGraph::for_planet(PlanetType::C);
+        assert_eq!(graph.recipes().len(), 6);
+        assert_eq!(graph.completable(), vec![ComplexResourceType::Diamond]);
+        assert_eq!(
+            graph.external_basics(),
+            vec![
+                BasicResourceType::Hydrogen,
+                BasicResourceType::Oxygen,
+                BasicResourceType::Silicon,
+            ]
+        );
+
+        let partner = graph.external_inputs(ComplexResourceType::AIPartner);
+        assert_eq!(partner.basics, graph.external_basics());
+        assert!(partner.complex.is_empty());
+        let life = graph.external_inputs(ComplexResourceType::Life);
+        assert_eq!(
+            life.basics,
+            vec![BasicResourceType::Hydrogen, BasicResourceType::Oxygen]
+        );
+    }
+
+    #[test]
+    fn missing_recipes_are_external_inputs() {
+        let graph = RecipeGraph::new(
+            [BasicResourceType::Carbon],
+            [ComplexResourceType::AIPartner, ComplexResourceType::Diamond],
+        );
+        let partner = graph.external_inputs(ComplexResourceType::AIPartner);
+        assert_eq!(partner.complex, vec![ComplexResourceType::Robot]);
+        assert!(partner.basics.is_empty());
+        assert!(!graph.can_complete(ComplexResourceType::AIPartner));
+        assert!(!graph.can_complete(ComplexResourceType::Water));
+    }
+
+    #[test]
+    fn other_types() {
+        let graph = RecipeGraph::for_planet(PlanetType::B);
+        assert_eq!(graph.completable(), vec![ComplexResourceType::Diamond]);
+        assert!(graph.external_basics().is_empty());
+
+        let graph = RecipeGraph::for_planet(PlanetType::A);
+        assert!(graph.recipes().is_empty());
+        assert!(graph.completable().is_empty());
+    }
+
+    #[test]
+    fn graph_matches_the_planet() {
+        let planet = spawn_planet();
+        register_explorer_with_planet(&planet, 1);
+        let _ = planet
+            .snd_exp_to_planet
+            .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id: 1 });
+        let recipes = match planet.recv_explorer_msg() {
+            PlanetToExplorer::SupportedCombinationResponse { combination_list } => combination_list,
+            other => panic!("expected SupportedCombinationResponse, got {:?}", other),
+        };
+        let graph = RecipeGraph::for_planet(PlanetType::C);
+        assert_eq!(recipes.len(), graph.recipes().len());
+        assert!(
+            graph
+                .recipes()
+                .iter()
+                .all(|recipe| recipes.contains(recipe))
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let dot = RecipeGraph::for_planet(PlanetType::C).to_dot();
+        assert!(dot.starts_with("digraph recipes {"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert!(dot.contains("\"Carbon\" [shape=ellipse, style=solid];"));
+        assert!(dot.contains("\"Silicon\" [shape=ellipse, style=dashed];"));
+        assert!(dot.contains("\"Water\" -> \"Life\";"));
+        assert!(dot.contains("\"Carbon\" -> \"Diamond\";"));
+        assert_eq!(dot.matches("->").count(), 11);
+
+        let partial =
+            RecipeGraph::new([BasicResourceType::Carbon], [ComplexResourceType::Life]).to_dot();
+        assert!(partial.contains("\"Water\" [shape=box, style=dashed];"));
+    }
+}
+
+mod decision_explanations {
+    use super::*;
+    use crate::{Callback, ExplanationConfig, Outcome, PlanetConfig, PlanetError};
+
+    #[test]
+    fn asteroids_explain_the_rocket() {
+        let ai = planet::PlanetAI::new();
+        let explanations = ai.explanation_handle();
+        let planet = spawn_planet_with_ai(ai);
+        charge_planet_with_sunrays(&planet, 1);
+
+        send_asteroid(&planet);
+        let deflected = explanations.last(Callback::Asteroid).unwrap();
+        assert_eq!(deflected.outcome, Outcome::Deflected);
+        assert_eq!(deflected.observed.charged_cells, 1);
+        assert!(!deflected.observed.rocket);
+        assert!(
+            deflected
+                .fired
+                .iter()
+                .any(|fired| fired.rule.ends_with("build_rocket"))
+        );
+
+        send_asteroid(&planet);
+        let hit = explanations.last(Callback::Asteroid).unwrap();
+        assert_eq!(hit.outcome, Outcome::Hit);
+        assert_eq!(hit.observed.charged_cells, 0);
+        assert!(
+            hit.to_string()
+                .starts_with("Asteroid: Hit (cells 0/1 charged, no rocket")
+        );
+    }
+
+    #[test]
+    fn refusals_name_the_error_and_the_explorer() {
+        let ai = planet::PlanetAI::new();
+        let explanations = ai.explanation_handle();
+        let planet = spawn_planet_with_ai(ai);
+        register_explorer_with_planet(&planet, 1);
+
+        assert!(get_basic_resource(&planet, 1, BasicResourceType::Carbon).is_none());
+        let refused = explanations.of_explorer(1).pop().unwrap();
+        assert_eq!(refused.callback, Callback::ExplorerRequest);
+        assert_eq!(refused.outcome, Outcome::Refused(PlanetError::NoEnergy));
+        assert_eq!(refused.observed.explorers, 1);
+
+        let arrival = &explanations.of_explorer(1)[0];
+        assert_eq!(arrival.callback, Callback::ExplorerArrival);
+        assert_eq!(arrival.outcome, Outcome::Applied);
+
+        charge_planet_with_sunrays(&planet, 1);
+        assert!(get_basic_resource(&planet, 1, BasicResourceType::Carbon).is_some());
+        assert_eq!(
+            explanations.of_explorer(1).pop().unwrap().outcome,
+            Outcome::Served
+        );
+    }
+
+    #[test]
+    fn the_ring_keeps_the_latest() {
+        let ai = planet::PlanetAI::with_config(PlanetConfig {
+            explanations: ExplanationConfig { capacity: 3 },
+            ..PlanetConfig::default()
+        });
+        let explanations = ai.explanation_handle();
+        let handle = ai.report_handle();
+        let planet = spawn_planet_with_ai(ai);
+        charge_planet_with_sunrays(&planet, 5);
+
+        let kept = explanations.snapshot();
+        assert_eq!(kept.len(), 3);
+        assert!(kept.iter().all(|kept| kept.callback == Callback::Sunray));
+        assert_eq!(kept[2].outcome, Outcome::Wasted);
+
+        let published = report(&planet, &handle).explanations;
+        assert_eq!(published.len(), 3);
+        assert_eq!(
+            published.last().unwrap().callback,
+            Callback::InternalStateRequest
+        );
+    }
+}
+
+mod energy_forecast {
+    use super::*;
+    use crate::{EnergyForecast, FakeClock};
+
+    fn spawn_with_clock() -> (TestContext, crate::ReportHandle, FakeClock) {
+        let clock = FakeClock::new();
+        let ai = planet::PlanetAI::new().with_clock(clock.clone());
+        let handle = ai.report_handle();
+        (spawn_planet_with_ai(ai), handle, clock)
+    }
+
+    fn forecast(planet: &TestContext, handle: &crate::ReportHandle) -> EnergyForecast {
+        report(planet, handle).energy_forecast
+    }
+
+    #[test]
+    fn eta_counts_down_to_the_next_sunray() {
+        let (planet, handle, clock) = spawn_with_clock();
+        register_explorer_with_planet(&planet, 1);
+        charge_planet_with_sunrays(&planet, 1);
+        clock.advance(Duration::from_secs(10));
+        // The second sunray builds the rocket and charges the cell again
+        charge_planet_with_sunrays(&planet, 1);
+        assert_eq!(forecast(&planet, &handle).eta, Some(Duration::ZERO));
+
+        assert!(get_basic_resource(&planet, 1, BasicResourceType::Carbon).is_some());
+        clock.advance(Duration::from_secs(4));
+        let forecast = forecast(&planet, &handle);
+        assert_eq!(forecast.free_cells, 0);
+        assert_eq!(forecast.sunrays_needed, 1);
+        assert_eq!(forecast.sunray_interval, Some(Duration::from_secs(10)));
+        assert_eq!(forecast.sunrays_per_minute(), Some(6.0));
+        assert_eq!(forecast.eta, Some(Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn the_rocket_cell_is_not_free() {
+        let (planet, handle, clock) = spawn_with_clock();
+        register_explorer_with_planet(&planet, 1);
+        charge_planet_with_sunrays(&planet, 1);
+        assert!(get_basic_resource(&planet, 1, BasicResourceType::Carbon).is_some());
+        assert!(!send_asteroid(&planet));
+        clock.advance(Duration::from_secs(10));
+        charge_planet_with_sunrays(&planet, 1);
+
+        // After the hit, the charged cell is kept to build a rocket
+        clock.advance(Duration::from_secs(3));
+        let forecast = forecast(&planet, &handle);
+        assert_eq!(forecast.free_cells, 0);
+        assert_eq!(forecast.sunrays_needed, 1);
+        assert_eq!(forecast.eta, Some(Duration::from_secs(7)));
+        assert!(get_basic_resource(&planet, 1, BasicResourceType::Carbon).is_none());
+    }
+
+    #[test]
+    fn warnings_lapse_on_the_planet_clock() {
+        let (planet, handle, clock) = spawn_with_clock();
+        register_explorer_with_planet(&planet, 1);
+        assert!(!send_asteroid(&planet));
+        charge_planet_with_sunrays(&planet, 1);
+        assert_eq!(forecast(&planet, &handle).free_cells, 0);
+
+        // The default warning lives 30 s, however long the test really took
+        clock.advance(Duration::from_secs(31));
+        let report = report(&planet, &handle);
+        assert!(!report.pending_warning);
+        assert_eq!(report.energy_forecast.free_cells, 1);
+        assert!(get_basic_resource(&planet, 1, BasicResourceType::Carbon).is_some());
+    }
+
+    #[test]
+    fn the_interval_is_averaged_and_overdue_sunrays_are_due_now() {
+        let (planet, handle, clock) = spawn_with_clock();
+        assert_eq!(forecast(&planet, &handle).eta, None);
+        for gap in [10, 20] {
+            charge_planet_with_sunrays(&planet, 1);
+            clock.advance(Duration::from_secs(gap));
+        }
+        charge_planet_with_sunrays(&planet, 1);
+        register_explorer_with_planet(&planet, 1);
+        assert!(get_basic_resource(&planet, 1, BasicResourceType::Carbon).is_some());
+
+        let averaged = forecast(&planet, &handle);
+        assert_eq!(averaged.sunray_interval, Some(Duration::from_secs(15)));
+        assert_eq!(averaged.eta, Some(Duration::from_secs(15)));
+        clock.advance(Duration::from_secs(60));
+        let overdue = forecast(&planet, &handle);
+        assert_eq!(overdue.eta, Some(Duration::ZERO));
+    }
+}
+
+mod parameter_tuning {
+    use super::*;
+    use crate::{
+        AsteroidPolicy, BanditConfig, Knob, OverflowPolicy, PlanetConfig, ReputationConfig, Search,
+        StreamConfig, SunrayPolicy, Tuner,
+    };
+    use std::collections::HashSet;
+
+    fn tuner() -> Tuner {
+        let load = StreamConfig {
+            ticks: 40,
+            ..StreamConfig::default()
+        };
+        Tuner::new(load, 0..2)
+    }
+
+    #[test]
+    fn config_files_set_knobs_over_the_defaults() {
+        let config =
+            PlanetConfig::parse("# tuned\nstock_overflow 1\n\nreserve 2\nstock_cap 4").unwrap();
+        assert_eq!(config.overflow, OverflowPolicy::StockCarbon);
+        assert_eq!(config.alliance.reserve, 2);
+        assert_eq!(config.stock.cap, 4);
+        assert_eq!(config.reputation, PlanetConfig::default().reputation);
+        assert_eq!(PlanetConfig::parse(""), Ok(PlanetConfig::default()));
+
+        let err = PlanetConfig::parse("reserve 1\nrocket_first 1").unwrap_err();
+        assert_eq!(err, "line 2: unknown knob \"rocket_first\"");
+        let err = PlanetConfig::parse("stock_cap -3").unwrap_err();
+        assert_eq!(err, "line 1: invalid value \"-3\"");
+        let err = PlanetConfig::parse("stock_cap").unwrap_err();
+        assert_eq!(
+            err,
+            "line 1: expected a knob and a value, found \"stock_cap\""
+        );
+    }
+
+    #[test]
+    fn grid_search_saves_the_best_config() {
+        let report = tuner()
+            .knob(Knob::StockOverflow, [0, 1])
+            .knob(Knob::Reserve, [0, 1])
+            .run(Search::Grid, forge())
+            .unwrap();
+
+        assert_eq!(report.trials.len(), 4);
+        let candidates: HashSet<Vec<(Knob, u32)>> = report
+            .trials
+            .iter()
+            .map(|trial| trial.values.clone())
+            .collect();
+        assert_eq!(candidates.len(), 4);
+        assert!(
+            report
+                .trials
+                .windows(2)
+                .all(|pair| pair[0].score.score >= pair[1].score.score)
+        );
+        assert_eq!(report.best().score.streams, 2);
+
+        let knobs: Vec<Knob> = report.sensitivity.iter().map(|s| s.knob).collect();
+        assert_eq!(knobs.len(), 2);
+        assert!(knobs.contains(&Knob::StockOverflow) && knobs.contains(&Knob::Reserve));
+        assert!(report.sensitivity[0].spread >= report.sensitivity[1].spread);
+        assert!(report.sensitivity.iter().all(|s| s.means.len() == 2));
+
+        let path = std::env::temp_dir().join(format!("air_fryer_tuned_{}.cfg", std::process::id()));
+        report.save(&path).unwrap();
+        let loaded = PlanetConfig::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded, report.best().config);
+        assert!(report.to_string().contains("# sensitivity"));
+    }
+
+    #[test]
+    fn random_search_draws_distinct_candidates() {
+        let tuner = tuner()
+            .knob(Knob::StockOverflow, [0, 1])
+            .knob(Knob::StockCap, [2, 4, 8]);
+
+        let candidates = |trials, seed| -> Vec<Vec<(Knob, u32)>> {
+            let mut values: Vec<_> = tuner
+                .run(Search::Random { trials, seed }, forge())
+                .unwrap()
+                .trials
+                .into_iter()
+                .map(|trial| trial.values)
+                .collect();
+            values.sort_by_key(|values| values.iter().map(|(_, v)| *v).collect::<Vec<_>>());
+            values
+        };
+
+        let drawn = candidates(3, 9);
+        assert_eq!(drawn.len(), 3);
+        assert_eq!(drawn.iter().collect::<HashSet<_>>().len(), 3);
+        assert_eq!(candidates(3, 9), drawn, "same seed, same candidates");
+        // Never more than the grid
+        assert_eq!(candidates(20, 9).len(), 6);
+    }
+
+    /// Repeated values used to be counted as distinct candidates, so the draw never ended
+    #[test]
+    fn repeated_values_are_tried_once() {
+        let report = tuner()
+            .knob(Knob::StockCap, [4, 4])
+            .run(Search::Random { trials: 2, seed: 0 }, forge())
+            .unwrap();
+        assert_eq!(report.trials.len(), 1);
+        assert_eq!(report.sensitivity[0].means.len(), 1);
+    }
+
+    #[test]
+    fn rocket_policy_plays_a_fixed_arm() {
+        let mut config = PlanetConfig::default();
+        assert_eq!(Knob::RocketPolicy.get(&config), 0);
+        for value in [3, 1, 4, 2, 0] {
+            Knob::RocketPolicy.set(&mut config, value);
+            assert_eq!(Knob::RocketPolicy.get(&config), value);
+        }
+        assert_eq!(config, PlanetConfig::default());
+
+        config.bandit = BanditConfig::fixed(SunrayPolicy::Hoard, AsteroidPolicy::BuildOnDemand);
+        assert_eq!(Knob::RocketPolicy.get(&config), 1);
+        let config = PlanetConfig::parse("rocket_policy 3").unwrap();
+        assert_eq!(
+            config.bandit,
+            BanditConfig::fixed(SunrayPolicy::RocketFirst, AsteroidPolicy::BuildOnDemand)
+        );
+
+        let err = PlanetConfig::parse("rocket_policy 5").unwrap_err();
+        assert_eq!(err, "line 1: invalid value \"5\"");
+        let err = tuner()
+            .knob(Knob::RocketPolicy, [0, 9])
+            .run(Search::Grid, forge())
+            .unwrap_err();
+        assert_eq!(err, "invalid value 9 for rocket_policy");
+    }
+
+    #[test]
+    fn knobs_are_searched_once() {
+        let err = tuner()
+            .knob(Knob::StockCap, [4])
+            .knob(Knob::Reserve, [0])
+            .knob(Knob::StockCap, [8])
+            .run(Search::Grid, forge())
+            .unwrap_err();
+        assert_eq!(err, "stock_cap searched twice");
+    }
+
+    #[test]
+    fn reports_of_a_custom_base_are_not_saved() {
+        let base = PlanetConfig {
+            reputation: ReputationConfig {
+                min_score: 0.5,
+                ..ReputationConfig::default()
+            },
+            ..PlanetConfig::default()
+        };
+        let report = tuner()
+            .with_base(base)
+            .knob(Knob::StockCap, [4])
+            .run(Search::Grid, forge())
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("air_fryer_base_{}.cfg", std::process::id()));
+        let err = report.save(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn knobs_need_values() {
+        let err = tuner()
+            .knob(Knob::StockCap, [])
+            .run(Search::Grid, forge())
+            .unwrap_err();
+        assert_eq!(err, "no value to try for stock_cap");
+    }
+}
@@ -0,0 +1,219 @@
+//! Threat intelligence shared by the AirFryer planets of one process.
+//!
+//! Every planet joining the same [`ThreatBus`], through
+//! [`create_planet_with_threat_bus`](crate::create_planet_with_threat_bus) or
+//! [`PlanetConfig::threat_bus`](crate::PlanetConfig::threat_bus), publishes its
+//! asteroid sightings and whether its rocket is ready. The siblings read the
+//! bus whenever they handle a message: a sighting warns their explorers right
+//! away when they have no rocket, keeps a charged cell for one, and sets the
+//! `threat` fact the [default rules](crate::DEFAULT_RULES) build it on.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// How many events the bus keeps for the planets that didn't read them yet.
+const CAPACITY: usize = 256;
+
+/// Something a planet published on the [`ThreatBus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreatEvent {
+    /// An asteroid hit the planet.
+    Asteroid {
+        planet: usize,
+        at: Instant,
+        /// The planet had a rocket.
+        deflected: bool,
+    },
+    /// The planet built or launched its rocket.
+    Rocket { planet: usize, ready: bool },
+}
+
+impl ThreatEvent {
+    /// Bus member that published the event, see [`ThreatBus::members`].
+    pub fn planet(&self) -> usize {
+        match self {
+            ThreatEvent::Asteroid { planet, .. } | ThreatEvent::Rocket { planet, .. } => *planet,
+        }
+    }
+}
+
+#[derive(Default)]
+struct BusState {
+    members: usize,
+    /// Sequence number of the first event in `events`.
+    first: u64,
+    events: VecDeque<ThreatEvent>,
+    rockets: Vec<bool>,
+}
+
+/// Cloneable in-process bus shared by sibling planets.
+///
+/// Put a clone in the [`PlanetConfig`](crate::PlanetConfig) of every planet
+/// that should take part; each [`PlanetAI`](crate::PlanetAI) joins the bus
+/// when it is created, as the next member. Two configs are equal when they
+/// hold the same bus.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{PlanetAI, PlanetConfig, ThreatBus};
+///
+/// let bus = ThreatBus::default();
+/// let config = PlanetConfig {
+///     threat_bus: Some(bus.clone()),
+///     ..PlanetConfig::default()
+/// };
+/// let first = PlanetAI::with_config(config.clone());
+/// let second = PlanetAI::with_config(config);
+/// assert_eq!(bus.members(), 2);
+/// ```
+#[derive(Clone)]
+pub struct ThreatBus {
+    state: Arc<Mutex<BusState>>,
+    window: Duration,
+}
+
+impl ThreatBus {
+    /// A bus on which sightings stay relevant for `window`.
+    pub fn new(window: Duration) -> ThreatBus {
+        ThreatBus {
+            state: Arc::default(),
+            window,
+        }
+    }
+
+    /// How long a sighting makes the siblings prepare.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Planets that joined the bus.
+    pub fn members(&self) -> usize {
+        self.lock().members
+    }
+
+    /// Whether each member has a rocket ready, by member.
+    pub fn rockets(&self) -> Vec<bool> {
+        self.lock().rockets.clone()
+    }
+
+    /// The events still kept on the bus, oldest first.
+    pub fn events(&self) -> Vec<ThreatEvent> {
+        self.lock().events.iter().copied().collect()
+    }
+
+    pub(crate) fn join(&self) -> ThreatLink {
+        let mut state = self.lock();
+        let member = state.members;
+        state.members += 1;
+        state.rockets.push(false);
+        ThreatLink {
+            bus: self.clone(),
+            member,
+            cursor: state.first + state.events.len() as u64,
+            last_sighting: None,
+        }
+    }
+
+    fn publish(&self, event: ThreatEvent) {
+        let mut state = self.lock();
+        if let ThreatEvent::Rocket { planet, ready } = event {
+            state.rockets[planet] = ready;
+        }
+        if state.events.len() == CAPACITY {
+            state.events.pop_front();
+            state.first += 1;
+        }
+        state.events.push_back(event);
+    }
+
+    // Events are plain data, so a poisoned mutex is still safe to use
+    fn lock(&self) -> MutexGuard<'_, BusState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for ThreatBus {
+    /// A bus with a one minute window, like the `asteroid_rate` rule fact.
+    fn default() -> Self {
+        ThreatBus::new(Duration::from_secs(60))
+    }
+}
+
+impl fmt::Debug for ThreatBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThreatBus")
+            .field("members", &self.members())
+            .field("window", &self.window)
+            .finish()
+    }
+}
+
+impl PartialEq for ThreatBus {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state) && self.window == other.window
+    }
+}
+
+/// Membership of a planet in a [`ThreatBus`].
+pub(crate) struct ThreatLink {
+    bus: ThreatBus,
+    member: usize,
+    /// Sequence number of the next event to read.
+    cursor: u64,
+    last_sighting: Option<Instant>,
+}
+
+impl ThreatLink {
+    pub(crate) fn asteroid(&mut self, now: Instant, deflected: bool) {
+        self.bus.publish(ThreatEvent::Asteroid {
+            planet: self.member,
+            at: now,
+            deflected,
+        });
+    }
+
+    /// Publishes the rocket readiness when it changed.
+    pub(crate) fn rocket(&mut self, ready: bool) {
+        if self.bus.rockets()[self.member] != ready {
+            self.bus.publish(ThreatEvent::Rocket {
+                planet: self.member,
+                ready,
+            });
+        }
+    }
+
+    /// Reads the sightings published by the siblings since the last call.
+    ///
+    /// Returns how many were still within the window.
+    pub(crate) fn sightings(&mut self, now: Instant) -> usize {
+        let events = {
+            let state = self.bus.lock();
+            // Events dropped before this planet could read them are lost
+            let skip = self.cursor.saturating_sub(state.first) as usize;
+            self.cursor = state.first + state.events.len() as u64;
+            state.events.iter().skip(skip).copied().collect::<Vec<_>>()
+        };
+        let mut fresh = 0;
+        for event in events {
+            if let ThreatEvent::Asteroid { planet, at, .. } = event
+                && planet != self.member
+                && now.duration_since(at) <= self.bus.window
+            {
+                self.last_sighting = Some(self.last_sighting.map_or(at, |last| last.max(at)));
+                fresh += 1;
+            }
+        }
+        fresh
+    }
+
+    /// Whether a sibling sighted an asteroid within the window.
+    pub(crate) fn threatened(&self, now: Instant) -> bool {
+        self.last_sighting
+            .is_some_and(|at| now.duration_since(at) <= self.bus.window)
+    }
+}
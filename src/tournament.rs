@@ -0,0 +1,577 @@
+//! Strategy tournament.
+//!
+//! A [`Tournament`] plays several [`PlanetConfig`]s against the same event
+//! streams: every entrant gets a fresh AirFryer planet, created with
+//! [`create_planet`], and the very same sunrays, asteroids and explorer
+//! requests. The entrants are then scored on survival, requests served,
+//! energy wasted and warnings delivered, and ranked in a [`TournamentReport`].
+//!
+//! Streams are either generated from a seed or recorded as plain text, one
+//! event per line:
+//!
+//! ```text
+//! sunray
+//! carbon 2
+//! query 0
+//! asteroid
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use common_game::{
+    components::{forge::Forge, planet as common_planet, resource::BasicResourceType},
+    protocols::{
+        orchestrator_planet::{OrchestratorToPlanet, PlanetToOrchestrator},
+        planet_explorer::{ExplorerToPlanet, PlanetToExplorer},
+    },
+    utils::ID,
+};
+use crossbeam_channel::{Receiver, Sender, unbounded};
+
+use crate::rng::Rng;
+use crate::{PlanetAI, PlanetConfig, ReportHandle, create_planet};
+
+const PLANET_ID: ID = 0;
+
+/// Something happening to the planet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Sunray,
+    /// An asteroid: without a rocket the planet is destroyed and the stream ends
+    /// at the next sunray or asteroid, the explorer requests in between being
+    /// still answered.
+    Asteroid,
+    /// The explorer with the given index asks for a unit of Carbon.
+    Carbon {
+        explorer: usize,
+    },
+    /// The explorer with the given index asks for the supported combinations.
+    Query {
+        explorer: usize,
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Sunray => write!(f, "sunray"),
+            Event::Asteroid => write!(f, "asteroid"),
+            Event::Carbon { explorer } => write!(f, "carbon {}", explorer),
+            Event::Query { explorer } => write!(f, "query {}", explorer),
+        }
+    }
+}
+
+/// Parameters of a generated [`EventStream`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamConfig {
+    pub ticks: u64,
+    /// Explorers sending requests, all on the planet for the whole stream.
+    pub explorers: usize,
+    /// Probability of a sunray in a tick.
+    pub sunray_probability: f64,
+    /// Probability of an asteroid in a tick.
+    pub asteroid_probability: f64,
+    /// Ticks at the beginning of the stream without asteroids.
+    pub asteroid_grace_ticks: u64,
+    /// Probability of a Carbon request in a tick.
+    pub carbon_probability: f64,
+    /// Probability of a supported combinations query in a tick.
+    pub query_probability: f64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        StreamConfig {
+            ticks: 200,
+            explorers: 8,
+            sunray_probability: 0.5,
+            asteroid_probability: 0.02,
+            asteroid_grace_ticks: 20,
+            carbon_probability: 0.3,
+            query_probability: 0.1,
+        }
+    }
+}
+
+/// A sequence of events played against every entrant.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventStream {
+    pub events: Vec<Event>,
+}
+
+impl EventStream {
+    /// Generates a stream; the same seed and config always give the same events.
+    pub fn generate(seed: u64, config: &StreamConfig) -> EventStream {
+        let mut rng = Rng::new(seed);
+        let mut events = Vec::new();
+        for tick in 0..config.ticks {
+            if rng.chance(config.sunray_probability) {
+                events.push(Event::Sunray);
+            }
+            if config.explorers > 0 {
+                if rng.chance(config.carbon_probability) {
+                    let explorer = rng.below(config.explorers);
+                    events.push(Event::Carbon { explorer });
+                }
+                if rng.chance(config.query_probability) {
+                    let explorer = rng.below(config.explorers);
+                    events.push(Event::Query { explorer });
+                }
+            }
+            if tick >= config.asteroid_grace_ticks && rng.chance(config.asteroid_probability) {
+                events.push(Event::Asteroid);
+            }
+        }
+        EventStream { events }
+    }
+
+    /// Parses a recorded stream, see the [module documentation](self).
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<EventStream, String> {
+        let mut events = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let event = match fields[..] {
+                ["sunray"] => Event::Sunray,
+                ["asteroid"] => Event::Asteroid,
+                ["carbon", explorer] => Event::Carbon {
+                    explorer: parse_explorer(index, explorer)?,
+                },
+                ["query", explorer] => Event::Query {
+                    explorer: parse_explorer(index, explorer)?,
+                },
+                _ => return Err(format!("line {}: unknown event {:?}", index + 1, line)),
+            };
+            events.push(event);
+        }
+        Ok(EventStream { events })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<EventStream, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("can't read {}: {}", path.display(), err))?;
+        EventStream::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    /// Number of explorers the stream needs on the planet.
+    pub fn explorers(&self) -> usize {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Carbon { explorer } | Event::Query { explorer } => Some(explorer + 1),
+                Event::Sunray | Event::Asteroid => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+fn parse_explorer(index: usize, explorer: &str) -> Result<usize, String> {
+    explorer
+        .parse()
+        .map_err(|_| format!("line {}: invalid explorer {:?}", index + 1, explorer))
+}
+
+impl fmt::Display for EventStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+/// A planet configuration taking part in the tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entrant {
+    pub name: String,
+    pub config: PlanetConfig,
+}
+
+impl Entrant {
+    pub fn new(name: impl Into<String>, config: PlanetConfig) -> Entrant {
+        Entrant {
+            name: name.into(),
+            config,
+        }
+    }
+}
+
+/// Points given for every scored outcome.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreWeights {
+    /// Per stream survived until its end.
+    pub survival: f64,
+    /// Per explorer request served.
+    pub served: f64,
+    /// Per sunray wasted, usually negative.
+    pub wasted: f64,
+    /// Per warning delivered to an explorer.
+    pub warning: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            survival: 100.0,
+            served: 1.0,
+            wasted: -0.5,
+            warning: 5.0,
+        }
+    }
+}
+
+/// What an entrant achieved over all the streams.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntrantScore {
+    pub name: String,
+    /// Streams played.
+    pub streams: u32,
+    /// Streams survived until their end.
+    pub survived: u32,
+    pub asteroids: u32,
+    /// Asteroids deflected with a rocket.
+    pub deflected: u32,
+    /// Carbon requests served.
+    pub served: u32,
+    /// Carbon requests refused.
+    pub refused: u32,
+    /// Sunrays wasted, as counted by the planet.
+    pub wasted: u32,
+    /// Warnings delivered to the explorers, as counted by the planet.
+    pub warnings: u32,
+    pub score: f64,
+}
+
+impl EntrantScore {
+    fn new(name: &str) -> EntrantScore {
+        EntrantScore {
+            name: name.to_string(),
+            streams: 0,
+            survived: 0,
+            asteroids: 0,
+            deflected: 0,
+            served: 0,
+            refused: 0,
+            wasted: 0,
+            warnings: 0,
+            score: 0.0,
+        }
+    }
+
+    fn rate(&mut self, weights: &ScoreWeights) {
+        self.score = weights.survival * self.survived as f64
+            + weights.served * self.served as f64
+            + weights.wasted * self.wasted as f64
+            + weights.warning * self.warnings as f64;
+    }
+}
+
+/// Outcome of a [`Tournament::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentReport {
+    /// The entrants, best score first.
+    pub ranking: Vec<EntrantScore>,
+}
+
+impl TournamentReport {
+    pub fn winner(&self) -> Option<&EntrantScore> {
+        self.ranking.first()
+    }
+}
+
+impl fmt::Display for TournamentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4} {:<20} {:>8} {:>9} {:>9} {:>6} {:>7} {:>6} {:>8} {:>9}",
+            "rank",
+            "entrant",
+            "survived",
+            "asteroids",
+            "deflected",
+            "served",
+            "refused",
+            "wasted",
+            "warnings",
+            "score"
+        )?;
+        for (rank, s) in self.ranking.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4} {:<20} {:>8} {:>9} {:>9} {:>6} {:>7} {:>6} {:>8} {:>9.1}",
+                rank + 1,
+                s.name,
+                format!("{}/{}", s.survived, s.streams),
+                s.asteroids,
+                s.deflected,
+                s.served,
+                s.refused,
+                s.wasted,
+                s.warnings,
+                s.score
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Several planet configurations played against the same event streams.
+///
+/// # Example
+///
+/// ```rust
+/// use air_fryer::{Entrant, EventStream, PlanetConfig, StreamConfig, Tournament};
+/// use common_game::components::forge::Forge;
+///
+/// let forge = Forge::new().unwrap();
+/// let streams = (0..3)
+///     .map(|seed| EventStream::generate(seed, &StreamConfig::default()))
+///     .collect();
+/// let entrants = vec![Entrant::new("default", PlanetConfig::default())];
+/// let report = Tournament::new(entrants, streams).run(&forge).unwrap();
+/// println!("{}", report);
+/// ```
+pub struct Tournament {
+    entrants: Vec<Entrant>,
+    streams: Vec<EventStream>,
+    weights: ScoreWeights,
+    timeout: Duration,
+}
+
+impl Tournament {
+    pub fn new(entrants: Vec<Entrant>, streams: Vec<EventStream>) -> Tournament {
+        Tournament {
+            entrants,
+            streams,
+            weights: ScoreWeights::default(),
+            timeout: Duration::from_millis(500),
+        }
+    }
+
+    pub fn with_weights(mut self, weights: ScoreWeights) -> Tournament {
+        self.weights = weights;
+        self
+    }
+
+    /// How long to wait for any planet response before giving up.
+    pub fn with_timeout(mut self, timeout: Duration) -> Tournament {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Plays every stream against every entrant, one planet at a time.
+    ///
+    /// The [`Forge`] is borrowed because only one can exist in a process.
+    pub fn run(&self, forge: &Forge) -> Result<TournamentReport, String> {
+        if self.entrants.is_empty() {
+            return Err("a tournament needs at least one entrant".to_string());
+        }
+        let mut ranking = Vec::with_capacity(self.entrants.len());
+        for entrant in &self.entrants {
+            let mut score = EntrantScore::new(&entrant.name);
+            for stream in &self.streams {
+                let mut game = Game::start(&entrant.config, stream.explorers(), self.timeout)?;
+                let result = game.play(stream, forge, &mut score);
+                game.shutdown();
+                result?;
+            }
+            score.rate(&self.weights);
+            ranking.push(score);
+        }
+        // Equal scores keep the entrants order
+        ranking.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(TournamentReport { ranking })
+    }
+}
+
+// =========================================================================
+// ORCHESTRATOR SIDE
+// =========================================================================
+
+struct Explorer {
+    id: ID,
+    inbox: Receiver<PlanetToExplorer>,
+}
+
+/// A single entrant playing a single stream.
+struct Game {
+    to_planet: Sender<OrchestratorToPlanet>,
+    from_planet: Receiver<PlanetToOrchestrator>,
+    to_planet_explorers: Sender<ExplorerToPlanet>,
+    thread: Option<JoinHandle<()>>,
+    report: ReportHandle,
+    explorers: Vec<Explorer>,
+    timeout: Duration,
+    alive: bool,
+}
+
+impl Game {
+    fn start(config: &PlanetConfig, explorers: usize, timeout: Duration) -> Result<Game, String> {
+        let (to_planet, rx_from_orc) = unbounded();
+        let (tx_to_orc, from_planet) = unbounded();
+        let (to_planet_explorers, rx_from_explorers) = unbounded();
+
+        let ai = PlanetAI::with_config(config.clone());
+        let report = ai.report_handle();
+        let mut planet = create_planet(
+            PLANET_ID,
+            common_planet::PlanetType::C,
+            ai,
+            (rx_from_orc, tx_to_orc),
+            rx_from_explorers,
+        )?;
+        let thread = thread::spawn(move || {
+            if let Err(err) = planet.run() {
+                log::error!("tournament planet stopped with an error: {}", err);
+            }
+        });
+
+        let mut game = Game {
+            to_planet,
+            from_planet,
+            to_planet_explorers,
+            thread: Some(thread),
+            report,
+            explorers: Vec::with_capacity(explorers),
+            timeout,
+            alive: true,
+        };
+        game.request(OrchestratorToPlanet::StartPlanetAI)?;
+
+        // Explorer IDs follow the planet ID
+        for i in 0..explorers {
+            let (sender, inbox) = unbounded();
+            let id = PLANET_ID + 1 + i as ID;
+            game.request(OrchestratorToPlanet::IncomingExplorerRequest {
+                explorer_id: id,
+                new_sender: sender,
+            })?;
+            game.explorers.push(Explorer { id, inbox });
+        }
+        Ok(game)
+    }
+
+    fn play(
+        &mut self,
+        stream: &EventStream,
+        forge: &Forge,
+        score: &mut EntrantScore,
+    ) -> Result<(), String> {
+        score.streams += 1;
+        let mut hit = false;
+        for event in &stream.events {
+            // The planet is destroyed before the orchestrator sends anything else
+            if hit && matches!(event, Event::Sunray | Event::Asteroid) {
+                break;
+            }
+            match *event {
+                Event::Sunray => {
+                    self.request(OrchestratorToPlanet::Sunray(forge.generate_sunray()))?;
+                }
+                Event::Asteroid => {
+                    score.asteroids += 1;
+                    let asteroid = OrchestratorToPlanet::Asteroid(forge.generate_asteroid());
+                    match self.request(asteroid)? {
+                        PlanetToOrchestrator::AsteroidAck {
+                            rocket: Some(_), ..
+                        } => score.deflected += 1,
+                        PlanetToOrchestrator::AsteroidAck { rocket: None, .. } => hit = true,
+                        _ => return Err("the planet didn't acknowledge the asteroid".to_string()),
+                    }
+                }
+                Event::Carbon { explorer } => {
+                    if self.carbon(explorer)? {
+                        score.served += 1;
+                    } else {
+                        score.refused += 1;
+                    }
+                }
+                Event::Query { explorer } => self.query(explorer)?,
+            }
+        }
+
+        self.request(OrchestratorToPlanet::InternalStateRequest)?;
+        let report = self.report.snapshot();
+        score.wasted += report.sunrays.wasted;
+        score.warnings += report
+            .warnings
+            .iter()
+            .map(|warning| warning.delivered.len() as u32)
+            .sum::<u32>();
+        if hit {
+            self.request(OrchestratorToPlanet::KillPlanet)?;
+            self.alive = false;
+        } else {
+            score.survived += 1;
+        }
+        Ok(())
+    }
+
+    /// Sends a message and waits for the planet acknowledgement.
+    fn request(&self, msg: OrchestratorToPlanet) -> Result<PlanetToOrchestrator, String> {
+        self.to_planet
+            .send(msg)
+            .map_err(|_| "the planet is unreachable".to_string())?;
+        self.from_planet
+            .recv_timeout(self.timeout)
+            .map_err(|_| "the planet didn't answer in time".to_string())
+    }
+
+    /// Sends an explorer request and waits for the answer.
+    fn ask(
+        &self,
+        explorer: usize,
+        msg: impl FnOnce(ID) -> ExplorerToPlanet,
+    ) -> Result<PlanetToExplorer, String> {
+        let explorer = &self.explorers[explorer];
+        self.to_planet_explorers
+            .send(msg(explorer.id))
+            .map_err(|_| "the planet is unreachable".to_string())?;
+        explorer
+            .inbox
+            .recv_timeout(self.timeout)
+            .map_err(|_| format!("explorer {} got no answer in time", explorer.id))
+    }
+
+    fn carbon(&self, explorer: usize) -> Result<bool, String> {
+        let response = self.ask(explorer, |explorer_id| {
+            ExplorerToPlanet::GenerateResourceRequest {
+                explorer_id,
+                resource: BasicResourceType::Carbon,
+            }
+        })?;
+        Ok(matches!(
+            response,
+            PlanetToExplorer::GenerateResourceResponse { resource: Some(_) }
+        ))
+    }
+
+    /// Asks for the supported combinations, which may carry a warning.
+    fn query(&self, explorer: usize) -> Result<(), String> {
+        self.ask(explorer, |explorer_id| {
+            ExplorerToPlanet::SupportedCombinationRequest { explorer_id }
+        })?;
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        if self.alive {
+            let _ = self.request(OrchestratorToPlanet::KillPlanet);
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
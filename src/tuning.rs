@@ -0,0 +1,458 @@
+//! Automated tuning of the [`PlanetConfig`] knobs.
+//!
+//! A [`Tuner`] plays candidate configurations against seeded event streams
+//! with a [`Tournament`], searching a grid of knob values or a random sample
+//! of it. The [`TuningReport`] keeps every trial, the best one and how much
+//! each knob moves the score; saved to a file, it loads back as a
+//! [`PlanetConfig`] with [`PlanetConfig::load`].
+//!
+//! ```text
+//! # air_fryer tuned config: score 212.0 over 5 streams
+//! stock_overflow 1
+//! stock_cap 8
+//! # sensitivity: mean score per value (spread)
+//! # stock_overflow: 0 => 180.5, 1 => 206.0 (25.5)
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use common_game::components::forge::Forge;
+
+use crate::PlanetConfig;
+use crate::bandit::{BanditConfig, BanditStats};
+use crate::overflow::OverflowPolicy;
+use crate::rng::Rng;
+use crate::tournament::{
+    Entrant, EntrantScore, EventStream, ScoreWeights, StreamConfig, Tournament,
+};
+
+/// A numeric knob of the [`PlanetConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Knob {
+    /// [`AllianceConfig::reserve`](crate::AllianceConfig::reserve), in cells.
+    Reserve,
+    /// 1 for [`OverflowPolicy::StockCarbon`], 0 for [`OverflowPolicy::Waste`].
+    StockOverflow,
+    /// [`StockConfig::cap`](crate::StockConfig::cap).
+    StockCap,
+    /// [`ReputationConfig::max_requests`](crate::ReputationConfig::max_requests).
+    MaxRequests,
+    /// [`ReputationConfig::probation_requests`](crate::ReputationConfig::probation_requests).
+    ProbationRequests,
+    /// [`CreditConfig::initial_credits`](crate::CreditConfig::initial_credits).
+    InitialCredits,
+    /// [`CreditConfig::carbon_price`](crate::CreditConfig::carbon_price).
+    CarbonPrice,
+    /// Sunray and asteroid policies: 0 for the default [`BanditConfig`], then
+    /// a [`BanditConfig::fixed`] playing `hoard build_on_demand`,
+    /// `hoard ready_only`, `rocket_first build_on_demand` or
+    /// `rocket_first ready_only`, from 1 to 4.
+    RocketPolicy,
+}
+
+impl Knob {
+    pub const ALL: [Knob; 8] = [
+        Knob::Reserve,
+        Knob::StockOverflow,
+        Knob::StockCap,
+        Knob::MaxRequests,
+        Knob::ProbationRequests,
+        Knob::InitialCredits,
+        Knob::CarbonPrice,
+        Knob::RocketPolicy,
+    ];
+
+    /// Name of the knob in the config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Knob::Reserve => "reserve",
+            Knob::StockOverflow => "stock_overflow",
+            Knob::StockCap => "stock_cap",
+            Knob::MaxRequests => "max_requests",
+            Knob::ProbationRequests => "probation_requests",
+            Knob::InitialCredits => "initial_credits",
+            Knob::CarbonPrice => "carbon_price",
+            Knob::RocketPolicy => "rocket_policy",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Knob> {
+        Knob::ALL.into_iter().find(|knob| knob.name() == name)
+    }
+
+    pub fn get(&self, config: &PlanetConfig) -> u32 {
+        match self {
+            Knob::Reserve => config.alliance.reserve as u32,
+            Knob::StockOverflow => u32::from(config.overflow == OverflowPolicy::StockCarbon),
+            Knob::StockCap => config.stock.cap as u32,
+            Knob::MaxRequests => config.reputation.max_requests,
+            Knob::ProbationRequests => config.reputation.probation_requests,
+            Knob::InitialCredits => config.credits.initial_credits,
+            Knob::CarbonPrice => config.credits.carbon_price,
+            // Any other bandit reads as the default one
+            // Any other bandit reads as the default one
+            Knob::RocketPolicy => BanditStats::default()
+                .arms
+                .iter()
+                .position(|arm| BanditConfig::fixed(arm.sunray, arm.asteroid) == config.bandit)
+                .map_or(0, |arm| arm as u32 + 1),
+        }
+    }
+
+    /// Whether the knob can take the value, see [`set`](Self::set).
+    pub fn accepts(&self, value: u32) -> bool {
+        match self {
+            Knob::StockOverflow => value <= 1,
+            Knob::RocketPolicy => value as usize <= BanditStats::default().arms.len(),
+            _ => true,
+        }
+    }
+
+    /// Sets the knob; a value it doesn't [accept](Self::accepts) leaves the config as it is.
+    pub fn set(&self, config: &mut PlanetConfig, value: u32) {
+        if !self.accepts(value) {
+            return;
+        }
+        match self {
+            Knob::Reserve => config.alliance.reserve = value as usize,
+            Knob::StockOverflow => {
+                config.overflow = if value == 0 {
+                    OverflowPolicy::Waste
+                } else {
+                    OverflowPolicy::StockCarbon
+                }
+            }
+            Knob::StockCap => config.stock.cap = value as usize,
+            Knob::MaxRequests => config.reputation.max_requests = value,
+            Knob::ProbationRequests => config.reputation.probation_requests = value,
+            Knob::InitialCredits => config.credits.initial_credits = value,
+            Knob::CarbonPrice => config.credits.carbon_price = value,
+            Knob::RocketPolicy => {
+                config.bandit = match value.checked_sub(1) {
+                    Some(arm) => {
+                        let arm = BanditStats::default().arms[arm as usize];
+                        BanditConfig::fixed(arm.sunray, arm.asteroid)
+                    }
+                    None => BanditConfig::default(),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Knob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// How the [`Tuner`] picks the candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Search {
+    /// Every combination of the knob values.
+    Grid,
+    /// Up to `trials` distinct combinations drawn at random.
+    Random { trials: usize, seed: u64 },
+}
+
+/// A candidate configuration and its score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trial {
+    /// The value of every searched knob, in the order of the search.
+    pub values: Vec<(Knob, u32)>,
+    pub config: PlanetConfig,
+    pub score: EntrantScore,
+}
+
+/// How much a knob moves the score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensitivity {
+    pub knob: Knob,
+    /// Mean score of the trials with each value, in the order of the search.
+    pub means: Vec<(u32, f64)>,
+    /// Best mean minus worst mean.
+    pub spread: f64,
+}
+
+/// Outcome of a [`Tuner::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningReport {
+    /// Every trial, best score first.
+    pub trials: Vec<Trial>,
+    /// The knobs, most influential first.
+    pub sensitivity: Vec<Sensitivity>,
+}
+
+impl TuningReport {
+    pub fn best(&self) -> &Trial {
+        // A report always has at least one trial, see `Tuner::run`
+        &self.trials[0]
+    }
+
+    /// Writes the best config and the sensitivity summary, see [`PlanetConfig::load`].
+    ///
+    /// Only the knobs are written: a best config differing from the default
+    /// anywhere else, set with [`Tuner::with_base`], is refused with
+    /// [`io::ErrorKind::InvalidInput`] rather than saved incomplete.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let best = &self.best().config;
+        let mut saved = PlanetConfig::default();
+        for knob in Knob::ALL {
+            knob.set(&mut saved, knob.get(best));
+        }
+        if saved != *best {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the best config differs from the default outside the knobs",
+            ));
+        }
+        fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for TuningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let best = self.best();
+        writeln!(
+            f,
+            "# air_fryer tuned config: score {:.1} over {} streams",
+            best.score.score, best.score.streams
+        )?;
+        for knob in Knob::ALL {
+            writeln!(f, "{} {}", knob, knob.get(&best.config))?;
+        }
+        writeln!(f, "# sensitivity: mean score per value (spread)")?;
+        for sensitivity in &self.sensitivity {
+            let means: Vec<String> = sensitivity
+                .means
+                .iter()
+                .map(|(value, mean)| format!("{} => {:.1}", value, mean))
+                .collect();
+            writeln!(
+                f,
+                "# {}: {} ({:.1})",
+                sensitivity.knob,
+                means.join(", "),
+                sensitivity.spread
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Searches the knob values scoring best over seeded simulations.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use air_fryer::{Knob, PlanetConfig, Search, StreamConfig, Tuner};
+/// use common_game::components::forge::Forge;
+///
+/// let forge = Forge::new().unwrap();
+/// let report = Tuner::new(StreamConfig::default(), 0..5)
+///     .knob(Knob::StockOverflow, [0, 1])
+///     .knob(Knob::StockCap, [4, 8, 16])
+///     .run(Search::Grid, &forge)
+///     .unwrap();
+/// report.save("tuned.cfg").unwrap();
+///
+/// let config = PlanetConfig::load("tuned.cfg").unwrap();
+/// assert_eq!(config, report.best().config);
+/// ```
+pub struct Tuner {
+    knobs: Vec<(Knob, Vec<u32>)>,
+    base: PlanetConfig,
+    load: StreamConfig,
+    seeds: Vec<u64>,
+    weights: ScoreWeights,
+    timeout: Duration,
+}
+
+impl Tuner {
+    /// A tuner simulating one stream of the given load per seed.
+    pub fn new(load: StreamConfig, seeds: impl IntoIterator<Item = u64>) -> Tuner {
+        Tuner {
+            knobs: Vec::new(),
+            base: PlanetConfig::default(),
+            load,
+            seeds: seeds.into_iter().collect(),
+            weights: ScoreWeights::default(),
+            timeout: Duration::from_millis(500),
+        }
+    }
+
+    /// Adds a knob to the search, with the values to try; repeated values are tried once.
+    pub fn knob(mut self, knob: Knob, values: impl IntoIterator<Item = u32>) -> Tuner {
+        let mut seen = HashSet::new();
+        let values = values
+            .into_iter()
+            .filter(|value| seen.insert(*value))
+            .collect();
+        self.knobs.push((knob, values));
+        self
+    }
+
+    /// The config the knobs are set on, the default one otherwise.
+    ///
+    /// The report of a base differing from the default outside the knobs
+    /// can't be [saved](TuningReport::save).
+    pub fn with_base(mut self, base: PlanetConfig) -> Tuner {
+        self.base = base;
+        self
+    }
+
+    pub fn with_weights(mut self, weights: ScoreWeights) -> Tuner {
+        self.weights = weights;
+        self
+    }
+
+    /// How long to wait for any planet response before giving up.
+    pub fn with_timeout(mut self, timeout: Duration) -> Tuner {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Plays every candidate against the same streams.
+    ///
+    /// The [`Forge`] is borrowed because only one can exist in a process.
+    pub fn run(&self, search: Search, forge: &Forge) -> Result<TuningReport, String> {
+        for (i, (knob, values)) in self.knobs.iter().enumerate() {
+            if values.is_empty() {
+                return Err(format!("no value to try for {}", knob));
+            }
+            if let Some(value) = values.iter().find(|value| !knob.accepts(**value)) {
+                return Err(format!("invalid value {} for {}", value, knob));
+            }
+            // The second set would silently override the first one
+            if self.knobs[..i].iter().any(|(other, _)| other == knob) {
+                return Err(format!("{} searched twice", knob));
+            }
+        }
+        let candidates = match search {
+            Search::Grid => self.grid(),
+            Search::Random { trials, seed } => self.sample(trials, seed),
+        };
+        if candidates.is_empty() {
+            return Err("nothing to try".to_string());
+        }
+
+        let entrants = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, values)| Entrant::new(i.to_string(), self.config(values)))
+            .collect();
+        let streams = self
+            .seeds
+            .iter()
+            .map(|seed| EventStream::generate(*seed, &self.load))
+            .collect();
+        let ranking = Tournament::new(entrants, streams)
+            .with_weights(self.weights.clone())
+            .with_timeout(self.timeout)
+            .run(forge)?
+            .ranking;
+
+        // Entrants are named after their candidate
+        let trials: Vec<Trial> = ranking
+            .into_iter()
+            .map(|score| {
+                let index: usize = score.name.parse().expect("entrant named by index");
+                let values = &candidates[index];
+                Trial {
+                    values: self
+                        .knobs
+                        .iter()
+                        .map(|(knob, _)| *knob)
+                        .zip(values.iter().copied())
+                        .collect(),
+                    config: self.config(values),
+                    score,
+                }
+            })
+            .collect();
+        let sensitivity = self.sensitivity(&trials);
+        Ok(TuningReport {
+            trials,
+            sensitivity,
+        })
+    }
+
+    fn config(&self, values: &[u32]) -> PlanetConfig {
+        let mut config = self.base.clone();
+        for ((knob, _), value) in self.knobs.iter().zip(values) {
+            knob.set(&mut config, *value);
+        }
+        config
+    }
+
+    fn grid(&self) -> Vec<Vec<u32>> {
+        let mut candidates = vec![Vec::new()];
+        for (_, values) in &self.knobs {
+            candidates = candidates
+                .into_iter()
+                .flat_map(|candidate| {
+                    values.iter().map(move |value| {
+                        let mut next = candidate.clone();
+                        next.push(*value);
+                        next
+                    })
+                })
+                .collect();
+        }
+        candidates
+    }
+
+    fn sample(&self, trials: usize, seed: u64) -> Vec<Vec<u32>> {
+        let size: usize = self.knobs.iter().map(|(_, values)| values.len()).product();
+        let mut rng = Rng::new(seed);
+        let mut seen = HashSet::new();
+        let mut candidates = Vec::new();
+        while candidates.len() < trials.min(size) {
+            let candidate: Vec<u32> = self
+                .knobs
+                .iter()
+                .map(|(_, values)| values[rng.below(values.len())])
+                .collect();
+            if seen.insert(candidate.clone()) {
+                candidates.push(candidate);
+            }
+        }
+        candidates
+    }
+
+    fn sensitivity(&self, trials: &[Trial]) -> Vec<Sensitivity> {
+        let mut sensitivity: Vec<Sensitivity> = self
+            .knobs
+            .iter()
+            .enumerate()
+            .map(|(i, (knob, values))| {
+                let means: Vec<(u32, f64)> = values
+                    .iter()
+                    .filter_map(|value| {
+                        let scores: Vec<f64> = trials
+                            .iter()
+                            .filter(|trial| trial.values[i].1 == *value)
+                            .map(|trial| trial.score.score)
+                            .collect();
+                        (!scores.is_empty())
+                            .then(|| (*value, scores.iter().sum::<f64>() / scores.len() as f64))
+                    })
+                    .collect();
+                let best = means.iter().map(|(_, mean)| *mean).fold(f64::MIN, f64::max);
+                let worst = means.iter().map(|(_, mean)| *mean).fold(f64::MAX, f64::min);
+                Sensitivity {
+                    knob: *knob,
+                    means,
+                    spread: best - worst,
+                }
+            })
+            .collect();
+        sensitivity.sort_by(|a, b| b.spread.total_cmp(&a.spread));
+        sensitivity
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use common_game::utils::ID;
+
+/// Tuning of the asteroid warnings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarningConfig {
+    /// How long a warning can be delivered after the asteroid hit.
+    pub ttl: Duration,
+    /// How many warnings are kept in the history.
+    pub history: usize,
+}
+
+impl Default for WarningConfig {
+    fn default() -> Self {
+        WarningConfig {
+            ttl: Duration::from_secs(30),
+            history: 32,
+        }
+    }
+}
+
+/// Warning issued when an asteroid hits the planet without a rocket.
+///
+/// It is delivered to each target with its next `SupportedCombinationResponse`,
+/// see [`CovertConfig`](crate::CovertConfig), until it expires or a later
+/// asteroid is deflected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Sequence number, starting from 0.
+    pub id: u32,
+    pub created: Instant,
+    pub ttl: Duration,
+    /// Explorers on the planet and allowed to read the warning when it was issued, sorted by ID.
+    pub targets: Vec<ID>,
+    /// Targets that received the warning, in delivery order.
+    pub delivered: Vec<ID>,
+    /// Warned targets that left the planet afterwards.
+    pub acknowledged: Vec<ID>,
+    /// A later asteroid was deflected, so the warning is moot.
+    pub cancelled: bool,
+}
+
+impl Warning {
+    pub fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.created) > self.ttl
+    }
+
+    /// Whether the warning can still be delivered.
+    pub fn is_active(&self, now: Instant) -> bool {
+        !self.cancelled && !self.is_expired(now)
+    }
+
+    /// Fraction of the targets that were warned, 1 without targets.
+    pub fn delivery_rate(&self) -> f64 {
+        if self.targets.is_empty() {
+            return 1.0;
+        }
+        self.delivered.len() as f64 / self.targets.len() as f64
+    }
+
+    fn awaits(&self, explorer_id: ID) -> bool {
+        self.targets.contains(&explorer_id) && !self.delivered.contains(&explorer_id)
+    }
+}
+
+/// Warnings issued by the [`PlanetAI`](crate::PlanetAI), the newest last.
+pub(crate) struct WarningBook {
+    config: WarningConfig,
+    next_id: u32,
+    warnings: VecDeque<Warning>,
+}
+
+impl WarningBook {
+    pub(crate) fn new(config: WarningConfig) -> WarningBook {
+        WarningBook {
+            config,
+            next_id: 0,
+            warnings: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn issue(&mut self, mut targets: Vec<ID>, now: Instant) {
+        targets.sort_unstable();
+        if self.warnings.len() >= self.config.history.max(1) {
+            self.warnings.pop_front();
+        }
+        self.warnings.push_back(Warning {
+            id: self.next_id,
+            created: now,
+            ttl: self.config.ttl,
+            targets,
+            delivered: Vec::new(),
+            acknowledged: Vec::new(),
+            cancelled: false,
+        });
+        self.next_id += 1;
+    }
+
+    /// Cancels the active warnings, after a rocket saved the planet.
+    pub(crate) fn cancel(&mut self, now: Instant) {
+        for warning in self.warnings.iter_mut() {
+            if warning.is_active(now) {
+                warning.cancelled = true;
+            }
+        }
+    }
+
+    /// Marks the active warnings awaited by the explorer as delivered.
+    ///
+    /// Returns whether the explorer has to be warned.
+    pub(crate) fn deliver(&mut self, explorer_id: ID, now: Instant) -> bool {
+        let mut warned = false;
+        for warning in self.warnings.iter_mut() {
+            if warning.is_active(now) && warning.awaits(explorer_id) {
+                warning.delivered.push(explorer_id);
+                warned = true;
+            }
+        }
+        warned
+    }
+
+    pub(crate) fn departed(&mut self, explorer_id: ID) {
+        for warning in self.warnings.iter_mut() {
+            if warning.delivered.contains(&explorer_id)
+                && !warning.acknowledged.contains(&explorer_id)
+            {
+                warning.acknowledged.push(explorer_id);
+            }
+        }
+    }
+
+    /// Whether an asteroid was announced and no rocket has answered it yet.
+    pub(crate) fn active(&self, now: Instant) -> bool {
+        self.warnings.iter().any(|warning| warning.is_active(now))
+    }
+
+    /// Whether an active warning still awaits some target.
+    pub(crate) fn pending(&self, now: Instant) -> bool {
+        self.warnings.iter().any(|warning| {
+            warning.is_active(now) && warning.delivered.len() < warning.targets.len()
+        })
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<Warning> {
+        self.warnings.iter().cloned().collect()
+    }
+}